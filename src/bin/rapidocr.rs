@@ -1,12 +1,18 @@
 use std::{
     env, fs,
+    io::{self, Write},
     path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use rapid_ocr_rs::{
-    EngineConfig, LangRec, LoadImage, OcrInput, OcrResult, ProviderPreference, RapidOcrEngine,
-    RunOptions,
+    CacheEntryStatus, CachedModel, DownloadEvent, DownloadProgress, EngineConfig, LangRec,
+    LoadImage, ModelCache, ModelDownloader, ModelRegistry, ModelType, OcrInput, OcrResult,
+    OcrVersion, ProviderPreference, RapidOcrEngine, RunOptions, default_model_store_dir,
 };
 
 const CHECK_IMG_URL: &str = "https://www.modelscope.cn/models/RapidAI/RapidOCR/resolve/v3.1.0/resources/test_files/ch_en_num.jpg";
@@ -25,6 +31,7 @@ fn run_main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Run(args) => run_cmd(args),
         Commands::Config(args) => config_cmd(args),
         Commands::Check => check_cmd(),
+        Commands::Models(args) => models_cmd(args),
     }
 }
 
@@ -44,6 +51,7 @@ enum Commands {
     Run(RunArgs),
     Config(ConfigArgs),
     Check,
+    Models(ModelsArgs),
 }
 
 #[derive(Debug, Args, Clone)]
@@ -82,6 +90,8 @@ struct RunArgs {
     no_return_single_char_box: bool,
     #[arg(long, value_parser = parse_f32_unit_interval)]
     box_thresh: Option<f32>,
+    #[arg(long, value_name = "K")]
+    char_top_k: Option<usize>,
     #[arg(long, value_parser = parse_positive_f32)]
     unclip_ratio: Option<f32>,
     #[arg(long, alias = "vis")]
@@ -90,7 +100,10 @@ struct RunArgs {
     vis_word: bool,
     #[arg(long, default_value = ".")]
     vis_save_dir: PathBuf,
-    #[arg(long, value_enum)]
+    #[cfg(feature = "searchable-pdf")]
+    #[arg(long)]
+    output_pdf: Option<PathBuf>,
+    #[arg(long, value_enum, alias = "format")]
     output_format: Option<OutputFormat>,
     #[arg(long, conflicts_with = "markdown")]
     json: bool,
@@ -115,6 +128,46 @@ struct ConfigInitArgs {
     output: PathBuf,
 }
 
+#[derive(Debug, Args)]
+struct ModelsArgs {
+    // Defaults to `RAPID_OCR_MODEL_DIR` or the per-user cache directory.
+    #[arg(long, global = true)]
+    dir: Option<PathBuf>,
+    // Extra registry manifest consulted before the compiled-in one.
+    #[arg(long, global = true)]
+    manifest: Option<PathBuf>,
+    #[command(subcommand)]
+    command: ModelsCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ModelsCommand {
+    List,
+    Prefetch(ModelsPrefetchArgs),
+    Verify,
+    Prune(ModelsPruneArgs),
+}
+
+#[derive(Debug, Args)]
+struct ModelsPrefetchArgs {
+    #[arg(long = "config")]
+    config_path: Option<PathBuf>,
+    #[arg(long, value_parser = parse_ocr_version)]
+    ocr_version: Option<OcrVersion>,
+    #[arg(long = "lang-type", alias = "lang", value_parser = parse_lang)]
+    lang_type: Option<LangRec>,
+    #[arg(long, value_parser = parse_model_type)]
+    model_type: Option<ModelType>,
+}
+
+#[derive(Debug, Args)]
+struct ModelsPruneArgs {
+    #[arg(long)]
+    dry_run: bool,
+    #[arg(long)]
+    unregistered: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 enum ProviderCli {
     Cpu,
@@ -128,6 +181,9 @@ enum OutputFormat {
     Summary,
     Json,
     Markdown,
+    Text,
+    Hocr,
+    Alto,
 }
 
 fn run_cmd(cli: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -188,7 +244,7 @@ fn run_cmd(cli: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let mut engine = RapidOcrEngine::new(cfg)?;
+    let mut engine = RapidOcrEngine::with_download_progress(cfg, Arc::new(CliProgress::default()))?;
     let input = parse_input(&img_path);
     let run_opts = RunOptions {
         use_det: cli.use_det,
@@ -202,11 +258,27 @@ fn run_cmd(cli: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         text_score: cli.text_score,
         box_thresh: cli.box_thresh,
         unclip_ratio: cli.unclip_ratio,
+        char_top_k: cli.char_top_k,
+        ..RunOptions::default()
     };
 
     let use_word_boxes = cli.vis_word || run_opts.return_word_box.unwrap_or(false);
     let out = engine.run(input.clone(), run_opts)?;
     let output_format = resolve_output_format(cli.output_format, cli.json, cli.markdown)?;
+    let vis_enabled = cli.vis || cli.vis_word;
+    #[cfg(feature = "searchable-pdf")]
+    let wants_pdf = cli.output_pdf.is_some();
+    #[cfg(not(feature = "searchable-pdf"))]
+    let wants_pdf = false;
+    let needs_image = vis_enabled
+        || wants_pdf
+        || matches!(output_format, OutputFormat::Hocr | OutputFormat::Alto);
+    let image = if needs_image {
+        Some(LoadImage.load(input)?)
+    } else {
+        None
+    };
+
     match output_format {
         OutputFormat::Summary => print_result_summary(&out),
         OutputFormat::Json => {
@@ -216,12 +288,35 @@ fn run_cmd(cli: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         OutputFormat::Markdown => {
             println!("{}", out.to_markdown()?);
         }
+        OutputFormat::Text => {
+            println!("{}", out.to_text()?);
+        }
+        OutputFormat::Hocr | OutputFormat::Alto => {
+            let image = image.as_ref().ok_or("missing input image")?;
+            let (width, height) = (image.width(), image.height());
+            if output_format == OutputFormat::Hocr {
+                print!("{}", out.to_hocr(width, height)?);
+            } else {
+                print!("{}", out.to_alto(width, height)?);
+            }
+        }
+    }
+
+    let Some(image) = image else {
+        return Ok(());
+    };
+    #[cfg(feature = "searchable-pdf")]
+    if let Some(pdf_path) = &cli.output_pdf {
+        if let Some(parent) = pdf_path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(pdf_path, out.to_searchable_pdf(&image)?)?;
+        println!("The searchable pdf has saved in {}", pdf_path.display());
     }
 
-    let vis_enabled = cli.vis || cli.vis_word;
     if vis_enabled {
-        let loader = LoadImage;
-        let image = loader.load(input)?;
         if let Some(vis_img) = out.visualize(&image, use_word_boxes) {
             fs::create_dir_all(&cli.vis_save_dir)?;
             let stem = infer_stem(&img_path);
@@ -266,6 +361,144 @@ fn check_cmd() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn models_cmd(args: ModelsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = ModelRegistry::with_manifest(args.manifest.as_deref())?.with_downloader(
+        ModelDownloader::default().with_progress(Arc::new(CliProgress::default())),
+    );
+    let cache =
+        ModelCache::with_registry(args.dir.unwrap_or_else(default_model_store_dir), registry);
+    println!("model dir: {}", cache.dir().display());
+    match args.command {
+        ModelsCommand::List => print_cached_models(&cache.list()?),
+        ModelsCommand::Prefetch(prefetch) => {
+            let mut cfg = if let Some(path) = &prefetch.config_path {
+                EngineConfig::from_yaml_file(path)?
+            } else {
+                EngineConfig::default()
+            };
+            if let Some(version) = prefetch.ocr_version {
+                cfg.det.ocr_version = version;
+                cfg.cls.ocr_version = version;
+                cfg.rec.model.ocr_version = version;
+            }
+            if let Some(model_type) = prefetch.model_type {
+                cfg.det.model_type = model_type;
+                cfg.cls.model_type = model_type;
+                cfg.rec.model.model_type = model_type;
+            }
+            if let Some(lang) = prefetch.lang_type {
+                cfg.rec.model.lang = lang;
+            }
+            for path in cache.prefetch(&cfg)? {
+                println!("{}", path.display());
+            }
+        }
+        ModelsCommand::Verify => {
+            let entries = cache.verify()?;
+            print_cached_models(&entries);
+            let failed = entries
+                .iter()
+                .filter(|e| matches!(e.status, CacheEntryStatus::Mismatch { .. }))
+                .count();
+            if failed > 0 {
+                return Err(format!("{failed} cached model(s) failed SHA256 verification").into());
+            }
+        }
+        ModelsCommand::Prune(prune) => {
+            let removed = cache.prune(prune.unregistered, prune.dry_run)?;
+            let verb = if prune.dry_run {
+                "would remove"
+            } else {
+                "removed"
+            };
+            for entry in &removed {
+                println!("{verb} {} ({})", entry.file_name, entry.status.as_str());
+            }
+            println!("{} file(s) {verb}", removed.len());
+        }
+    }
+    Ok(())
+}
+
+// Renders downloads on stderr, one line per file, redrawn on each whole percent.
+#[derive(Default)]
+struct CliProgress {
+    last_percent: AtomicU64,
+}
+
+impl DownloadProgress for CliProgress {
+    fn on_event(&self, event: &DownloadEvent<'_>) {
+        let mut stderr = io::stderr();
+        match *event {
+            DownloadEvent::Started {
+                file_name,
+                url,
+                resumed_from,
+                ..
+            } => {
+                self.last_percent.store(u64::MAX, Ordering::Relaxed);
+                if resumed_from > 0 {
+                    let _ = writeln!(
+                        stderr,
+                        "resuming {file_name} at {resumed_from} bytes ({url})"
+                    );
+                } else {
+                    let _ = writeln!(stderr, "downloading {file_name} ({url})");
+                }
+            }
+            DownloadEvent::Progress {
+                file_name,
+                downloaded,
+                total,
+            } => {
+                let Some(total) = total.filter(|total| *total > 0) else {
+                    return;
+                };
+                let percent = downloaded * 100 / total;
+                if self.last_percent.swap(percent, Ordering::Relaxed) != percent {
+                    let _ = write!(stderr, "\r{file_name}: {percent:>3}% of {total} bytes");
+                    let _ = stderr.flush();
+                }
+            }
+            DownloadEvent::Retrying {
+                url,
+                attempt,
+                delay,
+                error,
+            } => {
+                let _ = writeln!(
+                    stderr,
+                    "\n{url}: {error}; retry {attempt} in {:.1}s",
+                    delay.as_secs_f32()
+                );
+            }
+            DownloadEvent::Finished { .. } => {
+                let _ = writeln!(stderr);
+            }
+            DownloadEvent::Unverified { url } => {
+                let _ = writeln!(stderr, "warning: {url} has no SHA256 and is not verified");
+            }
+        }
+    }
+}
+
+fn print_cached_models(entries: &[CachedModel]) {
+    if entries.is_empty() {
+        println!("No cached models.");
+    }
+    for entry in entries {
+        println!(
+            "{:<12} {:>12}  {}",
+            entry.status.as_str(),
+            entry.size,
+            entry.file_name
+        );
+        if let CacheEntryStatus::Mismatch { expected, actual } = &entry.status {
+            println!("{:<12} expected {expected}, got {actual}", "");
+        }
+    }
+}
+
 fn normalize_legacy_args<I, S>(args: I) -> Vec<std::ffi::OsString>
 where
     I: IntoIterator<Item = S>,
@@ -341,6 +574,26 @@ fn parse_lang(value: &str) -> Result<LangRec, String> {
     })
 }
 
+fn parse_ocr_version(value: &str) -> Result<OcrVersion, String> {
+    Ok(match value.to_ascii_lowercase().as_str() {
+        "pp-ocrv4" | "ppocrv4" | "v4" => OcrVersion::PPocrV4,
+        "pp-ocrv5" | "ppocrv5" | "v5" => OcrVersion::PPocrV5,
+        "pp-ocrv6" | "ppocrv6" | "v6" => OcrVersion::PPocrV6,
+        _ => return Err(format!("unsupported --ocr-version value `{value}`")),
+    })
+}
+
+fn parse_model_type(value: &str) -> Result<ModelType, String> {
+    Ok(match value.to_ascii_lowercase().as_str() {
+        "mobile" => ModelType::Mobile,
+        "server" => ModelType::Server,
+        "tiny" => ModelType::Tiny,
+        "small" => ModelType::Small,
+        "medium" => ModelType::Medium,
+        _ => return Err(format!("unsupported --model-type value `{value}`")),
+    })
+}
+
 fn resolve_flag_pair(enable: bool, disable: bool) -> Option<bool> {
     if enable {
         Some(true)
@@ -360,7 +613,7 @@ fn resolve_output_format(
     if json {
         if format != OutputFormat::Summary && format != OutputFormat::Json {
             return Err(
-                "output format flags conflict; choose exactly one of summary/json/markdown".into(),
+                "output format flags conflict; choose exactly one of summary/json/markdown/text/hocr/alto".into(),
             );
         }
         format = OutputFormat::Json;
@@ -368,7 +621,7 @@ fn resolve_output_format(
     if markdown {
         if format != OutputFormat::Summary && format != OutputFormat::Markdown {
             return Err(
-                "output format flags conflict; choose exactly one of summary/json/markdown".into(),
+                "output format flags conflict; choose exactly one of summary/json/markdown/text/hocr/alto".into(),
             );
         }
         format = OutputFormat::Markdown;
@@ -462,8 +715,9 @@ fn infer_stem(img_path: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{Cli, Commands, ProviderCli, normalize_legacy_args};
+    use super::{Cli, Commands, ModelsCommand, OutputFormat, ProviderCli, normalize_legacy_args};
     use clap::Parser;
+    use rapid_ocr_rs::{LangRec, ModelType, OcrVersion};
 
     fn parse_cli(input: &[&str]) -> Result<Cli, clap::Error> {
         let mut args = vec!["rapidocr".to_string()];
@@ -543,4 +797,85 @@ mod tests {
         };
         assert_eq!(run.img_path.as_deref(), Some("test.png"));
     }
+
+    #[cfg(feature = "searchable-pdf")]
+    #[test]
+    fn parse_run_cli_output_pdf_path() {
+        let cli = parse_cli(&[
+            "run",
+            "--img-path",
+            "test.png",
+            "--output-pdf",
+            "out/test.pdf",
+        ])
+        .expect("cli parse should pass");
+        let Commands::Run(run) = cli.command else {
+            panic!("expected run command");
+        };
+        assert_eq!(
+            run.output_pdf.as_deref(),
+            Some(std::path::Path::new("out/test.pdf"))
+        );
+    }
+
+    #[test]
+    fn parse_run_cli_format_alias_selects_hocr_and_alto() {
+        for (value, expected) in [("hocr", OutputFormat::Hocr), ("alto", OutputFormat::Alto)] {
+            let cli = parse_cli(&["run", "--img-path", "test.png", "--format", value])
+                .expect("cli parse should pass");
+            let Commands::Run(run) = cli.command else {
+                panic!("expected run command");
+            };
+            assert_eq!(run.output_format, Some(expected));
+        }
+    }
+
+    #[test]
+    fn parse_run_cli_char_top_k() {
+        let cli = parse_cli(&["run", "--img-path", "test.png", "--char-top-k", "3"])
+            .expect("cli parse should pass");
+        let Commands::Run(run) = cli.command else {
+            panic!("expected run command");
+        };
+        assert_eq!(run.char_top_k, Some(3));
+    }
+
+    #[test]
+    fn parse_models_prefetch_and_prune() {
+        let cli = parse_cli(&[
+            "models",
+            "prefetch",
+            "--ocr-version",
+            "PP-OCRv5",
+            "--lang",
+            "en",
+            "--model-type",
+            "server",
+            "--dir",
+            "/srv/models",
+        ])
+        .expect("cli parse should pass");
+        let Commands::Models(models) = cli.command else {
+            panic!("expected models command");
+        };
+        assert_eq!(
+            models.dir.as_deref(),
+            Some(std::path::Path::new("/srv/models"))
+        );
+        let ModelsCommand::Prefetch(prefetch) = models.command else {
+            panic!("expected prefetch command");
+        };
+        assert_eq!(prefetch.ocr_version, Some(OcrVersion::PPocrV5));
+        assert_eq!(prefetch.lang_type, Some(LangRec::En));
+        assert_eq!(prefetch.model_type, Some(ModelType::Server));
+
+        let cli = parse_cli(&["models", "prune", "--dry-run"]).expect("cli parse should pass");
+        let Commands::Models(models) = cli.command else {
+            panic!("expected models command");
+        };
+        let ModelsCommand::Prune(prune) = models.command else {
+            panic!("expected prune command");
+        };
+        assert!(prune.dry_run && !prune.unregistered);
+    }
 }
@@ -0,0 +1,6 @@
+// `std::time::Instant::now` panics on wasm32-unknown-unknown; web-time reads
+// `performance.now()` there and is `std::time::Instant` everywhere else.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
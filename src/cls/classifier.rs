@@ -1,14 +1,15 @@
-use std::{path::PathBuf, time::Instant};
+use std::path::PathBuf;
 
 use ndarray::ArrayView4;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::{LangCls, ModelType, OcrVersion, RecImage, RuntimeConfig, VisionBackend},
+    clock::Instant,
+    config::{LangCls, ModelSource, ModelType, OcrVersion, RecImage, RuntimeConfig, VisionBackend},
     error::{RapidOcrError, Result},
     model_registry::ModelRegistry,
-    model_store::{default_model_store_dir, ensure_downloaded, verify_existing_file},
+    model_store::default_model_store_dir,
     runtime::provider::ProviderResolution,
     runtime::session::{OrtSession, SessionContract},
     vision::backend::resolve_backend_strict,
@@ -23,7 +24,9 @@ pub struct ClassifierConfig {
     pub lang: LangCls,
     pub ocr_version: OcrVersion,
     pub model_type: ModelType,
-    pub model_path: Option<PathBuf>,
+    // `model_path` is the legacy key for a file path.
+    #[serde(alias = "model_path")]
+    pub model_source: ModelSource,
     pub allow_download: bool,
     pub runtime: RuntimeConfig,
     pub cls_image_shape: [usize; 3],
@@ -39,7 +42,7 @@ impl Default for ClassifierConfig {
             lang: LangCls::Ch,
             ocr_version: OcrVersion::PPocrV4,
             model_type: ModelType::Mobile,
-            model_path: None,
+            model_source: ModelSource::Registry,
             allow_download: true,
             runtime: RuntimeConfig::default(),
             cls_image_shape: [3, 48, 192],
@@ -62,11 +65,10 @@ pub struct Classifier {
     config: ClassifierConfig,
     vision_backend: VisionBackend,
     session: OrtSession,
-    batch_scratch: Vec<f32>,
 }
 
 impl Classifier {
-    pub fn new(config: ClassifierConfig) -> Result<Self> {
+    pub fn new(config: ClassifierConfig, registry: &ModelRegistry) -> Result<Self> {
         if config.cls_batch_num == 0 {
             return Err(RapidOcrError::Config(
                 "cls_batch_num must be greater than zero".to_string(),
@@ -78,35 +80,35 @@ impl Classifier {
             .clone()
             .unwrap_or_else(default_model_store_dir);
 
-        let model_path = if let Some(path) = &config.model_path {
-            verify_existing_file(path)?
-        } else if config.allow_download {
-            let registry = ModelRegistry::from_default_yaml()?;
-            let resolved =
-                registry.resolve_cls(config.ocr_version, config.lang, config.model_type)?;
-            ensure_downloaded(
-                &resolved.model_url,
-                resolved.sha256.as_deref(),
-                model_store_dir,
-            )?
-        } else {
-            return Err(RapidOcrError::Config(
-                "classifier model_path is not set and allow_download=false".to_string(),
-            ));
-        };
+        let session = OrtSession::from_source(
+            &config.model_source,
+            &config.runtime,
+            SessionContract::Cls,
+            || {
+                let resolved =
+                    registry.resolve_cls(config.ocr_version, config.lang, config.model_type)?;
+                registry.fetch_model(
+                    &resolved.model_url,
+                    resolved.sha256.as_deref(),
+                    model_store_dir,
+                    config.allow_download,
+                )
+            },
+        )?;
 
-        let session =
-            OrtSession::new_with_contract(&model_path, &config.runtime, SessionContract::Cls)?;
         let vision_backend = resolve_backend_strict(config.runtime.vision_backend)?;
         Ok(Self {
             vision_backend,
             config,
             session,
-            batch_scratch: Vec::new(),
         })
     }
 
-    pub fn classify_in_place(&mut self, images: &mut [RecImage]) -> Result<ClsInPlaceOutput> {
+    pub fn classify_in_place(
+        &self,
+        images: &mut [RecImage],
+        batch_scratch: &mut Vec<f32>,
+    ) -> Result<ClsInPlaceOutput> {
         let start = Instant::now();
         if images.is_empty() {
             return Ok(ClsInPlaceOutput::default());
@@ -137,11 +139,11 @@ impl Classifier {
             let total_len = sample_len.checked_mul(batch_size).ok_or_else(|| {
                 RapidOcrError::InvalidInput("cls batch size overflow".to_string())
             })?;
-            self.batch_scratch.resize(total_len, 0.0);
+            batch_scratch.resize(total_len, 0.0);
 
             let batch_indices = &indices[beg..end];
             if batch_size > 1 {
-                self.batch_scratch[..total_len]
+                batch_scratch[..total_len]
                     .par_chunks_mut(sample_len)
                     .zip(batch_indices.par_iter().copied())
                     .try_for_each_init(
@@ -165,14 +167,14 @@ impl Classifier {
                     &images[sorted_idx],
                     self.config.cls_image_shape,
                     self.vision_backend,
-                    &mut self.batch_scratch[..sample_len],
+                    &mut batch_scratch[..sample_len],
                     &mut tmp_bgr,
                     &mut resize_scratch,
                 )?;
             }
 
             let batch_view =
-                ArrayView4::from_shape((batch_size, img_c, img_h, img_w), &self.batch_scratch)
+                ArrayView4::from_shape((batch_size, img_c, img_h, img_w), &batch_scratch[..])
                     .map_err(|e| {
                         RapidOcrError::InvalidInput(format!("invalid cls batch tensor shape: {e}"))
                     })?;
@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+
+use ndarray::ArrayView4;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{ModelSource, RecImage, RuntimeConfig, VisionBackend},
+    error::{RapidOcrError, Result},
+    model_registry::ModelRegistry,
+    model_store::default_model_store_dir,
+    runtime::provider::ProviderResolution,
+    runtime::session::{OrtSession, SessionContract},
+    vision::{backend::resolve_backend_strict, image_backend::resize_image},
+};
+
+use super::postprocess;
+
+// ImageNet statistics in RGB order, as used by the PP-LCNet classifiers.
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DocOrientationConfig {
+    // `model_path` is the legacy key for a file path.
+    #[serde(alias = "model_path")]
+    pub model_source: ModelSource,
+    pub allow_download: bool,
+    pub runtime: RuntimeConfig,
+    pub resize_short: usize,
+    pub image_size: usize,
+    pub thresh: f32,
+    pub label_list: Vec<String>,
+    pub model_store_dir: Option<PathBuf>,
+}
+
+impl Default for DocOrientationConfig {
+    fn default() -> Self {
+        Self {
+            model_source: ModelSource::Registry,
+            allow_download: true,
+            runtime: RuntimeConfig::default(),
+            resize_short: 256,
+            image_size: 224,
+            thresh: 0.5,
+            label_list: ["0", "90", "180", "270"]
+                .iter()
+                .map(|v| (*v).to_string())
+                .collect(),
+            model_store_dir: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DocOrientationClassifier {
+    config: DocOrientationConfig,
+    vision_backend: VisionBackend,
+    session: OrtSession,
+}
+
+impl DocOrientationClassifier {
+    pub fn new(config: DocOrientationConfig, registry: &ModelRegistry) -> Result<Self> {
+        let model_store_dir = config
+            .model_store_dir
+            .clone()
+            .unwrap_or_else(default_model_store_dir);
+
+        let session = OrtSession::from_source(
+            &config.model_source,
+            &config.runtime,
+            SessionContract::Cls,
+            || {
+                let resolved = registry.resolve_doc_ori()?;
+                registry.fetch_model(
+                    &resolved.model_url,
+                    resolved.sha256.as_deref(),
+                    model_store_dir,
+                    config.allow_download,
+                )
+            },
+        )?;
+
+        let vision_backend = resolve_backend_strict(config.runtime.vision_backend)?;
+        Ok(Self {
+            config,
+            vision_backend,
+            session,
+        })
+    }
+
+    // Returns the clockwise angle the page content is rotated by, or 0 when the
+    // prediction is below `thresh`.
+    pub fn predict(&self, img: &RecImage) -> Result<(u32, f32)> {
+        let size = self.config.image_size;
+        let input = preprocess(img, self.config.resize_short, size, self.vision_backend)?;
+        let view = ArrayView4::from_shape((1, 3, size, size), &input[..]).map_err(|e| {
+            RapidOcrError::InvalidInput(format!("invalid doc orientation tensor shape: {e}"))
+        })?;
+        let label_list = &self.config.label_list;
+        let decoded = self
+            .session
+            .run_array2_view_with(view, |preds| postprocess::decode_view(preds, label_list))?;
+        let Some((label, score)) = decoded.into_iter().next() else {
+            return Err(RapidOcrError::Decode(
+                "doc orientation model returned no prediction".to_string(),
+            ));
+        };
+        if score < self.config.thresh {
+            return Ok((0, score));
+        }
+        let angle = parse_angle(&label).ok_or_else(|| {
+            RapidOcrError::Decode(format!("unsupported doc orientation label `{label}`"))
+        })?;
+        Ok((angle, score))
+    }
+
+    pub fn provider_resolution(&self) -> ProviderResolution {
+        self.session.provider_resolution()
+    }
+}
+
+pub(crate) fn parse_angle(label: &str) -> Option<u32> {
+    match label.trim().parse::<u32>().ok()? {
+        angle @ (0 | 90 | 180 | 270) => Some(angle),
+        _ => None,
+    }
+}
+
+// Resize the short side to `resize_short`, center-crop `size`x`size`, then
+// normalize to an RGB CHW tensor.
+fn preprocess(
+    img: &RecImage,
+    resize_short: usize,
+    size: usize,
+    backend: VisionBackend,
+) -> Result<Vec<f32>> {
+    let scale = resize_short as f32 / img.width().min(img.height()).max(1) as f32;
+    let new_w = ((img.width() as f32 * scale).round() as usize).max(size);
+    let new_h = ((img.height() as f32 * scale).round() as usize).max(size);
+    let resized = resize_image(img, new_w, new_h, backend)?;
+    let left = (new_w - size) / 2;
+    let top = (new_h - size) / 2;
+
+    let bgr = resized.as_bgr_cow();
+    let bgr = bgr.as_ref();
+    let plane = size * size;
+    let mut out = vec![0.0_f32; 3 * plane];
+    for y in 0..size {
+        for x in 0..size {
+            let src = ((top + y) * new_w + left + x) * 3;
+            for c in 0..3 {
+                let value = bgr[src + 2 - c] as f32 / 255.0;
+                out[c * plane + y * size + x] = (value - MEAN[c]) / STD[c];
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cls::doc_orientation::{parse_angle, preprocess},
+        config::{RecImage, VisionBackend},
+    };
+
+    #[test]
+    fn preprocess_center_crops_to_square_rgb_tensor() {
+        // Pure red in BGR, wider than tall.
+        let img = RecImage::from_bgr_u8(8, 4, [0, 0, 255].repeat(32)).expect("valid image");
+        let out = preprocess(&img, 4, 4, VisionBackend::PureRust).expect("preprocess");
+        assert_eq!(out.len(), 3 * 16);
+        assert!((out[0] - (1.0 - 0.485) / 0.229).abs() < 1e-5);
+        assert!((out[16] - (0.0 - 0.456) / 0.224).abs() < 1e-5);
+    }
+
+    #[test]
+    fn only_quarter_turn_labels_parse() {
+        assert_eq!(parse_angle("270"), Some(270));
+        assert_eq!(parse_angle("45"), None);
+        assert_eq!(parse_angle("up"), None);
+    }
+}
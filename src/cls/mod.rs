@@ -1,3 +1,4 @@
 pub mod classifier;
+pub mod doc_orientation;
 pub mod postprocess;
 pub mod preprocess;
@@ -1,6 +1,8 @@
 use std::{
     borrow::Cow,
+    fmt,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use image::ImageReader;
@@ -174,8 +176,101 @@ pub enum ProviderPreference {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum RuntimeBackend {
-    #[default]
+    #[cfg_attr(feature = "onnxruntime", default)]
     OnnxCpu,
+    // Pure-Rust inference; needs the `tract` feature. The default when built
+    // without `onnxruntime`.
+    #[cfg_attr(not(feature = "onnxruntime"), default)]
+    Tract,
+}
+
+// Where a stage loads its ONNX model from. In YAML it reads `registry` or
+// `{ path: ... }`; a bare path, as the legacy `model_path` key takes, is a
+// `Path` too. `Bytes` covers models embedded with `include_bytes!` or handed
+// over by a host without a filesystem; it is set from code only and cannot be
+// written to YAML.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum ModelSource {
+    #[default]
+    Registry,
+    Path(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+impl ModelSource {
+    pub fn is_registry(&self) -> bool {
+        matches!(self, Self::Registry)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ModelSourceRepr {
+    Path { path: PathBuf },
+    // `registry`, a bare path, or null for the registry.
+    Plain(Option<PathBuf>),
+}
+
+impl Serialize for ModelSource {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Registry => serializer.serialize_str("registry"),
+            Self::Path(path) => ModelSourceRepr::Path { path: path.clone() }.serialize(serializer),
+            Self::Bytes(_) => Err(serde::ser::Error::custom(
+                "a model source of in-memory bytes cannot be serialized",
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelSource {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Ok(match ModelSourceRepr::deserialize(deserializer)? {
+            ModelSourceRepr::Path { path } => Self::Path(path),
+            ModelSourceRepr::Plain(None) => Self::Registry,
+            ModelSourceRepr::Plain(Some(path)) if path.as_os_str() == "registry" => Self::Registry,
+            ModelSourceRepr::Plain(Some(path)) => Self::Path(path),
+        })
+    }
+}
+
+impl From<PathBuf> for ModelSource {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for ModelSource {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for ModelSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl From<&[u8]> for ModelSource {
+    fn from(bytes: &[u8]) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl fmt::Debug for ModelSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Registry => f.write_str("Registry"),
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,19 +279,35 @@ pub struct ModelConfig {
     pub lang: LangRec,
     pub ocr_version: OcrVersion,
     pub model_type: ModelType,
-    pub model_path: Option<PathBuf>,
+    // `model_path` is the legacy key for a file path.
+    #[serde(alias = "model_path")]
+    pub model_source: ModelSource,
     pub rec_keys_path: Option<PathBuf>,
+    // Dictionary text, one character per line; takes the place of
+    // `rec_keys_path` and is only read when the model embeds no characters.
+    pub rec_keys_text: Option<String>,
     pub allow_download: bool,
 }
 
+impl ModelConfig {
+    // Drops every explicit model and dictionary so `lang` picks the registry
+    // entry.
+    pub(crate) fn use_registry(&mut self) {
+        self.model_source = ModelSource::Registry;
+        self.rec_keys_path = None;
+        self.rec_keys_text = None;
+    }
+}
+
 impl Default for ModelConfig {
     fn default() -> Self {
         Self {
             lang: LangRec::default(),
             ocr_version: OcrVersion::default(),
             model_type: ModelType::default(),
-            model_path: None,
+            model_source: ModelSource::Registry,
             rec_keys_path: None,
+            rec_keys_text: None,
             allow_download: true,
         }
     }
@@ -210,6 +321,11 @@ pub struct RuntimeConfig {
     pub inter_threads: Option<usize>,
     pub auto_tune_threads: bool,
     pub rayon_threads: Option<usize>,
+    // Most ONNX Runtime sessions a stage keeps for concurrent runs, each one a
+    // full copy of the model. Defaults to 1: concurrent callers share a single
+    // session and take turns. Raise it to trade memory for parallel runs;
+    // `null` lifts the limit so the pool grows with the callers.
+    pub max_sessions: Option<usize>,
     pub enable_cpu_mem_arena: bool,
     pub fail_if_provider_unavailable: bool,
     pub provider_preference: ProviderPreference,
@@ -224,6 +340,7 @@ impl Default for RuntimeConfig {
             inter_threads: None,
             auto_tune_threads: true,
             rayon_threads: None,
+            max_sessions: Some(1),
             enable_cpu_mem_arena: true,
             fail_if_provider_unavailable: false,
             provider_preference: ProviderPreference::default(),
@@ -240,6 +357,8 @@ pub struct RecognizerConfig {
     pub rec_batch_num: usize,
     pub rec_img_shape: [usize; 3],
     pub model_store_dir: Option<PathBuf>,
+    pub decode: CtcDecodeConfig,
+    pub correction: CorrectionConfig,
 }
 
 impl Default for RecognizerConfig {
@@ -250,6 +369,46 @@ impl Default for RecognizerConfig {
             rec_batch_num: 6,
             rec_img_shape: [3, 48, 320],
             model_store_dir: None,
+            decode: CtcDecodeConfig::default(),
+            correction: CorrectionConfig::default(),
+        }
+    }
+}
+
+// Greedy argmax unless `beam_width > 1`, `pattern` or a word list is set.
+// `allowed_chars`/`forbidden_chars` mask classes before the argmax (or beam
+// extension); `pattern` must match the whole line; `word_list`/`word_list_path`
+// restrict the line to space-separated words from the list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CtcDecodeConfig {
+    pub beam_width: usize,
+    pub pattern: Option<String>,
+    pub allowed_chars: Option<String>,
+    pub forbidden_chars: Option<String>,
+    pub word_list: Vec<String>,
+    pub word_list_path: Option<PathBuf>,
+}
+
+// Word-frequency post-correction, enabled by `word_freq_path`. Only characters
+// below `min_char_prob` are reconsidered, at most `max_edits` per word, among
+// their `top_k` decoder alternatives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorrectionConfig {
+    pub word_freq_path: Option<PathBuf>,
+    pub min_char_prob: f32,
+    pub max_edits: usize,
+    pub top_k: usize,
+}
+
+impl Default for CorrectionConfig {
+    fn default() -> Self {
+        Self {
+            word_freq_path: None,
+            min_char_prob: 0.9,
+            max_edits: 2,
+            top_k: 3,
         }
     }
 }
@@ -258,6 +417,9 @@ impl Default for RecognizerConfig {
 pub struct RecognizeOptions {
     pub return_word_box: bool,
     pub return_single_char_box: bool,
+    // Per-character details with up to this many alternatives each.
+    #[serde(default)]
+    pub char_top_k: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -374,9 +536,12 @@ impl RecImage {
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use std::{borrow::Cow, path::PathBuf};
 
-    use super::{ColorOrder, RecImage, RuntimeBackend, RuntimeConfig, VisionBackend};
+    use super::{
+        ColorOrder, ModelConfig, ModelSource, RecImage, RuntimeBackend, RuntimeConfig,
+        VisionBackend,
+    };
 
     #[test]
     fn rec_image_rejects_zero_dimension() {
@@ -390,7 +555,10 @@ mod tests {
     #[test]
     fn runtime_config_default_backend_matches_feature() {
         let cfg = RuntimeConfig::default();
+        #[cfg(feature = "onnxruntime")]
         assert_eq!(cfg.backend, RuntimeBackend::OnnxCpu);
+        #[cfg(not(feature = "onnxruntime"))]
+        assert_eq!(cfg.backend, RuntimeBackend::Tract);
         assert!(cfg.auto_tune_threads);
         assert_eq!(cfg.rayon_threads, None);
         assert!(cfg.enable_cpu_mem_arena);
@@ -401,6 +569,58 @@ mod tests {
         assert_eq!(cfg.vision_backend, VisionBackend::PureRust);
     }
 
+    #[test]
+    fn runtime_backend_names_round_trip() {
+        let cfg: RuntimeConfig =
+            serde_yaml::from_str("backend: tract\n").expect("tract backend should parse");
+        assert_eq!(cfg.backend, RuntimeBackend::Tract);
+        let cfg: RuntimeConfig =
+            serde_yaml::from_str("backend: onnx_cpu\n").expect("onnx backend should parse");
+        assert_eq!(cfg.backend, RuntimeBackend::OnnxCpu);
+    }
+
+    #[test]
+    fn model_source_reads_legacy_model_path_and_round_trips() {
+        let cfg: ModelConfig = serde_yaml::from_str("model_path: /models/rec.onnx\n")
+            .expect("model_path should parse");
+        assert_eq!(
+            cfg.model_source,
+            ModelSource::Path(PathBuf::from("/models/rec.onnx"))
+        );
+        let cfg: ModelConfig =
+            serde_yaml::from_str("model_path: null\n").expect("null model_path should parse");
+        assert_eq!(cfg.model_source, ModelSource::Registry);
+
+        for source in [
+            ModelSource::Registry,
+            ModelSource::Path(PathBuf::from("/models/rec.onnx")),
+        ] {
+            let cfg = ModelConfig {
+                model_source: source.clone(),
+                ..ModelConfig::default()
+            };
+            let yaml = serde_yaml::to_string(&cfg).expect("serialize");
+            let parsed: ModelConfig = serde_yaml::from_str(&yaml).expect("round trip");
+            assert_eq!(parsed.model_source, source, "{yaml}");
+        }
+        let cfg: ModelConfig =
+            serde_yaml::from_str("model_source:\n  path: rec.onnx\n").expect("path form");
+        assert_eq!(
+            cfg.model_source,
+            ModelSource::Path(PathBuf::from("rec.onnx"))
+        );
+
+        let both = "model_path: a.onnx\nmodel_source: registry\n";
+        assert!(serde_yaml::from_str::<ModelConfig>(both).is_err());
+
+        let cfg = ModelConfig {
+            model_source: ModelSource::from(vec![0u8; 4]),
+            ..ModelConfig::default()
+        };
+        assert_eq!(format!("{:?}", cfg.model_source), "Bytes(4 bytes)");
+        assert!(serde_yaml::to_string(&cfg).is_err());
+    }
+
     #[test]
     fn as_bgr_cow_borrows_for_bgr_images() {
         let image =
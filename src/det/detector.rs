@@ -1,14 +1,15 @@
-use std::{path::PathBuf, time::Instant};
+use std::path::PathBuf;
 
 use ndarray::{ArrayView4, Axis, s};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     Quad,
-    config::{LangDet, ModelType, OcrVersion, RecImage, RuntimeConfig},
+    clock::Instant,
+    config::{LangDet, ModelSource, ModelType, OcrVersion, RecImage, RuntimeConfig},
     error::{RapidOcrError, Result},
     model_registry::ModelRegistry,
-    model_store::{default_model_store_dir, ensure_downloaded, verify_existing_file},
+    model_store::default_model_store_dir,
     runtime::provider::ProviderResolution,
     runtime::session::{OrtSession, SessionContract},
     vision::backend::resolve_backend_strict,
@@ -25,7 +26,9 @@ pub struct DetectorConfig {
     pub lang: LangDet,
     pub ocr_version: OcrVersion,
     pub model_type: ModelType,
-    pub model_path: Option<PathBuf>,
+    // `model_path` is the legacy key for a file path.
+    #[serde(alias = "model_path")]
+    pub model_source: ModelSource,
     pub allow_download: bool,
     pub runtime: RuntimeConfig,
     pub limit_side_len: usize,
@@ -47,7 +50,7 @@ impl Default for DetectorConfig {
             lang: LangDet::Ch,
             ocr_version: OcrVersion::PPocrV4,
             model_type: ModelType::Mobile,
-            model_path: None,
+            model_source: ModelSource::Registry,
             allow_download: true,
             runtime: RuntimeConfig::default(),
             limit_side_len: 736,
@@ -80,38 +83,41 @@ pub struct DetTimingBreakdown {
     pub postprocess_ms: f32,
 }
 
+#[derive(Debug, Default)]
+pub struct DetScratch {
+    batch: Vec<f32>,
+    preprocess: DetPreprocessScratch,
+}
+
 #[derive(Debug)]
 pub struct Detector {
     pre: DetPreProcess,
     post: DbPostProcess,
     session: OrtSession,
-    batch_scratch: Vec<f32>,
-    preprocess_scratch: DetPreprocessScratch,
 }
 
 impl Detector {
-    pub fn new(config: DetectorConfig) -> Result<Self> {
+    pub fn new(config: DetectorConfig, registry: &ModelRegistry) -> Result<Self> {
         let model_store_dir = config
             .model_store_dir
             .clone()
             .unwrap_or_else(default_model_store_dir);
 
-        let model_path = if let Some(path) = &config.model_path {
-            verify_existing_file(path)?
-        } else if config.allow_download {
-            let registry = ModelRegistry::from_default_yaml()?;
-            let resolved =
-                registry.resolve_det(config.ocr_version, config.lang, config.model_type)?;
-            ensure_downloaded(
-                &resolved.model_url,
-                resolved.sha256.as_deref(),
-                model_store_dir,
-            )?
-        } else {
-            return Err(RapidOcrError::Config(
-                "detector model_path is not set and allow_download=false".to_string(),
-            ));
-        };
+        let session = OrtSession::from_source(
+            &config.model_source,
+            &config.runtime,
+            SessionContract::Det,
+            || {
+                let resolved =
+                    registry.resolve_det(config.ocr_version, config.lang, config.model_type)?;
+                registry.fetch_model(
+                    &resolved.model_url,
+                    resolved.sha256.as_deref(),
+                    model_store_dir,
+                    config.allow_download,
+                )
+            },
+        )?;
 
         let det_vision_backend = resolve_backend_strict(config.runtime.vision_backend)?;
 
@@ -132,18 +138,39 @@ impl Detector {
             vision_backend: pre.vision_backend,
             ..DbPostProcess::default()
         };
-        let session =
-            OrtSession::new_with_contract(&model_path, &config.runtime, SessionContract::Det)?;
-        Ok(Self {
-            pre,
-            post,
-            session,
-            batch_scratch: Vec::new(),
-            preprocess_scratch: DetPreprocessScratch::default(),
-        })
+        Ok(Self { pre, post, session })
+    }
+
+    pub fn detect(&self, img: &RecImage, scratch: &mut DetScratch) -> Result<DetOutput> {
+        self.detect_with_post(img, &self.post, scratch)
+    }
+
+    pub fn detect_with_overrides(
+        &self,
+        img: &RecImage,
+        box_thresh: Option<f32>,
+        unclip_ratio: Option<f32>,
+        scratch: &mut DetScratch,
+    ) -> Result<DetOutput> {
+        if box_thresh.is_none() && unclip_ratio.is_none() {
+            return self.detect(img, scratch);
+        }
+        let mut post = self.post.clone();
+        if let Some(v) = box_thresh {
+            post.box_thresh = v;
+        }
+        if let Some(v) = unclip_ratio {
+            post.unclip_ratio = v;
+        }
+        self.detect_with_post(img, &post, scratch)
     }
 
-    pub fn detect(&mut self, img: &RecImage) -> Result<DetOutput> {
+    fn detect_with_post(
+        &self,
+        img: &RecImage,
+        post: &DbPostProcess,
+        scratch: &mut DetScratch,
+    ) -> Result<DetOutput> {
         let start = Instant::now();
         // `DBPostProcess` expects destination size in the detector input image space
         // (before detector-side resize), matching RapidOCR Python behavior.
@@ -155,17 +182,16 @@ impl Detector {
         let pre_start = Instant::now();
         let (resized_h, resized_w) = self.pre.run_into_buffer_with_scratch(
             img,
-            &mut self.batch_scratch,
-            &mut self.preprocess_scratch,
+            &mut scratch.batch,
+            &mut scratch.preprocess,
             Some(det_limit_side_len),
         )?;
         let preprocess_ms = pre_start.elapsed().as_secs_f32() * 1000.0;
         let batch_view = ArrayView4::from_shape(
             (1, 3, resized_h, resized_w),
-            &self.batch_scratch[..3 * resized_h * resized_w],
+            &scratch.batch[..3 * resized_h * resized_w],
         )
         .map_err(|e| RapidOcrError::InvalidInput(format!("invalid det batch shape: {e}")))?;
-        let post = &self.post;
         let infer_start = Instant::now();
         let mut postprocess_ms = 0.0_f32;
         let (boxes, scores) = self.session.run_array4_view_with(batch_view, |preds| {
@@ -193,15 +219,6 @@ impl Detector {
         })
     }
 
-    pub fn update_postprocess(&mut self, box_thresh: Option<f32>, unclip_ratio: Option<f32>) {
-        if let Some(v) = box_thresh {
-            self.post.box_thresh = v;
-        }
-        if let Some(v) = unclip_ratio {
-            self.post.unclip_ratio = v;
-        }
-    }
-
     pub fn provider_resolution(&self) -> ProviderResolution {
         self.session.provider_resolution()
     }
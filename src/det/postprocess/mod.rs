@@ -1,4 +1,6 @@
+#[cfg(feature = "clipper")]
 use geo_clipper::{ClipperInt, EndType as ClipperEndType, JoinType as ClipperJoinType};
+#[cfg(feature = "clipper")]
 use geo_types::{Coord, LineString, Polygon};
 #[cfg(test)]
 use ndarray::Array2;
@@ -1033,7 +1035,7 @@ fn order_min_box_points_like_python(points: Quad) -> Quad {
     ]
 }
 
-fn mini_box_from_points_pure(points: &[[f32; 2]]) -> Option<(Quad, f32)> {
+pub(crate) fn mini_box_from_points_pure(points: &[[f32; 2]]) -> Option<(Quad, f32)> {
     if points.len() < 3 {
         return None;
     }
@@ -1216,24 +1218,87 @@ fn box_score_fast_opencv(bitmap: &Mat, box_points: &[[f32; 2]]) -> opencv::Resul
     Ok(mean[0] as f32)
 }
 
-fn unclip_polygon_pyclipper_into(in_poly: &[[f32; 2]], unclip_ratio: f32, out: &mut Vec<[f32; 2]>) {
-    out.clear();
+// Match Python/shapely numeric behavior (double precision) when computing
+// offset distance for pyclipper.
+fn unclip_distance(in_poly: &[[f32; 2]], unclip_ratio: f32) -> Option<f64> {
     if in_poly.len() < 3 {
-        return;
+        return None;
     }
-
-    // Match Python/shapely numeric behavior (double precision) when computing
-    // offset distance for pyclipper.
     let area = polygon_area_f64(in_poly).abs();
     let length = polygon_perimeter_f64(in_poly);
     if !area.is_finite() || !length.is_finite() || length <= 1e-6 {
-        return;
+        return None;
     }
-
     let distance = area * f64::from(unclip_ratio) / length;
-    if !distance.is_finite() {
+    distance.is_finite().then_some(distance)
+}
+
+#[cfg(not(feature = "clipper"))]
+fn unclip_polygon_pyclipper_into(in_poly: &[[f32; 2]], unclip_ratio: f32, out: &mut Vec<[f32; 2]>) {
+    unclip_polygon_round_hull_into(in_poly, unclip_ratio, out);
+}
+
+// Stand-in for the pyclipper offset in builds without `clipper` (e.g. wasm).
+// Callers only take the min-area box of the result, i.e. of its convex hull,
+// and the hull of a round-joined offset is the hull of a circle around every
+// vertex. Arcs use pyclipper's step count for `ArcTolerance` 0.25 but start
+// at angle 0 rather than at each edge normal, so box corners can land up to
+// `ROUND_HULL_MAX_DRIFT` px from the `clipper` build.
+#[cfg(all(test, feature = "clipper"))]
+const ROUND_HULL_MAX_DRIFT: f32 = 1.0;
+
+#[cfg(any(test, not(feature = "clipper")))]
+fn unclip_polygon_round_hull_into(
+    in_poly: &[[f32; 2]],
+    unclip_ratio: f32,
+    out: &mut Vec<[f32; 2]>,
+) {
+    out.clear();
+    let Some(distance) = unclip_distance(in_poly, unclip_ratio) else {
         return;
+    };
+    if distance <= 0.0 {
+        return;
+    }
+
+    let arc_tolerance = (distance * 0.25).min(0.25);
+    let steps = (std::f64::consts::PI / (1.0 - arc_tolerance / distance).acos())
+        .min(distance * std::f64::consts::PI)
+        .ceil()
+        .max(4.0) as usize;
+    // Pyclipper consumes integer coordinates truncated toward zero.
+    let ring: Vec<(f64, f64)> = in_poly
+        .iter()
+        .map(|p| ((p[0] as f64).trunc(), (p[1] as f64).trunc()))
+        .collect();
+    for (i, &(x, y)) in ring.iter().enumerate() {
+        for step in 0..steps {
+            let angle = std::f64::consts::TAU * step as f64 / steps as f64;
+            out.push([
+                (x + distance * angle.cos()).round() as f32,
+                (y + distance * angle.sin()).round() as f32,
+            ]);
+        }
+        // Offset edge endpoints, as clipper emits them; both sides are used
+        // since the inner ones fall inside the hull anyway.
+        let (nx, ny) = ring[(i + 1) % ring.len()];
+        let len = (nx - x).hypot(ny - y);
+        if len > 0.0 {
+            let (ox, oy) = (-(ny - y) / len * distance, (nx - x) / len * distance);
+            for (px, py) in [(x, y), (nx, ny)] {
+                out.push([(px + ox).round() as f32, (py + oy).round() as f32]);
+                out.push([(px - ox).round() as f32, (py - oy).round() as f32]);
+            }
+        }
     }
+}
+
+#[cfg(feature = "clipper")]
+fn unclip_polygon_pyclipper_into(in_poly: &[[f32; 2]], unclip_ratio: f32, out: &mut Vec<[f32; 2]>) {
+    out.clear();
+    let Some(distance) = unclip_distance(in_poly, unclip_ratio) else {
+        return;
+    };
 
     // Pyclipper consumes integer coordinates. Float points are truncated toward zero.
     let mut ring: Vec<Coord<i64>> = in_poly
@@ -2323,7 +2388,7 @@ fn filter_det_res(
     let mut out_boxes = Vec::with_capacity(dt_boxes.len());
     let mut out_scores = Vec::with_capacity(scores.len());
 
-    for (box_, score) in dt_boxes.into_iter().zip(scores.into_iter()) {
+    for (box_, score) in dt_boxes.into_iter().zip(scores) {
         let mut box_ = order_points_clockwise(box_);
         box_ = clip_det_res(box_, img_height, img_width);
 
@@ -2369,7 +2434,11 @@ fn order_points_clockwise(pts: Quad) -> Quad {
     [tl, tr, br, bl]
 }
 
-fn sort_boxes_like_python(boxes: &mut Vec<Quad>, scores: &mut Vec<f32>, y_threshold: f32) {
+pub(crate) fn sort_boxes_like_python(
+    boxes: &mut Vec<Quad>,
+    scores: &mut Vec<f32>,
+    y_threshold: f32,
+) {
     if boxes.is_empty() {
         return;
     }
@@ -2446,6 +2515,8 @@ mod tests {
         dilate_mask_2x2, fill_polygon_mask, masked_mean_in_roi, min_area_rect_from_points_pure,
         sort_boxes_like_python, unclip_polygon_like_opencv_db,
     };
+    #[cfg(feature = "clipper")]
+    use super::{ROUND_HULL_MAX_DRIFT, mini_box_from_points_pure, unclip_polygon_round_hull_into};
     use crate::config::VisionBackend;
     use ndarray::Array2;
 
@@ -2530,6 +2601,36 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "clipper")]
+    fn round_hull_unclip_matches_pyclipper_box() {
+        let quads = [
+            [[12.4_f32, 30.2], [118.9, 18.7], [122.3, 49.6], [15.8, 61.1]],
+            [[3.0, 3.0], [9.0, 3.0], [9.0, 8.0], [3.0, 8.0]],
+            [[40.7, 10.2], [612.5, 12.9], [611.8, 44.1], [40.1, 41.3]],
+            [[200.0, 20.0], [260.0, 80.0], [240.0, 100.0], [180.0, 40.0]],
+        ];
+        for quad in quads {
+            for ratio in [1.5_f32, 1.6, 2.0] {
+                let mut clipper = Vec::new();
+                super::unclip_polygon_pyclipper_into(&quad, ratio, &mut clipper);
+                let mut hull = Vec::new();
+                unclip_polygon_round_hull_into(&quad, ratio, &mut hull);
+
+                let (clipper_box, clipper_side) = mini_box_from_points_pure(&clipper).expect("box");
+                let (hull_box, hull_side) = mini_box_from_points_pure(&hull).expect("box");
+                assert!((clipper_side - hull_side).abs() <= ROUND_HULL_MAX_DRIFT);
+                for (a, b) in clipper_box.iter().zip(hull_box.iter()) {
+                    assert!(
+                        (a[0] - b[0]).abs() <= ROUND_HULL_MAX_DRIFT
+                            && (a[1] - b[1]).abs() <= ROUND_HULL_MAX_DRIFT,
+                        "{quad:?} x{ratio}: {clipper_box:?} vs {hull_box:?}"
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn unclip_polygon_like_opencv_db_expands_square() {
         let square = [
@@ -25,6 +25,12 @@ pub enum RapidOcrError {
     #[error("decoding failed: {0}")]
     Decode(String),
 
+    #[error("ocr run cancelled")]
+    Cancelled,
+
+    #[error("ocr run exceeded its deadline")]
+    Timeout,
+
     #[error("unsupported provider for v1: {0}")]
     UnsupportedProvider(String),
 
@@ -34,12 +40,18 @@ pub enum RapidOcrError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
+    #[cfg(feature = "onnxruntime")]
     #[error(transparent)]
     Ort(#[from] ort::Error),
 
+    #[cfg(feature = "tract")]
+    #[error("tract inference failed: {0}")]
+    Tract(#[from] tract_onnx::prelude::TractError),
+
     #[error(transparent)]
     Yaml(#[from] serde_yaml::Error),
 
+    #[cfg(feature = "download")]
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
@@ -2,11 +2,15 @@ use std::{fs, io::Cursor, path::PathBuf};
 
 use exif::{In, Reader as ExifReader, Tag};
 use image::{DynamicImage, GrayImage, ImageBuffer, LumaA, RgbImage, RgbaImage};
+#[cfg(feature = "turbojpeg")]
 use turbojpeg::{PixelFormat, decompress};
 
+#[cfg(feature = "pdf")]
+use crate::input::pdf::{PdfInput, PdfPages};
 use crate::{
     config::RecImage,
     error::{RapidOcrError, Result},
+    input::multi_frame::{decode_frames, frame_count},
 };
 
 #[derive(Debug, Clone)]
@@ -50,6 +54,61 @@ pub enum OcrInput {
         data: Vec<u8>,
     },
     Image(RecImage),
+    #[cfg(feature = "pdf")]
+    Pdf(PdfInput),
+}
+
+#[derive(Debug, Clone)]
+pub struct PageImage {
+    pub index: usize,
+    // `None` when the page was skipped because it already carries a text layer.
+    pub image: Option<RecImage>,
+    // PDF points per image pixel (`72 / dpi`) for rendered PDF pages.
+    pub points_per_pixel: Option<f32>,
+}
+
+// The pages of an input, yielded in order. PDF pages are rendered as they are
+// reached; other inputs are decoded up front.
+pub struct PageImages {
+    inner: PageSource,
+}
+
+enum PageSource {
+    Decoded(std::vec::IntoIter<PageImage>),
+    #[cfg(feature = "pdf")]
+    Pdf(Box<PdfPages>),
+}
+
+impl PageImages {
+    fn decoded(pages: Vec<PageImage>) -> Self {
+        Self {
+            inner: PageSource::Decoded(pages.into_iter()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            PageSource::Decoded(pages) => pages.len(),
+            #[cfg(feature = "pdf")]
+            PageSource::Pdf(pages) => pages.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Iterator for PageImages {
+    type Item = Result<PageImage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            PageSource::Decoded(pages) => pages.next().map(Ok),
+            #[cfg(feature = "pdf")]
+            PageSource::Pdf(pages) => pages.next(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -60,7 +119,7 @@ impl LoadImage {
         match input {
             OcrInput::Path(path) => self.load_path(path),
             OcrInput::Url(url) => self.load_url(&url),
-            OcrInput::Bytes(bytes) => self.decode_bytes_with_exif(&bytes, false),
+            OcrInput::Bytes(bytes) => self.decode_single_page(&bytes, false),
             OcrInput::Bgr {
                 width,
                 height,
@@ -112,27 +171,70 @@ impl LoadImage {
                 RecImage::from_bgr_u8(width, height, rgba_to_bgr(width, height, &data))
             }
             OcrInput::Image(image) => Ok(image),
+            #[cfg(feature = "pdf")]
+            OcrInput::Pdf(pdf) => single_page_image(PdfPages::new(pdf)?),
         }
     }
 
+    pub fn load_pages(&self, input: OcrInput) -> Result<PageImages> {
+        let pages = match input {
+            #[cfg(feature = "pdf")]
+            OcrInput::Pdf(pdf) => {
+                return Ok(PageImages {
+                    inner: PageSource::Pdf(Box::new(PdfPages::new(pdf)?)),
+                });
+            }
+            OcrInput::Path(path) => self.decode_pages(&read_path(path)?, true)?,
+            OcrInput::Url(url) => self.decode_pages(&fetch_url(&url)?, true)?,
+            OcrInput::Bytes(bytes) => self.decode_pages(&bytes, false)?,
+            other => vec![PageImage {
+                index: 0,
+                image: Some(self.load(other)?),
+                points_per_pixel: None,
+            }],
+        };
+        Ok(PageImages::decoded(pages))
+    }
+
     fn load_path(&self, path: PathBuf) -> Result<RecImage> {
-        if !path.exists() {
-            return Err(RapidOcrError::FileNotFound(path));
-        }
-        let bytes = fs::read(path)?;
-        self.decode_bytes_with_exif(&bytes, true)
+        self.decode_single_page(&read_path(path)?, true)
     }
 
     fn load_url(&self, url: &str) -> Result<RecImage> {
-        let response = reqwest::blocking::get(url)?;
-        if !response.status().is_success() {
-            return Err(RapidOcrError::Download(format!(
-                "failed to fetch image from url {url}: HTTP {}",
-                response.status()
+        self.decode_single_page(&fetch_url(url)?, true)
+    }
+
+    // Multi-page inputs are rejected here, as PDFs with several pages are, rather
+    // than silently reading only the first page.
+    // The pages are counted before anything is decoded.
+    fn decode_single_page(&self, bytes: &[u8], apply_exif_transpose: bool) -> Result<RecImage> {
+        if let Some(count) = frame_count(bytes)?
+            && count > 1
+        {
+            return Err(RapidOcrError::InvalidInput(format!(
+                "input yields {count} pages; use run_pages"
             )));
         }
-        let bytes = response.bytes()?;
-        self.decode_bytes_with_exif(bytes.as_ref(), true)
+        match decode_frames(bytes)? {
+            None => self.decode_bytes_with_exif(bytes, apply_exif_transpose),
+            Some(mut frames) => Ok(frames.remove(0)),
+        }
+    }
+
+    fn decode_pages(&self, bytes: &[u8], apply_exif_transpose: bool) -> Result<Vec<PageImage>> {
+        let images = match decode_frames(bytes)? {
+            Some(frames) => frames,
+            None => vec![self.decode_bytes_with_exif(bytes, apply_exif_transpose)?],
+        };
+        Ok(images
+            .into_iter()
+            .enumerate()
+            .map(|(index, image)| PageImage {
+                index,
+                image: Some(image),
+                points_per_pixel: None,
+            })
+            .collect())
     }
 
     fn decode_bytes_with_exif(&self, bytes: &[u8], apply_exif_transpose: bool) -> Result<RecImage> {
@@ -143,6 +245,7 @@ impl LoadImage {
         };
 
         // Match Python PIL JPEG decode path as closely as possible for non-oriented images.
+        #[cfg(feature = "turbojpeg")]
         if orientation.unwrap_or(1) == 1
             && let Ok(img) = decode_bytes_with_turbojpeg(bytes)
         {
@@ -158,6 +261,50 @@ impl LoadImage {
     }
 }
 
+fn read_path(path: PathBuf) -> Result<Vec<u8>> {
+    if !path.exists() {
+        return Err(RapidOcrError::FileNotFound(path));
+    }
+    Ok(fs::read(path)?)
+}
+
+#[cfg(not(feature = "download"))]
+fn fetch_url(url: &str) -> Result<Vec<u8>> {
+    Err(RapidOcrError::Download(format!(
+        "cannot fetch {url}: built without the `download` feature"
+    )))
+}
+
+#[cfg(feature = "download")]
+fn fetch_url(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::blocking::get(url)?;
+    if !response.status().is_success() {
+        return Err(RapidOcrError::Download(format!(
+            "failed to fetch image from url {url}: HTTP {}",
+            response.status()
+        )));
+    }
+    Ok(response.bytes()?.to_vec())
+}
+
+#[cfg(feature = "pdf")]
+fn single_page_image(mut pages: PdfPages) -> Result<RecImage> {
+    let page_count = pages.len();
+    if page_count != 1 {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "input yields {page_count} pages; select a single page or use run_pages"
+        )));
+    }
+    match pages.next().transpose()? {
+        Some(PageImage {
+            image: Some(image), ..
+        }) => Ok(image),
+        _ => Err(RapidOcrError::InvalidInput(
+            "the selected pdf page was skipped because it carries a text layer".to_string(),
+        )),
+    }
+}
+
 fn exif_orientation_from_bytes(bytes: &[u8]) -> Option<u32> {
     let mut cursor = Cursor::new(bytes);
     let exif = ExifReader::new().read_from_container(&mut cursor).ok()?;
@@ -186,6 +333,7 @@ fn apply_exif_orientation(img: DynamicImage, orientation: Option<u32>) -> Dynami
     }
 }
 
+#[cfg(feature = "turbojpeg")]
 fn decode_bytes_with_turbojpeg(bytes: &[u8]) -> Result<RecImage> {
     if !looks_like_jpeg(bytes) {
         return Err(RapidOcrError::InvalidImage("not a jpeg stream".to_string()));
@@ -220,11 +368,12 @@ fn decode_bytes_with_turbojpeg(bytes: &[u8]) -> Result<RecImage> {
     RecImage::from_bgr_u8(width, height, bgr)
 }
 
+#[cfg(feature = "turbojpeg")]
 fn looks_like_jpeg(bytes: &[u8]) -> bool {
     bytes.len() >= 3 && bytes[0] == 0xFF && bytes[1] == 0xD8 && bytes[2] == 0xFF
 }
 
-fn dynamic_to_rec_image(img: DynamicImage) -> Result<RecImage> {
+pub(crate) fn dynamic_to_rec_image(img: DynamicImage) -> Result<RecImage> {
     match img {
         DynamicImage::ImageLuma8(gray) => RecImage::from_bgr_u8(
             gray.width() as usize,
@@ -359,9 +508,75 @@ fn ensure_len(width: usize, height: usize, channels: usize, actual_len: usize) -
 
 #[cfg(test)]
 mod tests {
-    use image::{DynamicImage, RgbImage};
+    use std::io::Cursor;
+
+    use image::{Delay, DynamicImage, Frame, RgbImage, RgbaImage, codecs::gif::GifEncoder};
+    use tiff::encoder::{TiffEncoder, colortype};
 
     use super::{LoadImage, OcrInput, exif_transpose_from_bytes};
+    use crate::{config::RecImage, error::RapidOcrError};
+
+    fn two_page_tiff() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = TiffEncoder::new(Cursor::new(&mut bytes)).expect("tiff encoder");
+        encoder
+            .write_image::<colortype::Gray8>(3, 2, &[10; 6])
+            .expect("first page");
+        encoder
+            .write_image::<colortype::Gray8>(2, 4, &[20; 8])
+            .expect("second page");
+        bytes
+    }
+
+    #[test]
+    fn multi_page_input_is_rejected_by_load_and_expanded_by_load_pages() {
+        let err = LoadImage
+            .load(OcrInput::Bytes(two_page_tiff()))
+            .expect_err("two pages do not fit one image");
+        assert!(matches!(err, RapidOcrError::InvalidInput(msg) if msg.contains("2 pages")));
+
+        // Pages without image data are still counted and rejected, since
+        // nothing is decoded first.
+        let mut empty_pages = b"II*\0\x08\0\0\0".to_vec();
+        empty_pages.extend_from_slice(&[0, 0]);
+        empty_pages.extend_from_slice(&14_u32.to_le_bytes());
+        empty_pages.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        let err = LoadImage
+            .load(OcrInput::Bytes(empty_pages))
+            .expect_err("two pages do not fit one image");
+        assert!(matches!(err, RapidOcrError::InvalidInput(msg) if msg.contains("2 pages")));
+
+        let pages = LoadImage
+            .load_pages(OcrInput::Bytes(two_page_tiff()))
+            .expect("tiff pages")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("decoded pages");
+        assert_eq!(
+            pages
+                .iter()
+                .map(|p| (p.index, p.image.as_ref().map(RecImage::width)))
+                .collect::<Vec<_>>(),
+            vec![(0, Some(3)), (1, Some(2))]
+        );
+    }
+
+    #[test]
+    fn single_frame_gif_loads_as_an_image() {
+        let mut bytes = Vec::new();
+        GifEncoder::new(&mut bytes)
+            .encode_frames([Frame::from_parts(
+                RgbaImage::from_pixel(2, 1, image::Rgba([0, 0, 255, 255])),
+                0,
+                0,
+                Delay::from_numer_denom_ms(100, 1),
+            )])
+            .expect("gif frame");
+        let image = LoadImage
+            .load(OcrInput::Bytes(bytes))
+            .expect("one frame is one page");
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.as_bgr_cow()[..3], [255, 0, 0]);
+    }
 
     #[test]
     fn gray_alpha_input_is_supported() {
@@ -405,6 +620,19 @@ mod tests {
         assert_eq!(image.height(), 1);
     }
 
+    #[test]
+    fn single_image_input_loads_as_one_page() {
+        let image = RecImage::from_bgr_u8(2, 1, vec![0; 6]).expect("valid image");
+        let pages = LoadImage
+            .load_pages(OcrInput::Image(image))
+            .expect("image input should load");
+        let pages = pages.collect::<Result<Vec<_>, _>>().expect("decoded pages");
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].index, 0);
+        assert_eq!(pages[0].image.as_ref().map(RecImage::width), Some(2));
+        assert_eq!(pages[0].points_per_pixel, None);
+    }
+
     #[test]
     fn exif_transpose_is_noop_when_exif_is_missing() {
         let rgb = RgbImage::from_raw(2, 1, vec![255, 0, 0, 0, 255, 0]).expect("valid rgb image");
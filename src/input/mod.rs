@@ -1 +1,4 @@
 pub mod image_loader;
+pub mod multi_frame;
+#[cfg(feature = "pdf")]
+pub mod pdf;
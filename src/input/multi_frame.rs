@@ -0,0 +1,406 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, Cursor, Read, Seek, SeekFrom},
+};
+
+use image::{
+    AnimationDecoder, DynamicImage, ImageFormat,
+    codecs::{gif::GifDecoder, tiff::TiffDecoder, webp::WebPDecoder},
+};
+
+use crate::{
+    config::RecImage,
+    error::{RapidOcrError, Result},
+    input::image_loader::dynamic_to_rec_image,
+};
+
+// Guards against IFD chains that loop or never terminate in corrupt files.
+const MAX_TIFF_PAGES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct TiffLayout {
+    little_endian: bool,
+    big_tiff: bool,
+}
+
+// Returns `None` for a single still image that the regular decode path
+// (including EXIF handling) should read. A GIF is always decoded here, even
+// with one frame, since telling it apart means decoding it anyway.
+pub(crate) fn decode_frames(bytes: &[u8]) -> Result<Option<Vec<RecImage>>> {
+    let frames = match image::guess_format(bytes) {
+        Ok(ImageFormat::Tiff) => {
+            let (layout, offsets) = tiff_page_offsets(bytes)?;
+            if offsets.len() < 2 {
+                return Ok(None);
+            }
+            decode_tiff_pages(bytes, layout, offsets)?
+        }
+        Ok(ImageFormat::Gif) => {
+            let decoder = GifDecoder::new(Cursor::new(bytes)).map_err(invalid_image)?;
+            decode_animation(decoder)?
+        }
+        Ok(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes)).map_err(invalid_image)?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            decode_animation(decoder)?
+        }
+        _ => return Ok(None),
+    };
+    if frames.is_empty() {
+        return Err(RapidOcrError::InvalidImage(
+            "image holds no frames".to_string(),
+        ));
+    }
+    Ok(Some(frames))
+}
+
+// Counts pages or frames from the container structure alone, without decoding
+// any pixels. `None` for formats that only hold a single image.
+pub(crate) fn frame_count(bytes: &[u8]) -> Result<Option<usize>> {
+    Ok(match image::guess_format(bytes) {
+        Ok(ImageFormat::Tiff) => Some(tiff_page_offsets(bytes)?.1.len()),
+        Ok(ImageFormat::Gif) => Some(gif_frame_count(bytes)),
+        Ok(ImageFormat::WebP) => Some(webp_frame_count(bytes)),
+        _ => None,
+    })
+}
+
+// Walks the GIF blocks up to the trailer. A truncated file counts the frames
+// seen so far and leaves the error to the decoder.
+fn gif_frame_count(bytes: &[u8]) -> usize {
+    // Header and logical screen descriptor, then the global color table.
+    let mut at = 13 + bytes.get(10).map_or(0, |flags| gif_color_table_len(*flags));
+    let mut frames = 0;
+    loop {
+        match bytes.get(at) {
+            // Extension: introducer, label, data sub-blocks.
+            Some(0x21) => at = skip_gif_sub_blocks(bytes, at + 2),
+            // Image descriptor, local color table, LZW code size, data sub-blocks.
+            Some(0x2C) => {
+                frames += 1;
+                let local_table = bytes
+                    .get(at + 9)
+                    .map_or(0, |flags| gif_color_table_len(*flags));
+                at = skip_gif_sub_blocks(bytes, at + 10 + local_table + 1);
+            }
+            _ => return frames,
+        }
+    }
+}
+
+fn gif_color_table_len(flags: u8) -> usize {
+    if flags & 0x80 == 0 {
+        0
+    } else {
+        3 << ((flags & 0x07) + 1)
+    }
+}
+
+fn skip_gif_sub_blocks(bytes: &[u8], mut at: usize) -> usize {
+    while let Some(&len) = bytes.get(at) {
+        at += 1 + usize::from(len);
+        if len == 0 {
+            return at;
+        }
+    }
+    at
+}
+
+// An animated WebP holds one `ANMF` chunk per frame; anything else is a single
+// still image.
+fn webp_frame_count(bytes: &[u8]) -> usize {
+    let mut at = 12;
+    let mut frames = 0;
+    while let Some(header) = bytes.get(at..at + 8) {
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        if &header[..4] == b"ANMF" {
+            frames += 1;
+        }
+        at = at
+            .saturating_add(8)
+            .saturating_add(size)
+            .saturating_add(size & 1);
+    }
+    frames.max(1)
+}
+
+fn decode_animation<'a>(decoder: impl AnimationDecoder<'a>) -> Result<Vec<RecImage>> {
+    let mut out = Vec::new();
+    for frame in decoder.into_frames() {
+        let frame = frame.map_err(invalid_image)?;
+        out.push(dynamic_to_rec_image(DynamicImage::ImageRgba8(
+            frame.into_buffer(),
+        ))?);
+    }
+    Ok(out)
+}
+
+fn decode_tiff_pages(bytes: &[u8], layout: TiffLayout, offsets: Vec<u64>) -> Result<Vec<RecImage>> {
+    let mut out = Vec::with_capacity(offsets.len());
+    for offset in offsets {
+        // `TiffDecoder` only reads the first IFD, so point the header at each page in turn.
+        let reader = TiffPageReader::new(bytes, layout, offset);
+        let decoder = TiffDecoder::new(reader).map_err(invalid_image)?;
+        let img = DynamicImage::from_decoder(decoder).map_err(invalid_image)?;
+        out.push(dynamic_to_rec_image(img)?);
+    }
+    Ok(out)
+}
+
+fn tiff_page_offsets(bytes: &[u8]) -> Result<(TiffLayout, Vec<u64>)> {
+    let little_endian = match bytes.get(..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err(invalid_tiff("missing byte order mark")),
+    };
+    let read_u16 = |at: u64| -> Result<u64> {
+        let b = read_array::<2>(bytes, at)?;
+        Ok(u64::from(if little_endian {
+            u16::from_le_bytes(b)
+        } else {
+            u16::from_be_bytes(b)
+        }))
+    };
+    let read_u32 = |at: u64| -> Result<u64> {
+        let b = read_array::<4>(bytes, at)?;
+        Ok(u64::from(if little_endian {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        }))
+    };
+    let read_u64 = |at: u64| -> Result<u64> {
+        let b = read_array::<8>(bytes, at)?;
+        Ok(if little_endian {
+            u64::from_le_bytes(b)
+        } else {
+            u64::from_be_bytes(b)
+        })
+    };
+
+    let big_tiff = match read_u16(2)? {
+        42 => false,
+        43 => true,
+        other => return Err(invalid_tiff(&format!("unknown version {other}"))),
+    };
+    let mut next = if big_tiff { read_u64(8)? } else { read_u32(4)? };
+
+    let mut offsets = Vec::new();
+    let mut seen = HashSet::new();
+    while next != 0 {
+        if !seen.insert(next) || offsets.len() >= MAX_TIFF_PAGES {
+            return Err(invalid_tiff("IFD chain loops or is too long"));
+        }
+        offsets.push(next);
+        next = if big_tiff {
+            let count = read_u64(next)?;
+            read_u64(next + 8 + count * 20)?
+        } else {
+            let count = read_u16(next)?;
+            read_u32(next + 2 + count * 12)?
+        };
+    }
+    Ok((
+        TiffLayout {
+            little_endian,
+            big_tiff,
+        },
+        offsets,
+    ))
+}
+
+fn read_array<const N: usize>(bytes: &[u8], at: u64) -> Result<[u8; N]> {
+    usize::try_from(at)
+        .ok()
+        .and_then(|start| bytes.get(start..start.checked_add(N)?))
+        .and_then(|slice| slice.try_into().ok())
+        .ok_or_else(|| invalid_tiff("IFD offset out of bounds"))
+}
+
+fn invalid_tiff(reason: &str) -> RapidOcrError {
+    RapidOcrError::InvalidImage(format!("invalid tiff: {reason}"))
+}
+
+fn invalid_image(err: image::ImageError) -> RapidOcrError {
+    RapidOcrError::InvalidImage(err.to_string())
+}
+
+struct TiffPageReader<'a> {
+    bytes: &'a [u8],
+    header: [u8; 16],
+    header_len: usize,
+    pos: usize,
+}
+
+impl<'a> TiffPageReader<'a> {
+    fn new(bytes: &'a [u8], layout: TiffLayout, ifd_offset: u64) -> Self {
+        let header_len = if layout.big_tiff { 16 } else { 8 };
+        let mut header = [0_u8; 16];
+        header[..header_len].copy_from_slice(&bytes[..header_len]);
+        if layout.big_tiff {
+            header[8..16].copy_from_slice(&if layout.little_endian {
+                ifd_offset.to_le_bytes()
+            } else {
+                ifd_offset.to_be_bytes()
+            });
+        } else {
+            // Offsets were read from a 32-bit field, so they always fit.
+            let offset = ifd_offset as u32;
+            header[4..8].copy_from_slice(&if layout.little_endian {
+                offset.to_le_bytes()
+            } else {
+                offset.to_be_bytes()
+            });
+        }
+        Self {
+            bytes,
+            header,
+            header_len,
+            pos: 0,
+        }
+    }
+}
+
+impl BufRead for TiffPageReader<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.pos < self.header_len {
+            return Ok(&self.header[self.pos..self.header_len]);
+        }
+        Ok(self.bytes.get(self.pos..).unwrap_or(&[]))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+impl Read for TiffPageReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl Seek for TiffPageReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(v) => i128::from(v),
+            SeekFrom::End(v) => self.bytes.len() as i128 + i128::from(v),
+            SeekFrom::Current(v) => self.pos as i128 + i128::from(v),
+        };
+        self.pos = usize::try_from(target).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek position")
+        })?;
+        Ok(self.pos as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{Delay, Frame, RgbaImage, codecs::gif::GifEncoder};
+    use tiff::encoder::{TiffEncoder, colortype};
+
+    use super::{decode_frames, frame_count, tiff_page_offsets};
+
+    #[test]
+    fn multi_page_tiff_decodes_every_page() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = TiffEncoder::new(Cursor::new(&mut bytes)).expect("tiff encoder");
+            encoder
+                .write_image::<colortype::Gray8>(3, 2, &[10; 6])
+                .expect("first page");
+            encoder
+                .write_image::<colortype::RGB8>(2, 4, &[200; 24])
+                .expect("second page");
+        }
+
+        let (_, offsets) = tiff_page_offsets(&bytes).expect("valid tiff");
+        assert_eq!(offsets.len(), 2);
+
+        let frames = decode_frames(&bytes)
+            .expect("tiff should decode")
+            .expect("two pages are multi-frame");
+        assert_eq!(
+            frames
+                .iter()
+                .map(|f| (f.width(), f.height()))
+                .collect::<Vec<_>>(),
+            vec![(3, 2), (2, 4)]
+        );
+        assert_eq!(frames[0].as_bgr_cow()[..3], [10, 10, 10]);
+        assert_eq!(frames[1].as_bgr_cow()[..3], [200, 200, 200]);
+    }
+
+    #[test]
+    fn single_page_tiff_keeps_the_regular_decode_path() {
+        let mut bytes = Vec::new();
+        TiffEncoder::new(Cursor::new(&mut bytes))
+            .expect("tiff encoder")
+            .write_image::<colortype::Gray8>(1, 1, &[0])
+            .expect("page");
+        assert!(decode_frames(&bytes).expect("tiff should decode").is_none());
+    }
+
+    #[test]
+    fn animated_gif_decodes_every_frame() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            let frames = (0..3).map(|i| {
+                Frame::from_parts(
+                    RgbaImage::from_pixel(2, 2, image::Rgba([i * 50, 0, 0, 255])),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                )
+            });
+            encoder.encode_frames(frames).expect("gif frames");
+        }
+        assert_eq!(frame_count(&bytes).expect("gif frames"), Some(3));
+        let frames = decode_frames(&bytes)
+            .expect("gif should decode")
+            .expect("three frames are multi-frame");
+        assert_eq!(frames.len(), 3);
+    }
+
+    #[test]
+    fn frames_are_counted_without_decoding_pixels() {
+        // Two IFDs without entries: countable, but not decodable.
+        let mut bytes = b"II*\0\x08\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&14_u32.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        assert_eq!(frame_count(&bytes).expect("tiff pages"), Some(2));
+        assert!(decode_frames(&bytes).is_err());
+
+        // A GIF header with a global color table and two frames whose LZW
+        // data is garbage.
+        let mut gif = b"GIF89a\x02\0\x02\0\x80\0\0".to_vec();
+        gif.extend_from_slice(&[0; 6]);
+        for _ in 0..2 {
+            gif.extend_from_slice(&[0x2C, 0, 0, 0, 0, 2, 0, 2, 0, 0, 2, 3, 0xFF, 0xFF, 0xFF, 0]);
+        }
+        gif.push(0x3B);
+        assert_eq!(frame_count(&gif).expect("gif frames"), Some(2));
+
+        assert_eq!(frame_count(b"\x89PNG\r\n\x1a\n").expect("png"), None);
+    }
+
+    #[test]
+    fn tiff_ifd_loops_are_rejected() {
+        // Header pointing at an IFD with no entries whose next pointer is itself.
+        let mut bytes = b"II*\0\x08\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&8_u32.to_le_bytes());
+        let err = tiff_page_offsets(&bytes).expect_err("loop must be rejected");
+        assert!(err.to_string().contains("loops"));
+    }
+}
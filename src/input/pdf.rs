@@ -0,0 +1,345 @@
+use std::{fs, path::Path, sync::Arc};
+
+use hayro::{InterpreterSettings, Pdf, RenderSettings, render};
+use hayro_syntax::{
+    object::{Dict, Name, Stream},
+    page::Page,
+};
+
+use crate::{
+    config::RecImage,
+    error::{RapidOcrError, Result},
+    input::image_loader::PageImage,
+};
+
+const PDF_POINTS_PER_INCH: f32 = 72.0;
+
+#[derive(Debug, Clone)]
+pub struct PdfInput {
+    pub data: Vec<u8>,
+    // Zero-based page indices; `None` renders every page.
+    pub pages: Option<Vec<usize>>,
+    pub dpi: f32,
+    pub skip_pages_with_text: bool,
+}
+
+impl PdfInput {
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            pages: None,
+            dpi: 200.0,
+            skip_pages_with_text: false,
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(RapidOcrError::FileNotFound(path.to_path_buf()));
+        }
+        Ok(Self::from_bytes(fs::read(path)?))
+    }
+}
+
+// Renders the selected pages one at a time as the iterator is advanced, so a
+// long document never holds more than one rasterized page.
+pub(crate) struct PdfPages {
+    pdf: Pdf,
+    selected: std::vec::IntoIter<usize>,
+    skip_pages_with_text: bool,
+    scale: f32,
+}
+
+impl PdfPages {
+    pub(crate) fn new(input: PdfInput) -> Result<Self> {
+        if !(input.dpi.is_finite() && input.dpi > 0.0) {
+            return Err(RapidOcrError::InvalidInput(format!(
+                "pdf dpi must be > 0, got {}",
+                input.dpi
+            )));
+        }
+
+        let pdf = Pdf::new(Arc::new(input.data))
+            .map_err(|e| RapidOcrError::InvalidInput(format!("failed to parse pdf: {e:?}")))?;
+        let selected = select_pages(input.pages.as_deref(), pdf.pages().len())?;
+        Ok(Self {
+            pdf,
+            selected: selected.into_iter(),
+            skip_pages_with_text: input.skip_pages_with_text,
+            scale: input.dpi / PDF_POINTS_PER_INCH,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    fn render_page(&self, index: usize) -> Result<PageImage> {
+        let page = &self.pdf.pages()[index];
+        let points_per_pixel = Some(1.0 / self.scale);
+        if self.skip_pages_with_text && page_shows_text(page) {
+            return Ok(PageImage {
+                index,
+                image: None,
+                points_per_pixel,
+            });
+        }
+
+        let render_settings = RenderSettings {
+            x_scale: self.scale,
+            y_scale: self.scale,
+            ..RenderSettings::default()
+        };
+        let pixmap = render(page, &InterpreterSettings::default(), &render_settings);
+        let width = pixmap.width() as usize;
+        let height = pixmap.height() as usize;
+        let bgr = premultiplied_rgba_over_white_to_bgr(pixmap.data_as_u8_slice());
+        Ok(PageImage {
+            index,
+            image: Some(RecImage::from_bgr_u8(width, height, bgr)?),
+            points_per_pixel,
+        })
+    }
+}
+
+impl Iterator for PdfPages {
+    type Item = Result<PageImage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.selected.next()?;
+        Some(self.render_page(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.selected.size_hint()
+    }
+}
+
+fn select_pages(pages: Option<&[usize]>, page_count: usize) -> Result<Vec<usize>> {
+    let Some(pages) = pages else {
+        return Ok((0..page_count).collect());
+    };
+    if let Some(page) = pages.iter().find(|page| **page >= page_count) {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "pdf page index {page} out of range for page count {page_count}"
+        )));
+    }
+    Ok(pages.to_vec())
+}
+
+// Form XObjects can nest; deeper ones are not looked into.
+const MAX_FORM_DEPTH: usize = 8;
+
+// Whether the page shows text, either in its own content stream or in a form
+// XObject the page draws with `Do`.
+fn page_shows_text(page: &Page<'_>) -> bool {
+    page.page_stream()
+        .is_some_and(|content| shows_text(content, &page.resources().x_objects, 0))
+}
+
+fn shows_text(content: &[u8], x_objects: &Dict<'_>, depth: usize) -> bool {
+    let scan = scan_content(content);
+    if scan.shows_text {
+        return true;
+    }
+    depth < MAX_FORM_DEPTH
+        && scan.drawn_x_objects.iter().any(|name| {
+            let Some(form) = x_objects.get::<Stream<'_>>(*name) else {
+                return false;
+            };
+            if form
+                .dict()
+                .get::<Name<'_>>(&b"Subtype"[..])
+                .is_none_or(|subtype| &*subtype != b"Form")
+            {
+                return false;
+            }
+            let Ok(content) = form.decoded() else {
+                return false;
+            };
+            // A form without its own resources uses the ones of its parent.
+            let own = form
+                .dict()
+                .get::<Dict<'_>>(&b"Resources"[..])
+                .and_then(|resources| resources.get::<Dict<'_>>(&b"XObject"[..]));
+            shows_text(&content, own.as_ref().unwrap_or(x_objects), depth + 1)
+        })
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ContentScan<'a> {
+    // A non-empty string is shown through `Tj`, `TJ`, `'` or `"`.
+    shows_text: bool,
+    // Names of the XObjects drawn with `Do`, in order.
+    drawn_x_objects: Vec<&'a [u8]>,
+}
+
+fn scan_content(content: &[u8]) -> ContentScan<'_> {
+    let mut scan = ContentScan::default();
+    let mut i = 0;
+    let mut has_string = false;
+    let mut last_name = None;
+    while i < content.len() {
+        match content[i] {
+            b'%' => {
+                while i < content.len() && !matches!(content[i], b'\r' | b'\n') {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                let (end, non_empty) = skip_literal_string(content, i);
+                has_string |= non_empty;
+                i = end;
+                continue;
+            }
+            b'<' if content.get(i + 1) == Some(&b'<') => i += 1,
+            b'<' => {
+                let end = content[i..]
+                    .iter()
+                    .position(|b| *b == b'>')
+                    .map_or(content.len(), |p| i + p);
+                has_string |= content[i + 1..end].iter().any(u8::is_ascii_hexdigit);
+                i = end;
+            }
+            b'/' => {
+                let start = i + 1;
+                i = token_end(content, start);
+                last_name = Some(&content[start..i]);
+                has_string = false;
+                continue;
+            }
+            b => {
+                if !is_pdf_delimiter(b) && !b.is_ascii_whitespace() {
+                    let start = i;
+                    i = token_end(content, i);
+                    match &content[start..i] {
+                        b"Tj" | b"TJ" | b"'" | b"\"" if has_string => {
+                            scan.shows_text = true;
+                            return scan;
+                        }
+                        b"Do" => scan.drawn_x_objects.extend(last_name.take()),
+                        // Inline image data is binary; resume after its `EI`.
+                        b"ID" => i = skip_inline_image(content, i),
+                        token if token.first().is_some_and(u8::is_ascii_alphabetic) => {
+                            has_string = false;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    scan
+}
+
+fn token_end(content: &[u8], mut i: usize) -> usize {
+    while i < content.len() && !is_pdf_delimiter(content[i]) && !content[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn is_pdf_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+// Returns the index just past the string opened at `start` and whether it
+// holds any bytes.
+fn skip_literal_string(content: &[u8], start: usize) -> (usize, bool) {
+    let mut depth = 0_usize;
+    let mut i = start;
+    while i < content.len() {
+        match content[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (i + 1, i > start + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    (content.len(), content.len() > start + 1)
+}
+
+fn skip_inline_image(content: &[u8], from: usize) -> usize {
+    content[from..]
+        .windows(4)
+        .position(|w| {
+            w[0].is_ascii_whitespace() && &w[1..3] == b"EI" && !w[3].is_ascii_alphanumeric()
+        })
+        .map_or(content.len(), |p| from + p + 3)
+}
+
+fn premultiplied_rgba_over_white_to_bgr(rgba: &[u8]) -> Vec<u8> {
+    let mut bgr = Vec::with_capacity(rgba.len() / 4 * 3);
+    for px in rgba.chunks_exact(4) {
+        let background = 255 - px[3];
+        bgr.push(px[2].saturating_add(background));
+        bgr.push(px[1].saturating_add(background));
+        bgr.push(px[0].saturating_add(background));
+    }
+    bgr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{premultiplied_rgba_over_white_to_bgr, scan_content, select_pages};
+
+    fn content_shows_text(content: &[u8]) -> bool {
+        scan_content(content).shows_text
+    }
+
+    #[test]
+    fn transparent_pixels_become_white() {
+        let bgr = premultiplied_rgba_over_white_to_bgr(&[0, 0, 0, 0, 10, 20, 30, 255]);
+        assert_eq!(bgr, vec![255, 255, 255, 30, 20, 10]);
+    }
+
+    #[test]
+    fn page_selection_rejects_out_of_range_pages() {
+        assert_eq!(select_pages(None, 3).expect("all pages"), vec![0, 1, 2]);
+        assert_eq!(
+            select_pages(Some(&[2, 0]), 3).expect("valid pages"),
+            vec![2, 0]
+        );
+        let err = select_pages(Some(&[3]), 3).expect_err("page 3 is out of range");
+        assert!(err.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn content_with_shown_strings_counts_as_text() {
+        assert!(content_shows_text(b"BT /F1 12 Tf 72 700 Td (Hello) Tj ET"));
+        assert!(content_shows_text(b"BT [(A) -120 (B)] TJ ET"));
+        assert!(content_shows_text(b"BT <0041> Tj ET"));
+        assert!(content_shows_text(b"BT (nested (parens) \\) ok) ' ET"));
+    }
+
+    #[test]
+    fn content_without_shown_strings_is_not_text() {
+        assert!(!content_shows_text(b"q 100 0 0 100 0 0 cm /Im0 Do Q"));
+        assert!(!content_shows_text(b"BT () Tj [] TJ ET"));
+        assert!(!content_shows_text(b"% (commented) Tj\n0 0 m 10 10 l S"));
+        assert!(!content_shows_text(b"<< /Tag (x) >> BDC EMC (x) re"));
+        assert!(!content_shows_text(b"BI /W 1 /H 1 ID (x) Tj EI Q"));
+    }
+
+    #[test]
+    fn drawn_x_objects_are_collected_for_form_lookup() {
+        let scan = scan_content(b"q /Fm0 Do Q /Im1 Do /GS0 gs (x) re /Fm2 Do");
+        assert!(!scan.shows_text);
+        assert_eq!(
+            scan.drawn_x_objects,
+            [&b"Fm0"[..], &b"Im1"[..], &b"Fm2"[..]]
+        );
+    }
+}
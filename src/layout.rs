@@ -0,0 +1,352 @@
+use serde::Serialize;
+
+use crate::{
+    Quad,
+    error::{RapidOcrError, Result},
+};
+
+// Gaps are measured in median box heights. The paragraph ratio matches the
+// blank-line rule the markdown exporter has always used.
+const PARAGRAPH_GAP_RATIO: f32 = 0.7;
+const COLUMN_GAP_RATIO: f32 = 1.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Page {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Block {
+    pub bbox: Quad,
+    pub lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Line {
+    pub bbox: Quad,
+    pub spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Span {
+    // Position of the source box in the detection output.
+    pub index: usize,
+    pub bbox: Quad,
+    pub text: String,
+}
+
+impl Page {
+    pub fn reading_order(&self) -> Vec<usize> {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.lines)
+            .flat_map(|line| &line.spans)
+            .map(|span| span.index)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+}
+
+impl Rect {
+    fn from_quad(quad: &Quad) -> Self {
+        let mut rect = Self {
+            min_x: f32::MAX,
+            min_y: f32::MAX,
+            max_x: f32::MIN,
+            max_y: f32::MIN,
+        };
+        for p in quad {
+            rect.min_x = rect.min_x.min(p[0]);
+            rect.min_y = rect.min_y.min(p[1]);
+            rect.max_x = rect.max_x.max(p[0]);
+            rect.max_y = rect.max_y.max(p[1]);
+        }
+        rect
+    }
+
+    fn height(&self) -> f32 {
+        (self.max_y - self.min_y).max(0.0)
+    }
+
+    fn center_y(&self) -> f32 {
+        (self.min_y + self.max_y) / 2.0
+    }
+
+    fn range(&self, axis: Axis) -> (f32, f32) {
+        match axis {
+            Axis::X => (self.min_x, self.max_x),
+            Axis::Y => (self.min_y, self.max_y),
+        }
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    fn to_quad(self) -> Quad {
+        [
+            [self.min_x, self.min_y],
+            [self.max_x, self.min_y],
+            [self.max_x, self.max_y],
+            [self.min_x, self.max_y],
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    index: usize,
+    rect: Rect,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Gap {
+    size: f32,
+    at: f32,
+}
+
+pub fn analyze_layout(boxes: &[Quad], txts: &[String]) -> Result<Page> {
+    if boxes.len() != txts.len() {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "layout length mismatch: boxes={}, txts={}",
+            boxes.len(),
+            txts.len()
+        )));
+    }
+    if boxes.is_empty() {
+        return Ok(Page::default());
+    }
+
+    let items: Vec<Item> = boxes
+        .iter()
+        .enumerate()
+        .map(|(index, quad)| Item {
+            index,
+            rect: Rect::from_quad(quad),
+        })
+        .collect();
+    let mut heights: Vec<f32> = items.iter().map(|item| item.rect.height()).collect();
+    heights.sort_by(f32::total_cmp);
+    let unit = heights[heights.len() / 2].max(1.0);
+
+    let mut regions = Vec::new();
+    xy_cut(items, unit, &mut regions);
+    let blocks = regions
+        .into_iter()
+        .map(|region| build_block(region, boxes, txts))
+        .collect();
+    Ok(Page { blocks })
+}
+
+// Recursive XY-cut: split at the widest whitespace gap on either axis until no
+// gap is wide enough, so columns are read top to bottom before moving right.
+fn xy_cut(items: Vec<Item>, unit: f32, out: &mut Vec<Vec<Item>>) {
+    if items.len() < 2 {
+        out.extend((!items.is_empty()).then_some(items));
+        return;
+    }
+    let y_gap = widest_gap(&items, Axis::Y).filter(|g| g.size > unit * PARAGRAPH_GAP_RATIO);
+    // A single row is never split into columns; wide gaps inside one line are
+    // table-like spacing, not a gutter.
+    let x_gap = if group_rows(items.clone()).len() > 1 {
+        widest_gap(&items, Axis::X).filter(|g| g.size >= unit * COLUMN_GAP_RATIO)
+    } else {
+        None
+    };
+    let (axis, gap) = match (y_gap, x_gap) {
+        (Some(y), Some(x)) if x.size >= y.size => (Axis::X, x),
+        (Some(y), _) => (Axis::Y, y),
+        (None, Some(x)) => (Axis::X, x),
+        (None, None) => {
+            out.push(items);
+            return;
+        }
+    };
+    let (before, after): (Vec<Item>, Vec<Item>) = items
+        .into_iter()
+        .partition(|item| item.rect.range(axis).0 < gap.at);
+    xy_cut(before, unit, out);
+    xy_cut(after, unit, out);
+}
+
+fn widest_gap(items: &[Item], axis: Axis) -> Option<Gap> {
+    let mut ranges: Vec<(f32, f32)> = items.iter().map(|item| item.rect.range(axis)).collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut covered_to = ranges[0].1;
+    let mut widest: Option<Gap> = None;
+    for &(start, end) in &ranges[1..] {
+        let size = start - covered_to;
+        if size > 0.0 && widest.is_none_or(|g| size > g.size) {
+            widest = Some(Gap {
+                size,
+                at: covered_to + size / 2.0,
+            });
+        }
+        covered_to = covered_to.max(end);
+    }
+    widest
+}
+
+// Same row rule as the original markdown exporter: boxes sorted by top join the
+// previous box's row when their centers are close or they overlap vertically.
+fn group_rows(mut items: Vec<Item>) -> Vec<Vec<Item>> {
+    items.sort_by(|a, b| {
+        a.rect
+            .min_y
+            .total_cmp(&b.rect.min_y)
+            .then(a.rect.min_x.total_cmp(&b.rect.min_x))
+    });
+    let mut rows: Vec<Vec<Item>> = Vec::new();
+    let mut prev: Option<Rect> = None;
+    for item in items {
+        let same_row = prev.is_some_and(|prev| {
+            let min_height = prev.height().min(item.rect.height());
+            let centers_are_close =
+                (item.rect.center_y() - prev.center_y()).abs() < min_height * 0.5;
+            let has_overlap = prev.max_y.min(item.rect.max_y) > prev.min_y.max(item.rect.min_y);
+            centers_are_close || has_overlap
+        });
+        prev = Some(item.rect);
+        match rows.last_mut() {
+            Some(row) if same_row => row.push(item),
+            _ => rows.push(vec![item]),
+        }
+    }
+    rows
+}
+
+fn build_block(region: Vec<Item>, boxes: &[Quad], txts: &[String]) -> Block {
+    let mut block_rect = region[0].rect;
+    let lines = group_rows(region)
+        .into_iter()
+        .map(|mut row| {
+            row.sort_by(|a, b| a.rect.min_x.total_cmp(&b.rect.min_x));
+            let line_rect = row
+                .iter()
+                .fold(row[0].rect, |rect, item| rect.union(item.rect));
+            block_rect = block_rect.union(line_rect);
+            Line {
+                bbox: line_rect.to_quad(),
+                spans: row
+                    .into_iter()
+                    .map(|item| Span {
+                        index: item.index,
+                        bbox: boxes[item.index],
+                        text: txts[item.index].clone(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    Block {
+        bbox: block_rect.to_quad(),
+        lines,
+    }
+}
+
+pub(crate) fn render_page(page: &Page, span_sep: &str) -> String {
+    page.blocks
+        .iter()
+        .map(|block| {
+            block
+                .lines
+                .iter()
+                .map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(span_sep)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Quad,
+        layout::{analyze_layout, render_page},
+    };
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Quad {
+        [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+    }
+
+    fn texts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| (*v).to_string()).collect()
+    }
+
+    #[test]
+    fn two_columns_are_read_one_after_the_other() {
+        // Detection order interleaves the columns row by row.
+        let boxes = vec![
+            rect(10.0, 10.0, 390.0, 30.0),
+            rect(10.0, 50.0, 180.0, 70.0),
+            rect(220.0, 50.0, 390.0, 70.0),
+            rect(10.0, 75.0, 180.0, 95.0),
+            rect(220.0, 75.0, 390.0, 95.0),
+        ];
+        let txts = texts(&["title", "left 1", "right 1", "left 2", "right 2"]);
+        let page = analyze_layout(&boxes, &txts).expect("valid layout input");
+        assert_eq!(page.reading_order(), vec![0, 1, 3, 2, 4]);
+        assert_eq!(page.blocks.len(), 3);
+        assert_eq!(page.blocks[1].bbox, rect(10.0, 50.0, 180.0, 95.0));
+        assert_eq!(
+            render_page(&page, " "),
+            "title\n\nleft 1\nleft 2\n\nright 1\nright 2"
+        );
+    }
+
+    #[test]
+    fn single_row_keeps_wide_gaps_on_one_line() {
+        let boxes = vec![
+            rect(300.0, 11.0, 400.0, 31.0),
+            rect(10.0, 10.0, 100.0, 30.0),
+        ];
+        let page = analyze_layout(&boxes, &texts(&["value", "name"])).expect("valid input");
+        assert_eq!(page.blocks.len(), 1);
+        assert_eq!(page.blocks[0].lines.len(), 1);
+        assert_eq!(page.reading_order(), vec![1, 0]);
+        assert_eq!(page.blocks[0].lines[0].bbox, rect(10.0, 10.0, 400.0, 31.0));
+    }
+
+    #[test]
+    fn paragraph_gaps_start_new_blocks() {
+        let boxes = vec![
+            rect(10.0, 10.0, 200.0, 30.0),
+            rect(10.0, 33.0, 200.0, 53.0),
+            rect(10.0, 90.0, 200.0, 110.0),
+        ];
+        let page = analyze_layout(&boxes, &texts(&["a", "b", "c"])).expect("valid input");
+        assert_eq!(page.blocks.len(), 2);
+        assert_eq!(page.blocks[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn length_mismatch_is_rejected() {
+        let err = analyze_layout(&[rect(0.0, 0.0, 1.0, 1.0)], &[]).expect_err("mismatch");
+        assert!(err.to_string().contains("layout length mismatch"));
+    }
+}
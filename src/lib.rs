@@ -1,8 +1,11 @@
+mod clock;
 mod cls;
 mod config;
 mod det;
 mod error;
 mod input;
+mod layout;
+mod model_cache;
 mod model_registry;
 mod model_store;
 mod output;
@@ -11,24 +14,49 @@ mod rec;
 mod runtime;
 mod types;
 mod vision;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use config::{
-    ColorOrder, LangCls, LangDet, LangRec, ModelConfig, ModelType, OcrVersion, ProviderPreference,
-    RecImage, RecognizeOptions, RecognizerConfig, RuntimeBackend, RuntimeConfig, VisionBackend,
+    ColorOrder, CorrectionConfig, CtcDecodeConfig, LangCls, LangDet, LangRec, ModelConfig,
+    ModelSource, ModelType, OcrVersion, ProviderPreference, RecImage, RecognizeOptions,
+    RecognizerConfig, RuntimeBackend, RuntimeConfig, VisionBackend,
 };
 pub use error::{RapidOcrError, Result};
-pub use input::image_loader::{LoadImage, OcrInput};
+pub use input::image_loader::{LoadImage, OcrInput, PageImage, PageImages};
+#[cfg(feature = "pdf")]
+pub use input::pdf::PdfInput;
+pub use layout::{Block, Line, Page, Span, analyze_layout};
+pub use model_cache::{CacheEntryStatus, CachedModel, ModelCache};
+pub use model_registry::{ModelRegistry, RegistryFile};
+pub use model_store::{
+    DownloadConfig, DownloadEvent, DownloadProgress, MODEL_DIR_ENV, ModelDownloader,
+    default_model_store_dir,
+};
 pub use output::json::OcrJsonItem;
+pub use output::{to_alto, to_hocr, to_plain_text};
+#[cfg(feature = "searchable-pdf")]
+pub use output::{to_searchable_pdf, to_searchable_pdf_with_dpi};
 pub use pipeline::compat_rapidocr::{from_rapidocr_yaml_file, from_rapidocr_yaml_str};
 pub use pipeline::{
     config::{EngineConfig, GlobalConfig},
-    rapid_ocr::{PipelineProviderResolutions, RapidOcr, RapidOcrEngine},
+    observer::{CancellationToken, OcrEvent, OcrObserver},
+    rapid_ocr::{
+        PipelineProviderResolutions, RapidOcr, RapidOcrEngine, SharedRapidOcr, StageModel,
+    },
     types::{
-        ClsResult, DetResult, FullResult, OcrCallOptions, OcrOutput, OcrResult, RecResult,
-        RunOptions, StageTimings,
+        ClsResult, DetResult, FullResult, OcrCallOptions, OcrOutput, OcrPageOutput, OcrPageResult,
+        OcrResult, RecResult, RunOptions, StageTimings,
     },
 };
+pub use rec::correction::{LineCorrector, WordFrequencyCorrector};
+pub use rec::router::MultiRecConfig;
 pub use runtime::provider::{ProviderResolution, ResolvedExecutionProvider};
-pub use types::{LineResult, RecognizeOutput, WordBox, WordInfo, WordType};
+pub use types::{
+    CharAlternative, CharDetail, LineResult, RecognizeOutput, TextOrientation, WordBox, WordInfo,
+    WordType,
+};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmOcr;
 
 pub type Quad = [[f32; 2]; 4];
@@ -0,0 +1,423 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::LangRec,
+    error::Result,
+    model_registry::{ModelRegistry, RegistryFile},
+    model_store::{default_model_store_dir, sha256_file, sidecar_path, try_lock_download},
+    pipeline::config::EngineConfig,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheEntryStatus {
+    // Known to the registry; `list` does not hash files.
+    Registered,
+    Verified,
+    // Known to the registry, which has no SHA256 for it.
+    NoChecksum,
+    Mismatch { expected: String, actual: String },
+    Unregistered,
+    // Leftover of an interrupted download.
+    Partial,
+}
+
+impl CacheEntryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Registered => "registered",
+            Self::Verified => "ok",
+            Self::NoChecksum => "no-checksum",
+            Self::Mismatch { .. } => "mismatch",
+            Self::Unregistered => "unregistered",
+            Self::Partial => "partial",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedModel {
+    pub file_name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub status: CacheEntryStatus,
+}
+
+// The directory engines download models into, checked against the model
+// registry. Pre-seeding this directory (or `RAPID_OCR_MODEL_DIR`) with the
+// files from `prefetch` lets engines start without network access.
+#[derive(Debug, Clone)]
+pub struct ModelCache {
+    dir: PathBuf,
+    registry: ModelRegistry,
+}
+
+impl ModelCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self::with_registry(
+            dir,
+            ModelRegistry::from_default_yaml()?,
+        ))
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Self::new(default_model_store_dir())
+    }
+
+    pub fn with_registry(dir: impl Into<PathBuf>, registry: ModelRegistry) -> Self {
+        Self {
+            dir: dir.into(),
+            registry,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn list(&self) -> Result<Vec<CachedModel>> {
+        let known = self.known_files()?;
+        let mut entries = Vec::new();
+        if !self.dir.is_dir() {
+            return Ok(entries);
+        }
+        for item in fs::read_dir(&self.dir)? {
+            let item = item?;
+            let metadata = item.metadata()?;
+            let file_name = item.file_name().to_string_lossy().into_owned();
+            // Download locks are not models; `prune` clears the stale ones.
+            if !metadata.is_file() || file_name.ends_with(".lock") {
+                continue;
+            }
+            let status = if file_name.ends_with(".part") {
+                CacheEntryStatus::Partial
+            } else if known.contains_key(&file_name) {
+                CacheEntryStatus::Registered
+            } else {
+                CacheEntryStatus::Unregistered
+            };
+            entries.push(CachedModel {
+                file_name,
+                path: item.path(),
+                size: metadata.len(),
+                status,
+            });
+        }
+        entries.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok(entries)
+    }
+
+    // Hashes every registered file that has a registry checksum.
+    pub fn verify(&self) -> Result<Vec<CachedModel>> {
+        let known = self.known_files()?;
+        let mut entries = self.list()?;
+        for entry in &mut entries {
+            if entry.status != CacheEntryStatus::Registered {
+                continue;
+            }
+            let Some(expected) = known
+                .get(&entry.file_name)
+                .and_then(|file| file.sha256.as_ref())
+            else {
+                entry.status = CacheEntryStatus::NoChecksum;
+                continue;
+            };
+            let actual = sha256_file(&entry.path)?;
+            entry.status = if actual.eq_ignore_ascii_case(expected) {
+                CacheEntryStatus::Verified
+            } else {
+                CacheEntryStatus::Mismatch {
+                    expected: expected.clone(),
+                    actual,
+                }
+            };
+        }
+        Ok(entries)
+    }
+
+    // Removes partial downloads and files failing their checksum, plus files
+    // the registry does not know when `include_unregistered` is set. Returns
+    // the removed (or, for `dry_run`, removable) entries. Partial files of a
+    // download still running elsewhere are left alone, and download locks no
+    // one holds are cleared.
+    pub fn prune(&self, include_unregistered: bool, dry_run: bool) -> Result<Vec<CachedModel>> {
+        let mut removed = Vec::new();
+        for entry in self.verify()? {
+            let remove = match entry.status {
+                CacheEntryStatus::Partial | CacheEntryStatus::Mismatch { .. } => true,
+                CacheEntryStatus::Unregistered => include_unregistered,
+                _ => false,
+            };
+            if !remove {
+                continue;
+            }
+            if entry.status == CacheEntryStatus::Partial {
+                let target = entry.path.with_extension("");
+                let Some(_lock) = try_lock_download(&target)? else {
+                    continue;
+                };
+                if !dry_run {
+                    fs::remove_file(&entry.path)?;
+                    remove_if_present(&sidecar_path(&target, "lock"))?;
+                }
+            } else if !dry_run {
+                fs::remove_file(&entry.path)?;
+            }
+            removed.push(entry);
+        }
+        if !dry_run {
+            self.remove_stale_locks()?;
+        }
+        Ok(removed)
+    }
+
+    fn remove_stale_locks(&self) -> Result<()> {
+        if !self.dir.is_dir() {
+            return Ok(());
+        }
+        for item in fs::read_dir(&self.dir)? {
+            let path = item?.path();
+            if path.extension().is_none_or(|ext| ext != "lock") {
+                continue;
+            }
+            if try_lock_download(&path.with_extension(""))?.is_some() {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Downloads every registry model `config` can load, including stages that
+    // are switched off (they may be enabled per call, page orientation too) and
+    // `multi_rec` routes.
+    // Sections whose model is a path or bytes are skipped. `config.download`
+    // replaces the download settings of the cache's registry; its progress
+    // reporter is kept.
+    pub fn prefetch(&self, config: &EngineConfig) -> Result<Vec<PathBuf>> {
+        let downloader = self
+            .registry
+            .downloader()
+            .clone()
+            .with_config(config.download.clone());
+        let registry = match &config.model_manifest {
+            Some(path) => ModelRegistry::from_yaml_file(path)?.with_fallback(self.registry.clone()),
+            None => self.registry.clone(),
+        }
+        .with_downloader(downloader);
+        let registry = &registry;
+        let mut paths = Vec::new();
+        if config.det.model_source.is_registry() {
+            let det = &config.det;
+            let resolved = registry.resolve_det(det.ocr_version, det.lang, det.model_type)?;
+            paths.push(registry.fetch_model(
+                &resolved.model_url,
+                resolved.sha256.as_deref(),
+                &self.dir,
+                true,
+            )?);
+        }
+        if config.cls.model_source.is_registry() {
+            let cls = &config.cls;
+            let resolved = registry.resolve_cls(cls.ocr_version, cls.lang, cls.model_type)?;
+            paths.push(registry.fetch_model(
+                &resolved.model_url,
+                resolved.sha256.as_deref(),
+                &self.dir,
+                true,
+            )?);
+        }
+
+        let model = &config.rec.model;
+        let mut langs: Vec<LangRec> = Vec::new();
+        if model.model_source.is_registry() {
+            langs.push(model.lang);
+        }
+        for lang in &config.multi_rec.langs {
+            if *lang != model.lang && !langs.contains(lang) {
+                langs.push(*lang);
+            }
+        }
+        for lang in langs {
+            let resolved = registry.resolve_rec(model.ocr_version, lang, model.model_type)?;
+            paths.push(registry.fetch_model(
+                &resolved.model_url,
+                resolved.sha256.as_deref(),
+                &self.dir,
+                true,
+            )?);
+            if let Some(dict_url) = &resolved.dict_url {
+                paths.push(registry.download(dict_url, None, &self.dir)?);
+            }
+        }
+
+        if config.doc_ori.model_source.is_registry() {
+            let resolved = registry.resolve_doc_ori()?;
+            paths.push(registry.fetch_model(
+                &resolved.model_url,
+                resolved.sha256.as_deref(),
+                &self.dir,
+                true,
+            )?);
+        }
+        Ok(paths)
+    }
+
+    fn known_files(&self) -> Result<HashMap<String, RegistryFile>> {
+        Ok(self
+            .registry
+            .files()?
+            .into_iter()
+            .map(|file| (file.file_name.clone(), file))
+            .collect())
+    }
+}
+
+fn remove_if_present(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use sha2::{Digest, Sha256};
+
+    use crate::{
+        config::ModelSource,
+        model_cache::{CacheEntryStatus, ModelCache},
+        model_registry::ModelRegistry,
+        pipeline::config::EngineConfig,
+    };
+
+    #[test]
+    fn prefetch_includes_doc_orientation_even_when_it_is_off() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-prefetch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create cache dir");
+        let model = dir.join("doc_ori.onnx");
+        fs::write(&model, b"doc ori").expect("write model");
+        let yaml = format!(
+            "doc_ori:\n  doc_ori.onnx:\n    model_dir: {}\n    SHA256: {:x}\n",
+            model.display(),
+            Sha256::digest(b"doc ori")
+        );
+        let registry = ModelRegistry::from_yaml_str(&yaml).expect("valid registry");
+
+        let mut config = EngineConfig::default();
+        config.det.model_source = ModelSource::Path(dir.join("det.onnx"));
+        config.cls.model_source = ModelSource::Path(dir.join("cls.onnx"));
+        config.rec.model.model_source = ModelSource::Path(dir.join("rec.onnx"));
+        assert!(!config.global.use_doc_orientation);
+
+        let cache = ModelCache::with_registry(&dir, registry);
+        assert_eq!(cache.prefetch(&config).expect("prefetch"), vec![model]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prune_skips_running_downloads_and_clears_stale_locks() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-locks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create cache dir");
+        fs::write(dir.join("busy.onnx.part"), b"half").expect("write busy part");
+        let busy = fs::File::create(dir.join("busy.onnx.lock")).expect("create busy lock");
+        busy.lock().expect("hold busy lock");
+        fs::write(dir.join("idle.onnx.part"), b"half").expect("write idle part");
+        fs::write(dir.join("idle.onnx.lock"), b"").expect("write idle lock");
+        fs::write(dir.join("done.onnx.lock"), b"").expect("write stale lock");
+
+        let cache = ModelCache::with_registry(
+            &dir,
+            ModelRegistry::from_yaml_str("{}").expect("empty registry"),
+        );
+        let removed = cache.prune(false, false).expect("prune");
+        assert_eq!(
+            removed
+                .iter()
+                .map(|e| e.file_name.as_str())
+                .collect::<Vec<_>>(),
+            ["idle.onnx.part"]
+        );
+        let mut left = fs::read_dir(&dir)
+            .expect("read cache dir")
+            .map(|e| e.expect("entry").file_name().into_string().expect("utf-8"))
+            .collect::<Vec<_>>();
+        left.sort();
+        assert_eq!(left, ["busy.onnx.lock", "busy.onnx.part"]);
+        drop(busy);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verify_and_prune_classify_cached_files() {
+        let good = b"good model";
+        let sha = format!("{:x}", Sha256::digest(good));
+        let yaml = format!(
+            r#"
+onnxruntime:
+  PP-OCRv4:
+    det:
+      ch_PP-OCRv4_det_infer.onnx:
+        model_dir: https://example.com/ch_PP-OCRv4_det_infer.onnx
+        SHA256: {sha}
+    rec:
+      ch_PP-OCRv4_rec_infer.onnx:
+        model_dir: https://example.com/ch_PP-OCRv4_rec_infer.onnx
+        SHA256: {sha}
+        dict_url: https://example.com/ppocr_keys_v1.txt
+"#
+        );
+        let registry = ModelRegistry::from_yaml_str(&yaml).expect("valid registry");
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create cache dir");
+        fs::write(dir.join("ch_PP-OCRv4_det_infer.onnx"), good).expect("write det");
+        fs::write(dir.join("ch_PP-OCRv4_rec_infer.onnx"), b"corrupt").expect("write rec");
+        fs::write(dir.join("ppocr_keys_v1.txt"), b"a\nb\n").expect("write dict");
+        fs::write(dir.join("ch_PP-OCRv4_rec_infer.onnx.part"), b"half").expect("write part");
+        fs::write(dir.join("custom.onnx"), b"mine").expect("write custom");
+
+        let cache = ModelCache::with_registry(&dir, registry);
+        let status = |entries: &[super::CachedModel], name: &str| {
+            entries
+                .iter()
+                .find(|e| e.file_name == name)
+                .map(|e| e.status.as_str())
+        };
+        let listed = cache.list().expect("list");
+        assert_eq!(listed.len(), 5);
+        assert_eq!(
+            status(&listed, "ch_PP-OCRv4_det_infer.onnx"),
+            Some("registered")
+        );
+
+        let verified = cache.verify().expect("verify");
+        assert_eq!(status(&verified, "ch_PP-OCRv4_det_infer.onnx"), Some("ok"));
+        assert_eq!(
+            status(&verified, "ch_PP-OCRv4_rec_infer.onnx"),
+            Some("mismatch")
+        );
+        assert_eq!(status(&verified, "ppocr_keys_v1.txt"), Some("no-checksum"));
+        assert_eq!(status(&verified, "custom.onnx"), Some("unregistered"));
+
+        let planned = cache.prune(false, true).expect("dry run");
+        assert_eq!(planned.len(), 2);
+        assert_eq!(cache.list().expect("list").len(), 5);
+
+        let removed = cache.prune(true, false).expect("prune");
+        assert_eq!(removed.len(), 3);
+        let left = cache.list().expect("list");
+        assert_eq!(left.len(), 2);
+        assert!(
+            left.iter()
+                .all(|e| e.status == CacheEntryStatus::Registered)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,17 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::Deserialize;
 
 use crate::{
     config::{LangCls, LangDet, LangRec, ModelType, OcrVersion},
     error::{RapidOcrError, Result},
+    model_store::{ModelDownloader, extract_file_name, local_model_path},
 };
 
 const DEFAULT_MODELS_YAML: &str = include_str!("../assets/default_models.yaml");
 
 #[derive(Debug, Clone, Deserialize)]
 struct Root {
+    // Optional so a manifest may only list page-level models.
+    #[serde(default)]
     onnxruntime: HashMap<String, OcrVersionNode>,
+    // Page-level models are not tied to an OCR version.
+    #[serde(default)]
+    doc_ori: HashMap<String, ModelEntry>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -62,9 +72,20 @@ pub struct ResolvedTaskModel {
     pub sha256: Option<String>,
 }
 
+// A file the registry can download, under the name it is stored as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryFile {
+    pub file_name: String,
+    pub url: String,
+    pub sha256: Option<String>,
+}
+
+// Layers are consulted in order: a manifest loaded from disk shadows the
+// compiled-in registry for every task/lang/variant it provides.
 #[derive(Debug, Clone)]
 pub struct ModelRegistry {
-    root: Root,
+    layers: Vec<Root>,
+    downloader: ModelDownloader,
 }
 
 impl ModelRegistry {
@@ -74,7 +95,100 @@ impl ModelRegistry {
 
     pub fn from_yaml_str(yaml: &str) -> Result<Self> {
         let root = serde_yaml::from_str::<Root>(yaml)?;
-        Ok(Self { root })
+        Ok(Self {
+            layers: vec![root],
+            downloader: ModelDownloader::default(),
+        })
+    }
+
+    // Relative `model_dir`/`dict_url` paths are taken relative to the
+    // manifest, so a model bundle can be moved as a whole.
+    pub fn from_yaml_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let mut registry = Self::from_yaml_str(&text)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for root in &mut registry.layers {
+            let version_entries = root.onnxruntime.values_mut().flat_map(|node| {
+                node.det
+                    .values_mut()
+                    .chain(node.cls.values_mut())
+                    .chain(node.rec.values_mut())
+            });
+            for entry in version_entries.chain(root.doc_ori.values_mut()) {
+                entry.model_dir = anchor_relative_path(&entry.model_dir, base_dir);
+                if let Some(dict_url) = &mut entry.dict_url {
+                    *dict_url = anchor_relative_path(dict_url, base_dir);
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    // The compiled-in registry, shadowed by `manifest` when one is given.
+    pub fn with_manifest(manifest: Option<&Path>) -> Result<Self> {
+        let default = Self::from_default_yaml()?;
+        match manifest {
+            Some(path) => Ok(Self::from_yaml_file(path)?.with_fallback(default)),
+            None => Ok(default),
+        }
+    }
+
+    pub fn with_fallback(mut self, fallback: ModelRegistry) -> Self {
+        self.layers.extend(fallback.layers);
+        self
+    }
+
+    // How remote entries are fetched: mirrors, retries and progress reporting.
+    pub fn with_downloader(mut self, downloader: ModelDownloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    pub fn downloader(&self) -> &ModelDownloader {
+        &self.downloader
+    }
+
+    pub fn download(
+        &self,
+        file_url: &str,
+        expected_sha256: Option<&str>,
+        save_dir: impl AsRef<Path>,
+    ) -> Result<PathBuf> {
+        self.downloader.fetch(file_url, expected_sha256, save_dir)
+    }
+
+    // A stage's registry file. With `allow_download` off only local entries and
+    // files already in `save_dir` are used, so a manifest of local paths works
+    // without network access.
+    pub fn fetch_file(
+        &self,
+        file_url: &str,
+        expected_sha256: Option<&str>,
+        save_dir: impl AsRef<Path>,
+        allow_download: bool,
+    ) -> Result<PathBuf> {
+        if allow_download {
+            self.download(file_url, expected_sha256, save_dir)
+        } else {
+            self.downloader
+                .fetch_offline(file_url, expected_sha256, save_dir)
+        }
+    }
+
+    // `fetch_file` for model weights, which should always carry a checksum;
+    // entries without one are reported as `DownloadEvent::Unverified`.
+    pub fn fetch_model(
+        &self,
+        file_url: &str,
+        expected_sha256: Option<&str>,
+        save_dir: impl AsRef<Path>,
+        allow_download: bool,
+    ) -> Result<PathBuf> {
+        if expected_sha256.is_none() {
+            self.downloader.report_unverified(file_url);
+        }
+        self.fetch_file(file_url, expected_sha256, save_dir, allow_download)
     }
 
     pub fn resolve_rec(
@@ -83,26 +197,9 @@ impl ModelRegistry {
         lang: LangRec,
         model_type: ModelType,
     ) -> Result<ResolvedRecModel> {
-        let version_map = self
-            .root
-            .onnxruntime
-            .get(ocr_version.as_str())
-            .ok_or_else(|| {
-                RapidOcrError::ModelResolve(format!(
-                    "unsupported ocr version for onnxruntime: {}",
-                    ocr_version.as_str()
-                ))
-            })?;
-
-        let lang_prefix = lang.as_str();
-        let selected = select_model(
-            &version_map.rec,
-            lang_prefix,
-            model_type,
-            "rec",
-            ocr_version,
-        )?;
-
+        let selected = self.select("rec", ocr_version, lang.as_str(), model_type, |node| {
+            &node.rec
+        })?;
         Ok(ResolvedRecModel {
             model_name: selected.0.clone(),
             model_url: selected.1.model_dir.clone(),
@@ -117,14 +214,9 @@ impl ModelRegistry {
         lang: LangDet,
         model_type: ModelType,
     ) -> Result<ResolvedTaskModel> {
-        let version_map = self.version_node(ocr_version)?;
-        let selected = select_model(
-            &version_map.det,
-            lang.as_str(),
-            model_type,
-            "det",
-            ocr_version,
-        )?;
+        let selected = self.select("det", ocr_version, lang.as_str(), model_type, |node| {
+            &node.det
+        })?;
         Ok(ResolvedTaskModel {
             model_name: selected.0.clone(),
             model_url: selected.1.model_dir.clone(),
@@ -138,14 +230,9 @@ impl ModelRegistry {
         lang: LangCls,
         model_type: ModelType,
     ) -> Result<ResolvedTaskModel> {
-        let version_map = self.version_node(ocr_version)?;
-        let selected = select_model(
-            &version_map.cls,
-            lang.as_str(),
-            model_type,
-            "cls",
-            ocr_version,
-        )?;
+        let selected = self.select("cls", ocr_version, lang.as_str(), model_type, |node| {
+            &node.cls
+        })?;
         Ok(ResolvedTaskModel {
             model_name: selected.0.clone(),
             model_url: selected.1.model_dir.clone(),
@@ -153,19 +240,115 @@ impl ModelRegistry {
         })
     }
 
-    fn version_node(&self, ocr_version: OcrVersion) -> Result<&OcrVersionNode> {
-        self.root
-            .onnxruntime
-            .get(ocr_version.as_str())
+    pub fn resolve_doc_ori(&self) -> Result<ResolvedTaskModel> {
+        let (name, entry) = self
+            .layers
+            .iter()
+            .find_map(|root| root.doc_ori.iter().min_by(|a, b| a.0.cmp(b.0)))
             .ok_or_else(|| {
-                RapidOcrError::ModelResolve(format!(
-                    "unsupported ocr version for onnxruntime: {}",
-                    ocr_version.as_str()
-                ))
-            })
+                RapidOcrError::ModelResolve("no doc_ori model found in registry".to_string())
+            })?;
+        Ok(ResolvedTaskModel {
+            model_name: name.clone(),
+            model_url: entry.model_dir.clone(),
+            sha256: entry.sha256.clone(),
+        })
+    }
+
+    // Every model and dictionary file, sorted by file name. A file listed
+    // twice keeps the first entry that carries a checksum.
+    pub fn files(&self) -> Result<Vec<RegistryFile>> {
+        let mut files: BTreeMap<String, RegistryFile> = BTreeMap::new();
+        let mut add = |url: &str, sha256: Option<&String>| -> Result<()> {
+            let file_name = extract_file_name(url)?;
+            let entry = files.entry(file_name.clone()).or_insert(RegistryFile {
+                file_name,
+                url: url.to_string(),
+                sha256: None,
+            });
+            if entry.sha256.is_none() {
+                entry.sha256 = sha256.cloned();
+            }
+            Ok(())
+        };
+        for root in &self.layers {
+            let version_entries = root.onnxruntime.values().flat_map(|node| {
+                node.det
+                    .values()
+                    .chain(node.cls.values())
+                    .chain(node.rec.values())
+            });
+            for entry in version_entries.chain(root.doc_ori.values()) {
+                add(&entry.model_dir, entry.sha256.as_ref())?;
+                if let Some(dict_url) = &entry.dict_url {
+                    add(dict_url, None)?;
+                }
+            }
+        }
+        Ok(files.into_values().collect())
+    }
+
+    // The first layer that provides a matching model decides, including its
+    // errors; the last layer is always asked so its errors surface.
+    fn select(
+        &self,
+        task: &str,
+        ocr_version: OcrVersion,
+        lang_prefix: &str,
+        model_type: ModelType,
+        task_map: impl Fn(&OcrVersionNode) -> &HashMap<String, ModelEntry>,
+    ) -> Result<(&String, &ModelEntry)> {
+        let last = self.layers.len().saturating_sub(1);
+        for (idx, root) in self.layers.iter().enumerate() {
+            let Some(node) = root.onnxruntime.get(ocr_version.as_str()) else {
+                if idx == last {
+                    return Err(RapidOcrError::ModelResolve(format!(
+                        "unsupported ocr version for onnxruntime: {}",
+                        ocr_version.as_str()
+                    )));
+                }
+                continue;
+            };
+            let model_map = task_map(node);
+            if idx == last || provides_model(model_map, lang_prefix, model_type, task, ocr_version)
+            {
+                return select_model(model_map, lang_prefix, model_type, task, ocr_version);
+            }
+        }
+        Err(RapidOcrError::ModelResolve(
+            "model registry has no layers".to_string(),
+        ))
     }
 }
 
+fn anchor_relative_path(location: &str, base_dir: &Path) -> String {
+    if local_model_path(location).is_some_and(|path| path.is_relative()) {
+        return base_dir.join(location).to_string_lossy().into_owned();
+    }
+    location.to_string()
+}
+
+fn provides_model(
+    model_map: &HashMap<String, ModelEntry>,
+    lang_prefix: &str,
+    model_type: ModelType,
+    task: &str,
+    ocr_version: OcrVersion,
+) -> bool {
+    if ocr_version == OcrVersion::PPocrV6 {
+        let model_key = format!("multi_PP-OCRv6_{task}_{}", model_type.as_str());
+        return model_map.contains_key(&model_key);
+    }
+    let variant = match model_type {
+        ModelType::Mobile => ModelVariant::Mobile,
+        ModelType::Server => ModelVariant::Server,
+        ModelType::Tiny | ModelType::Small | ModelType::Medium => return false,
+    };
+    model_map.keys().any(|name| {
+        language_tag_matches(name, lang_prefix) && classify_model_variant(name) == variant
+    })
+}
+
 fn select_model<'a>(
     model_map: &'a HashMap<String, ModelEntry>,
     lang_prefix: &str,
@@ -346,8 +529,13 @@ fn format_candidate_names(candidates: &[ModelCandidate<'_>]) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use super::ModelRegistry;
-    use crate::config::{LangCls, LangDet, LangRec, ModelType, OcrVersion};
+    use crate::{
+        config::{LangCls, LangDet, LangRec, ModelType, OcrVersion},
+        model_store::{DownloadEvent, DownloadProgress, ModelDownloader},
+    };
 
     const CUSTOM_YAML: &str = r#"
 onnxruntime:
@@ -402,6 +590,16 @@ onnxruntime:
         assert!(cls.model_name.contains("cls"));
     }
 
+    #[test]
+    fn resolve_doc_orientation_model() {
+        let reg = ModelRegistry::from_default_yaml().expect("registry should parse");
+        let doc_ori = reg.resolve_doc_ori().expect("doc_ori model should resolve");
+        assert!(doc_ori.model_name.contains("doc_ori"));
+
+        let without = ModelRegistry::from_yaml_str(AMBIGUOUS_MOBILE_YAML).expect("valid yaml");
+        assert!(without.resolve_doc_ori().is_err());
+    }
+
     #[test]
     fn resolve_ppocr_v6_size_models() {
         let reg = ModelRegistry::from_default_yaml().expect("registry should parse");
@@ -445,6 +643,95 @@ onnxruntime:
         );
     }
 
+    #[test]
+    fn registry_files_are_unique_and_keep_checksums() {
+        let reg = ModelRegistry::from_default_yaml().expect("registry should parse");
+        let files = reg.files().expect("file names should extract");
+        assert!(files.windows(2).all(|w| w[0].file_name < w[1].file_name));
+        let det = files
+            .iter()
+            .find(|f| f.file_name == "ch_PP-OCRv4_det_infer.onnx")
+            .expect("default det model listed");
+        assert!(det.sha256.is_some());
+        assert!(files.iter().any(|f| f.file_name == "ppocrv6_dict.txt"));
+    }
+
+    #[test]
+    fn manifest_file_shadows_default_and_anchors_relative_paths() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-manifest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let manifest = dir.join("models.yaml");
+        std::fs::write(
+            &manifest,
+            r#"
+onnxruntime:
+  PP-OCRv4:
+    rec:
+      en_PP-OCRv4_rec_infer.onnx:
+        model_dir: rec/en_PP-OCRv4_rec_infer.onnx
+        SHA256: abc
+        dict_url: file:///opt/bundle/en_dict.txt
+"#,
+        )
+        .expect("write manifest");
+
+        let reg = ModelRegistry::with_manifest(Some(&manifest)).expect("manifest should parse");
+        let en = reg
+            .resolve_rec(OcrVersion::PPocrV4, LangRec::En, ModelType::Mobile)
+            .expect("manifest model should resolve");
+        assert_eq!(
+            std::path::PathBuf::from(&en.model_url),
+            dir.join("rec/en_PP-OCRv4_rec_infer.onnx")
+        );
+        assert_eq!(en.sha256.as_deref(), Some("abc"));
+        assert_eq!(
+            en.dict_url.as_deref(),
+            Some("file:///opt/bundle/en_dict.txt")
+        );
+
+        let ch = reg
+            .resolve_rec(OcrVersion::PPocrV4, LangRec::Ch, ModelType::Mobile)
+            .expect("default registry still answers other langs");
+        assert!(ch.model_url.starts_with("https://"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[derive(Default)]
+    struct Unverified(Mutex<Vec<String>>);
+
+    impl DownloadProgress for Unverified {
+        fn on_event(&self, event: &DownloadEvent<'_>) {
+            if let DownloadEvent::Unverified { url } = event {
+                self.0
+                    .lock()
+                    .expect("recorder lock")
+                    .push((*url).to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn models_without_checksum_are_reported_before_use() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-unverified-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let model = dir.join("doc_ori.onnx");
+        std::fs::write(&model, b"model").expect("write model");
+        let url = model.display().to_string();
+
+        let recorder = Arc::new(Unverified::default());
+        let reg = ModelRegistry::from_yaml_str("{}")
+            .expect("empty registry")
+            .with_downloader(ModelDownloader::default().with_progress(recorder.clone()));
+        let path = reg
+            .fetch_model(&url, None, dir.join("store"), false)
+            .expect("local model without network");
+        assert_eq!(path, model);
+        reg.fetch_file(&url, None, dir.join("store"), false)
+            .expect("dictionaries carry no checksum");
+        assert_eq!(*recorder.0.lock().expect("recorder lock"), vec![url]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn resolve_lang_prefix_match_is_exact() {
         let reg = ModelRegistry::from_yaml_str(CUSTOM_YAML).expect("registry should parse");
@@ -1,23 +1,44 @@
 use std::{
-    fs,
-    io::{Read, Write},
+    ffi::OsString,
+    fmt, fs,
     path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
+#[cfg(feature = "download")]
+use std::{
+    io::{self, Read, Write},
+    thread,
+};
 
-use reqwest::blocking::Client;
+#[cfg(feature = "download")]
+use reqwest::{StatusCode, blocking::Client};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::error::{RapidOcrError, Result};
 
+pub const MODEL_DIR_ENV: &str = "RAPID_OCR_MODEL_DIR";
+
+// `RAPID_OCR_MODEL_DIR` wins; otherwise the per-user cache directory
+// (`%LOCALAPPDATA%`, then `$XDG_CACHE_HOME`, then `~/.cache`).
 pub fn default_model_store_dir() -> PathBuf {
-    if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
-        return PathBuf::from(local_app_data)
-            .join("rapid-ocr-rs")
-            .join("models");
+    model_store_dir_from(|key| std::env::var_os(key))
+}
+
+fn model_store_dir_from(var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    let non_empty = |key: &str| var(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(dir) = non_empty(MODEL_DIR_ENV) {
+        return dir;
     }
 
-    PathBuf::from("models")
+    let cache_dir = non_empty("LOCALAPPDATA")
+        .or_else(|| non_empty("XDG_CACHE_HOME"))
+        .or_else(|| non_empty("HOME").map(|home| home.join(".cache")));
+    match cache_dir {
+        Some(dir) => dir.join("rapid-ocr-rs").join("models"),
+        None => PathBuf::from("models"),
+    }
 }
 
 pub fn verify_existing_file(path: impl AsRef<Path>) -> Result<PathBuf> {
@@ -34,91 +55,477 @@ pub fn verify_existing_file(path: impl AsRef<Path>) -> Result<PathBuf> {
     Ok(path)
 }
 
-pub fn ensure_downloaded(
-    file_url: &str,
-    expected_sha256: Option<&str>,
-    save_dir: impl AsRef<Path>,
-) -> Result<PathBuf> {
-    let save_dir = save_dir.as_ref();
-    fs::create_dir_all(save_dir)?;
+// Registry locations that are not http(s) URLs: `file://` URIs and plain
+// paths. Other schemes are left to the HTTP client to reject.
+pub(crate) fn local_model_path(location: &str) -> Option<PathBuf> {
+    if let Some(rest) = location.strip_prefix("file://") {
+        // `file:///C:/models/x.onnx` names a Windows drive path.
+        let bytes = rest.as_bytes();
+        let rest = if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' {
+            &rest[1..]
+        } else {
+            rest
+        };
+        return Some(PathBuf::from(rest));
+    }
+    if location.contains("://") {
+        return None;
+    }
+    Some(PathBuf::from(location))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DownloadConfig {
+    // Base URLs tried in order before the registry URL. The path of the
+    // registry URL is appended, so a mirror must keep the upstream layout.
+    pub mirrors: Vec<String>,
+    // Extra attempts per URL after a transient failure, waiting
+    // `backoff_ms * 2^attempt` in between.
+    pub retries: u32,
+    pub backoff_ms: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            mirrors: Vec::new(),
+            retries: 3,
+            backoff_ms: 500,
+            timeout_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DownloadEvent<'a> {
+    Started {
+        file_name: &'a str,
+        url: &'a str,
+        // Bytes already on disk from an interrupted download.
+        resumed_from: u64,
+        total: Option<u64>,
+    },
+    Progress {
+        file_name: &'a str,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Retrying {
+        url: &'a str,
+        attempt: u32,
+        delay: Duration,
+        error: &'a RapidOcrError,
+    },
+    Finished {
+        file_name: &'a str,
+        path: &'a Path,
+    },
+    // A registry model entry without a SHA256; it is used as found.
+    Unverified {
+        url: &'a str,
+    },
+}
+
+pub trait DownloadProgress: Send + Sync {
+    fn on_event(&self, event: &DownloadEvent<'_>);
+}
+
+impl fmt::Debug for dyn DownloadProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DownloadProgress")
+    }
+}
+
+#[cfg(feature = "download")]
+enum AttemptError {
+    Transient(RapidOcrError),
+    Fatal(RapidOcrError),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModelDownloader {
+    config: DownloadConfig,
+    progress: Option<Arc<dyn DownloadProgress>>,
+}
+
+impl ModelDownloader {
+    pub fn new(config: DownloadConfig) -> Self {
+        Self {
+            config,
+            progress: None,
+        }
+    }
 
-    let file_name = extract_file_name(file_url)?;
-    let target_path = save_dir.join(file_name);
+    pub fn with_config(mut self, config: DownloadConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-    if target_path.exists() {
-        if let Some(expected) = expected_sha256 {
-            let actual = sha256_file(&target_path)?;
-            if actual.eq_ignore_ascii_case(expected) {
-                return Ok(target_path);
+    pub fn with_progress(mut self, progress: Arc<dyn DownloadProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn config(&self) -> &DownloadConfig {
+        &self.config
+    }
+
+    // Local registry entries are used in place (after the checksum check);
+    // remote ones are downloaded into `save_dir` once. Interrupted downloads
+    // resume from their `.part` file, and a `.lock` file next to it keeps
+    // processes sharing `save_dir` from writing the same file.
+    pub fn fetch(
+        &self,
+        file_url: &str,
+        expected_sha256: Option<&str>,
+        save_dir: impl AsRef<Path>,
+    ) -> Result<PathBuf> {
+        if let Some(local) = local_model_path(file_url) {
+            let path = verify_existing_file(local)?;
+            if let Some(expected) = expected_sha256 {
+                let actual = sha256_file(&path)?;
+                if !actual.eq_ignore_ascii_case(expected) {
+                    return Err(RapidOcrError::HashMismatch {
+                        path,
+                        expected: expected.to_string(),
+                        actual,
+                    });
+                }
             }
-        } else {
+            return Ok(path);
+        }
+
+        let save_dir = save_dir.as_ref();
+        fs::create_dir_all(save_dir)?;
+
+        let file_name = extract_file_name(file_url)?;
+        let target_path = save_dir.join(&file_name);
+        if is_complete(&target_path, expected_sha256)? {
+            return Ok(target_path);
+        }
+
+        self.download_locked(file_url, &file_name, &target_path, expected_sha256)
+    }
+
+    // `fetch` for callers that may not download: local entries are used in
+    // place and remote ones only when already complete in `save_dir`.
+    pub fn fetch_offline(
+        &self,
+        file_url: &str,
+        expected_sha256: Option<&str>,
+        save_dir: impl AsRef<Path>,
+    ) -> Result<PathBuf> {
+        if local_model_path(file_url).is_some() {
+            return self.fetch(file_url, expected_sha256, save_dir);
+        }
+        let save_dir = save_dir.as_ref();
+        let target_path = save_dir.join(extract_file_name(file_url)?);
+        if is_complete(&target_path, expected_sha256)? {
             return Ok(target_path);
         }
+        Err(RapidOcrError::Config(format!(
+            "{file_url} is not in {} and allow_download=false",
+            save_dir.display()
+        )))
     }
 
-    let tmp_path = target_path.with_extension("part");
-    if tmp_path.exists() {
-        let _ = fs::remove_file(&tmp_path);
+    pub(crate) fn report_unverified(&self, url: &str) {
+        self.emit(&DownloadEvent::Unverified { url });
     }
 
-    let client = build_http_client()?;
-    let mut response = client
-        .get(file_url)
-        .header(
-            reqwest::header::USER_AGENT,
-            "Mozilla/5.0 (compatible; rapid-ocr-rs model downloader)",
-        )
-        .header(reqwest::header::REFERER, "https://www.modelscope.cn/")
-        .send()?;
-    if !response.status().is_success() {
-        return Err(RapidOcrError::Download(format!(
-            "failed to download {file_url}: HTTP {}",
-            response.status()
-        )));
+    #[cfg(not(feature = "download"))]
+    fn download_locked(
+        &self,
+        file_url: &str,
+        _file_name: &str,
+        _target_path: &Path,
+        _expected_sha256: Option<&str>,
+    ) -> Result<PathBuf> {
+        Err(RapidOcrError::Download(format!(
+            "cannot fetch {file_url}: built without the `download` feature"
+        )))
     }
 
-    let mut hasher = Sha256::new();
-    let mut file = fs::File::create(&tmp_path)?;
-    let mut buf = [0_u8; 16 * 1024];
-    loop {
-        let read = response.read(&mut buf)?;
-        if read == 0 {
-            break;
-        }
-        hasher.update(&buf[..read]);
-        file.write_all(&buf[..read])?;
-    }
-    file.flush()?;
-    file.sync_all()?;
-
-    if let Some(expected) = expected_sha256 {
-        let actual = format!("{:x}", hasher.finalize());
-        if !actual.eq_ignore_ascii_case(expected) {
-            let _ = fs::remove_file(&tmp_path);
-            return Err(RapidOcrError::HashMismatch {
+    #[cfg(feature = "download")]
+    fn download_locked(
+        &self,
+        file_url: &str,
+        file_name: &str,
+        target_path: &Path,
+        expected_sha256: Option<&str>,
+    ) -> Result<PathBuf> {
+        let lock_path = sidecar_path(target_path, "lock");
+        let lock = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)?;
+        lock.lock()?;
+        // Another process may have finished the file while we waited.
+        if is_complete(target_path, expected_sha256)? {
+            return Ok(target_path.to_path_buf());
+        }
+
+        let tmp_path = sidecar_path(target_path, "part");
+        let client = Client::builder()
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .build()?;
+        let mut last_error = None;
+        for url in self.candidate_urls(file_url) {
+            let actual = match self.download_with_retries(&client, &url, file_name, &tmp_path) {
+                Ok(actual) => actual,
+                Err(err) => {
+                    last_error = Some(err);
+                    continue;
+                }
+            };
+            if let Some(expected) = expected_sha256
+                && !actual.eq_ignore_ascii_case(expected)
+            {
+                let _ = fs::remove_file(&tmp_path);
+                last_error = Some(RapidOcrError::HashMismatch {
+                    path: target_path.to_path_buf(),
+                    expected: expected.to_string(),
+                    actual,
+                });
+                continue;
+            }
+
+            if target_path.exists() {
+                fs::remove_file(target_path)?;
+            }
+            fs::rename(&tmp_path, target_path)?;
+            // Waiters holding the old lock file re-check the finished file
+            // before downloading, so it can go while still locked.
+            let _ = fs::remove_file(&lock_path);
+            self.emit(&DownloadEvent::Finished {
+                file_name,
                 path: target_path,
-                expected: expected.to_string(),
-                actual,
             });
+            return Ok(target_path.to_path_buf());
         }
+        Err(last_error.unwrap_or_else(|| {
+            RapidOcrError::Download(format!("no download location for {file_url}"))
+        }))
     }
 
-    if target_path.exists() {
-        fs::remove_file(&target_path)?;
+    #[cfg(feature = "download")]
+    fn candidate_urls(&self, file_url: &str) -> Vec<String> {
+        let mut urls: Vec<String> = self
+            .config
+            .mirrors
+            .iter()
+            .filter_map(|base| mirror_url(base, file_url))
+            .collect();
+        urls.push(file_url.to_string());
+        urls.dedup();
+        urls
+    }
+
+    #[cfg(feature = "download")]
+    fn download_with_retries(
+        &self,
+        client: &Client,
+        url: &str,
+        file_name: &str,
+        tmp_path: &Path,
+    ) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            match self.download_once(client, url, file_name, tmp_path) {
+                Ok(actual) => return Ok(actual),
+                Err(AttemptError::Transient(err)) if attempt < self.config.retries => {
+                    let delay = backoff_delay(self.config.backoff_ms, attempt);
+                    attempt += 1;
+                    self.emit(&DownloadEvent::Retrying {
+                        url,
+                        attempt,
+                        delay,
+                        error: &err,
+                    });
+                    thread::sleep(delay);
+                }
+                Err(AttemptError::Transient(err) | AttemptError::Fatal(err)) => return Err(err),
+            }
+        }
     }
-    fs::rename(&tmp_path, &target_path)?;
 
-    Ok(target_path)
+    // Returns the SHA256 of the completed `.part` file.
+    #[cfg(feature = "download")]
+    fn download_once(
+        &self,
+        client: &Client,
+        url: &str,
+        file_name: &str,
+        tmp_path: &Path,
+    ) -> std::result::Result<String, AttemptError> {
+        let transient = |err: RapidOcrError| AttemptError::Transient(err);
+        let offset = fs::metadata(tmp_path).map(|m| m.len()).unwrap_or(0);
+        let mut request = client
+            .get(url)
+            .header(
+                reqwest::header::USER_AGENT,
+                "Mozilla/5.0 (compatible; rapid-ocr-rs model downloader)",
+            )
+            .header(reqwest::header::REFERER, "https://www.modelscope.cn/");
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let mut response = request.send().map_err(|err| transient(err.into()))?;
+
+        let status = response.status();
+        let resumed = status == StatusCode::PARTIAL_CONTENT
+            && offset > 0
+            && response
+                .headers()
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_content_range)
+                .is_some_and(|(start, _)| start == offset);
+        if status == StatusCode::RANGE_NOT_SATISFIABLE || (status.is_success() && !resumed) {
+            // The server ignored or rejected the range; start over.
+            let _ = fs::remove_file(tmp_path);
+        }
+        if !status.is_success() {
+            let err = RapidOcrError::Download(format!("failed to download {url}: HTTP {status}"));
+            let retry = status.is_server_error()
+                || status == StatusCode::REQUEST_TIMEOUT
+                || status == StatusCode::TOO_MANY_REQUESTS
+                || status == StatusCode::RANGE_NOT_SATISFIABLE;
+            return Err(if retry {
+                transient(err)
+            } else {
+                AttemptError::Fatal(err)
+            });
+        }
+
+        let mut hasher = Sha256::new();
+        let mut downloaded = 0;
+        let mut file = if resumed {
+            let mut existing = fs::File::open(tmp_path).map_err(|err| transient(err.into()))?;
+            io::copy(&mut existing, &mut hasher).map_err(|err| transient(err.into()))?;
+            downloaded = offset;
+            fs::OpenOptions::new().append(true).open(tmp_path)
+        } else {
+            fs::File::create(tmp_path)
+        }
+        .map_err(|err| AttemptError::Fatal(err.into()))?;
+        let total = response.content_length().map(|len| len + downloaded);
+        self.emit(&DownloadEvent::Started {
+            file_name,
+            url,
+            resumed_from: downloaded,
+            total,
+        });
+
+        let mut buf = [0_u8; 16 * 1024];
+        loop {
+            let read = response
+                .read(&mut buf)
+                .map_err(|err| transient(err.into()))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            file.write_all(&buf[..read])
+                .map_err(|err| AttemptError::Fatal(err.into()))?;
+            downloaded += read as u64;
+            self.emit(&DownloadEvent::Progress {
+                file_name,
+                downloaded,
+                total,
+            });
+        }
+        file.flush()
+            .and_then(|()| file.sync_all())
+            .map_err(|err| AttemptError::Fatal(err.into()))?;
+        if let Some(total) = total
+            && downloaded < total
+        {
+            return Err(transient(RapidOcrError::Download(format!(
+                "download of {url} ended early: {downloaded} of {total} bytes"
+            ))));
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn emit(&self, event: &DownloadEvent<'_>) {
+        if let Some(progress) = &self.progress {
+            progress.on_event(event);
+        }
+    }
+}
+
+// `<name>.part` holds the bytes of an unfinished download of `<name>`, and
+// `<name>.lock` keeps processes from writing it at the same time.
+pub(crate) fn sidecar_path(target_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = target_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    target_path.with_file_name(name)
 }
 
-fn build_http_client() -> Result<Client> {
-    Client::builder()
-        .timeout(Duration::from_secs(60))
-        .build()
-        .map_err(Into::into)
+// Held while no download of a file can start; the lock file, if any, stays
+// locked until this is dropped.
+pub(crate) struct IdleDownload {
+    _lock: Option<fs::File>,
+}
+
+// Takes the download lock of `target_path` without waiting or creating a lock
+// file; `None` while a download of it is running.
+pub(crate) fn try_lock_download(target_path: &Path) -> Result<Option<IdleDownload>> {
+    let lock = match fs::OpenOptions::new()
+        .write(true)
+        .open(sidecar_path(target_path, "lock"))
+    {
+        Ok(lock) => lock,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Some(IdleDownload { _lock: None }));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    match lock.try_lock() {
+        Ok(()) => Ok(Some(IdleDownload { _lock: Some(lock) })),
+        Err(fs::TryLockError::WouldBlock) => Ok(None),
+        Err(fs::TryLockError::Error(err)) => Err(err.into()),
+    }
 }
 
-fn extract_file_name(url: &str) -> Result<String> {
+fn is_complete(path: &Path, expected_sha256: Option<&str>) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    match expected_sha256 {
+        Some(expected) => Ok(sha256_file(path)?.eq_ignore_ascii_case(expected)),
+        None => Ok(true),
+    }
+}
+
+// `https://www.modelscope.cn/models/a/b.onnx` on mirror `https://m.example/ms`
+// becomes `https://m.example/ms/models/a/b.onnx`.
+#[cfg(feature = "download")]
+fn mirror_url(base: &str, file_url: &str) -> Option<String> {
+    let (_, rest) = file_url.split_once("://")?;
+    let path = rest.find('/').map_or("", |idx| &rest[idx..]);
+    Some(format!("{}{path}", base.trim_end_matches('/')))
+}
+
+// `bytes 100-199/1000` -> (100, Some(1000)); the total may be `*`.
+#[cfg(feature = "download")]
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+#[cfg(feature = "download")]
+fn backoff_delay(backoff_ms: u64, attempt: u32) -> Duration {
+    Duration::from_millis(backoff_ms.saturating_mul(1_u64 << attempt.min(16)))
+}
+
+pub(crate) fn extract_file_name(url: &str) -> Result<String> {
     let trimmed = url.split('?').next().unwrap_or(url);
     let file_name = trimmed
         .rsplit('/')
@@ -130,9 +537,243 @@ fn extract_file_name(url: &str) -> Result<String> {
     Ok(file_name.to_string())
 }
 
-fn sha256_file(path: impl AsRef<Path>) -> Result<String> {
+pub(crate) fn sha256_file(path: impl AsRef<Path>) -> Result<String> {
     let bytes = fs::read(path.as_ref())?;
     let mut hasher = Sha256::new();
     hasher.update(bytes);
     Ok(format!("{:x}", hasher.finalize()))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsString, path::PathBuf};
+    #[cfg(feature = "download")]
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    #[cfg(feature = "download")]
+    use super::{
+        DownloadConfig, DownloadEvent, DownloadProgress, backoff_delay, mirror_url,
+        parse_content_range,
+    };
+    use super::{ModelDownloader, local_model_path, model_store_dir_from};
+    use crate::error::RapidOcrError;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    fn model_store_dir_prefers_env_override_then_user_cache() {
+        let vars = [
+            ("RAPID_OCR_MODEL_DIR", "/srv/models"),
+            ("XDG_CACHE_HOME", "/cache"),
+        ];
+        assert_eq!(
+            model_store_dir_from(env(&vars)),
+            PathBuf::from("/srv/models")
+        );
+
+        let vars = [("RAPID_OCR_MODEL_DIR", ""), ("XDG_CACHE_HOME", "/cache")];
+        assert_eq!(
+            model_store_dir_from(env(&vars)),
+            PathBuf::from("/cache/rapid-ocr-rs/models")
+        );
+
+        let vars = [("HOME", "/home/ocr")];
+        assert_eq!(
+            model_store_dir_from(env(&vars)),
+            PathBuf::from("/home/ocr/.cache/rapid-ocr-rs/models")
+        );
+
+        assert_eq!(model_store_dir_from(env(&[])), PathBuf::from("models"));
+    }
+
+    #[test]
+    fn local_locations_are_recognized() {
+        assert_eq!(
+            local_model_path("file:///opt/models/det.onnx"),
+            Some(PathBuf::from("/opt/models/det.onnx"))
+        );
+        assert_eq!(
+            local_model_path("file:///C:/models/det.onnx"),
+            Some(PathBuf::from("C:/models/det.onnx"))
+        );
+        assert_eq!(
+            local_model_path("bundle/det.onnx"),
+            Some(PathBuf::from("bundle/det.onnx"))
+        );
+        assert_eq!(local_model_path("https://example.com/det.onnx"), None);
+        assert_eq!(local_model_path("s3://bucket/det.onnx"), None);
+    }
+
+    #[test]
+    fn local_entries_are_checked_in_place() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-local-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let model = dir.join("det.onnx");
+        std::fs::write(&model, b"abc").expect("write model");
+        let url = format!("file://{}", model.display());
+        // sha256("abc")
+        let sha = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let unused_store = dir.join("store");
+
+        let downloader = ModelDownloader::default();
+        let path = downloader
+            .fetch(&url, Some(sha), &unused_store)
+            .expect("checksum matches");
+        assert_eq!(path, model);
+        assert!(!unused_store.exists());
+
+        let err = downloader
+            .fetch(&url, Some(&sha.replace('b', "c")), &unused_store)
+            .expect_err("checksum mismatch");
+        assert!(matches!(err, RapidOcrError::HashMismatch { .. }));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn offline_fetch_uses_local_entries_and_complete_cached_files() {
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-offline-{}", std::process::id()));
+        let store = dir.join("store");
+        std::fs::create_dir_all(&store).expect("create dir");
+        let local = dir.join("det.onnx");
+        std::fs::write(&local, b"abc").expect("write model");
+        // sha256("abc")
+        let sha = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+        let downloader = ModelDownloader::default();
+        let path = downloader
+            .fetch_offline(&local.display().to_string(), Some(sha), &store)
+            .expect("local entry");
+        assert_eq!(path, local);
+
+        let url = "https://example.com/models/rec.onnx";
+        let err = downloader
+            .fetch_offline(url, Some(sha), &store)
+            .expect_err("remote entry is not cached");
+        assert!(err.to_string().contains("allow_download=false"));
+
+        std::fs::write(store.join("rec.onnx"), b"abc").expect("write cached model");
+        let path = downloader
+            .fetch_offline(url, Some(sha), &store)
+            .expect("cached copy");
+        assert_eq!(path, store.join("rec.onnx"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[cfg(feature = "download")]
+    fn mirror_urls_keep_the_upstream_path() {
+        let url = "https://www.modelscope.cn/models/RapidAI/RapidOCR/resolve/v3.4.0/det.onnx";
+        assert_eq!(
+            mirror_url("https://mirror.example/ms/", url).as_deref(),
+            Some("https://mirror.example/ms/models/RapidAI/RapidOCR/resolve/v3.4.0/det.onnx")
+        );
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            Some((100, Some(1000)))
+        );
+        assert_eq!(parse_content_range("bytes 5-9/*"), Some((5, None)));
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+        assert_eq!(backoff_delay(500, 0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(500, 3), Duration::from_millis(4000));
+    }
+
+    #[cfg(feature = "download")]
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[cfg(feature = "download")]
+    impl DownloadProgress for Recorder {
+        fn on_event(&self, event: &DownloadEvent<'_>) {
+            let line = match *event {
+                DownloadEvent::Started { resumed_from, .. } => format!("start {resumed_from}"),
+                DownloadEvent::Retrying { attempt, .. } => format!("retry {attempt}"),
+                DownloadEvent::Finished { .. } => "finish".to_string(),
+                DownloadEvent::Unverified { .. } => "unverified".to_string(),
+                DownloadEvent::Progress { .. } => return,
+            };
+            self.0.lock().expect("recorder lock").push(line);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "download")]
+    fn downloads_fall_back_from_dead_mirror_and_resume_part_file() {
+        let body = b"0123456789abcdef";
+        let server = TcpListener::bind("127.0.0.1:0").expect("bind server");
+        let port = server.local_addr().expect("server addr").port();
+        // The mirror drops every connection unanswered, once per attempt.
+        let dead = TcpListener::bind("127.0.0.1:0").expect("bind dead mirror");
+        let dead_port = dead.local_addr().expect("dead addr").port();
+        let dead_handle = thread::spawn(move || {
+            for stream in dead.incoming().take(2) {
+                drop(stream.expect("accept"));
+            }
+        });
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = server.accept().expect("accept");
+            let mut request = Vec::new();
+            let mut buf = [0_u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let read = stream.read(&mut buf).expect("read request");
+                assert!(read > 0, "connection closed early");
+                request.extend_from_slice(&buf[..read]);
+            }
+            let request = String::from_utf8_lossy(&request).to_lowercase();
+            assert!(request.contains("range: bytes=6-"), "{request}");
+            let rest = &body[6..];
+            write!(
+                stream,
+                "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes 6-15/16\r\nConnection: close\r\n\r\n",
+                rest.len()
+            )
+            .expect("write head");
+            stream.write_all(rest).expect("write body");
+        });
+
+        let dir = std::env::temp_dir().join(format!("rapid-ocr-resume-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create dir");
+        std::fs::write(dir.join("model.onnx.part"), &body[..6]).expect("write part");
+
+        let recorder = Arc::new(Recorder::default());
+        let downloader = ModelDownloader::new(DownloadConfig {
+            mirrors: vec![format!("http://127.0.0.1:{dead_port}/mirror")],
+            retries: 1,
+            backoff_ms: 1,
+            timeout_secs: 5,
+        })
+        .with_progress(recorder.clone());
+        // sha256("0123456789abcdef")
+        let sha = "9f9f5111f7b27a781f1f1ddde5ebc2dd2b796bfc7365c9c28b548e564176929f";
+        let path = downloader
+            .fetch(
+                &format!("http://127.0.0.1:{port}/models/model.onnx"),
+                Some(sha),
+                &dir,
+            )
+            .expect("download resumes from the upstream url");
+        handle.join().expect("server thread");
+        dead_handle.join().expect("dead mirror thread");
+
+        assert_eq!(std::fs::read(&path).expect("read model"), body);
+        assert!(!dir.join("model.onnx.part").exists());
+        assert!(!dir.join("model.onnx.lock").exists());
+        assert_eq!(
+            *recorder.0.lock().expect("recorder lock"),
+            ["retry 1", "start 6", "finish"]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt::Write as _;
+
+use crate::{
+    error::Result,
+    output::hierarchy::{PixelBox, escape_xml, export_blocks},
+    pipeline::types::OcrResult,
+};
+
+pub fn to_alto(result: &OcrResult, width: usize, height: usize) -> Result<String> {
+    let blocks = export_blocks(result, width, height)?;
+
+    let mut out = String::new();
+    out.push_str(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <alto xmlns=\"http://www.loc.gov/standards/alto/ns-v4#\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.loc.gov/standards/alto/ns-v4# \
+         http://www.loc.gov/alto/v4/alto-4-2.xsd\">\n\
+         <Description>\n<MeasurementUnit>pixel</MeasurementUnit>\n\
+         <OCRProcessing ID=\"OCR_0\">\n<ocrProcessingStep>\n<processingSoftware>\n\
+         <softwareName>rapid-ocr-rs</softwareName>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<softwareVersion>{}</softwareVersion>",
+        env!("CARGO_PKG_VERSION")
+    );
+    out.push_str(
+        "</processingSoftware>\n</ocrProcessingStep>\n</OCRProcessing>\n</Description>\n<Layout>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<Page ID=\"page_1\" PHYSICAL_IMG_NR=\"1\" WIDTH=\"{width}\" HEIGHT=\"{height}\">\n\
+         <PrintSpace HPOS=\"0\" VPOS=\"0\" WIDTH=\"{width}\" HEIGHT=\"{height}\">"
+    );
+
+    let (mut line_id, mut word_id) = (0, 0);
+    for (block_idx, block) in blocks.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "<TextBlock ID=\"block_{}\" {}>",
+            block_idx + 1,
+            position(block.bbox)
+        );
+        for line in &block.lines {
+            line_id += 1;
+            let _ = writeln!(
+                out,
+                "<TextLine ID=\"line_{line_id}\" {}>",
+                position(line.bbox)
+            );
+            for (idx, word) in line.words.iter().enumerate() {
+                word_id += 1;
+                if idx > 0 {
+                    out.push_str("<SP/>\n");
+                }
+                let _ = writeln!(
+                    out,
+                    "<String ID=\"string_{word_id}\" {} CONTENT=\"{}\" WC=\"{:.2}\"/>",
+                    position(word.bbox),
+                    escape_xml(&word.text),
+                    word.score.clamp(0.0, 1.0)
+                );
+            }
+            out.push_str("</TextLine>\n");
+        }
+        out.push_str("</TextBlock>\n");
+    }
+    out.push_str("</PrintSpace>\n</Page>\n</Layout>\n</alto>\n");
+    Ok(out)
+}
+
+fn position(bbox: PixelBox) -> String {
+    format!(
+        "HPOS=\"{}\" VPOS=\"{}\" WIDTH=\"{}\" HEIGHT=\"{}\"",
+        bbox[0],
+        bbox[1],
+        bbox[2] - bbox[0],
+        bbox[3] - bbox[1]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output::{alto::to_alto, hierarchy::tests::sample_result};
+
+    #[test]
+    fn alto_emits_text_blocks_lines_and_strings() {
+        let alto = to_alto(&sample_result(false), 200, 160).expect("valid result");
+        assert!(
+            alto.contains(
+                "<Page ID=\"page_1\" PHYSICAL_IMG_NR=\"1\" WIDTH=\"200\" HEIGHT=\"160\">"
+            )
+        );
+        assert_eq!(alto.matches("<TextBlock ").count(), 2);
+        assert!(alto.contains(
+            "<TextBlock ID=\"block_1\" HPOS=\"10\" VPOS=\"10\" WIDTH=\"80\" HEIGHT=\"42\">"
+        ));
+        assert!(alto.contains(
+            "<String ID=\"string_1\" HPOS=\"10\" VPOS=\"10\" WIDTH=\"80\" HEIGHT=\"20\" \
+             CONTENT=\"Hello R&amp;D\" WC=\"0.85\"/>"
+        ));
+        assert!(alto.contains("CONTENT=\"&lt;world&gt;\" WC=\"0.70\""));
+    }
+
+    #[test]
+    fn alto_separates_word_boxes_with_spaces() {
+        let alto = to_alto(&sample_result(true), 200, 160).expect("valid result");
+        assert!(alto.contains(
+            "CONTENT=\"Hello\" WC=\"0.90\"/>\n<SP/>\n<String ID=\"string_2\" \
+             HPOS=\"60\" VPOS=\"10\" WIDTH=\"30\" HEIGHT=\"20\" CONTENT=\"R&amp;D\" WC=\"0.80\"/>"
+        ));
+    }
+}
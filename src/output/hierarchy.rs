@@ -0,0 +1,254 @@
+use crate::{
+    Quad,
+    error::{RapidOcrError, Result},
+    layout::{Span, analyze_layout},
+    pipeline::types::OcrResult,
+    types::WordBox,
+};
+
+// Axis-aligned `[x0, y0, x1, y1]` in whole pixels, as hOCR and ALTO expect.
+pub(crate) type PixelBox = [u32; 4];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExportWord {
+    pub(crate) bbox: PixelBox,
+    pub(crate) text: String,
+    pub(crate) score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExportLine {
+    pub(crate) bbox: PixelBox,
+    pub(crate) score: f32,
+    pub(crate) words: Vec<ExportWord>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExportBlock {
+    pub(crate) bbox: PixelBox,
+    pub(crate) lines: Vec<ExportLine>,
+}
+
+// Only full results carry both boxes and text; other modes yield an empty page.
+pub(crate) fn export_blocks(
+    result: &OcrResult,
+    width: usize,
+    height: usize,
+) -> Result<Vec<ExportBlock>> {
+    let OcrResult::Full(full) = result else {
+        return Ok(Vec::new());
+    };
+    if full.boxes.len() != full.txts.len() || full.scores.len() != full.txts.len() {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "export length mismatch: boxes={}, txts={}, scores={}",
+            full.boxes.len(),
+            full.txts.len(),
+            full.scores.len()
+        )));
+    }
+
+    let page = analyze_layout(&full.boxes, &full.txts)?;
+    let word_boxes = full.word_boxes.as_deref();
+    let blocks = page
+        .blocks
+        .iter()
+        .map(|block| ExportBlock {
+            bbox: pixel_box(&block.bbox, width, height),
+            lines: block
+                .lines
+                .iter()
+                .map(|line| {
+                    let words = line
+                        .spans
+                        .iter()
+                        .flat_map(|span| span_words(span, word_boxes, &full.scores, width, height))
+                        .collect();
+                    let score = line
+                        .spans
+                        .iter()
+                        .map(|span| full.scores[span.index])
+                        .sum::<f32>()
+                        / line.spans.len() as f32;
+                    ExportLine {
+                        bbox: pixel_box(&line.bbox, width, height),
+                        score,
+                        words,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(blocks)
+}
+
+// Word boxes are used when recognition produced them; otherwise the whole
+// detected box becomes a single word.
+fn span_words(
+    span: &Span,
+    word_boxes: Option<&[Vec<WordBox>]>,
+    scores: &[f32],
+    width: usize,
+    height: usize,
+) -> Vec<ExportWord> {
+    match word_boxes.and_then(|all| all.get(span.index)) {
+        Some(words) if !words.is_empty() => words
+            .iter()
+            .map(|word| ExportWord {
+                bbox: pixel_box(&word.bbox, width, height),
+                text: word.text.clone(),
+                score: word.score,
+            })
+            .collect(),
+        _ => vec![ExportWord {
+            bbox: pixel_box(&span.bbox, width, height),
+            text: span.text.clone(),
+            score: scores[span.index],
+        }],
+    }
+}
+
+fn pixel_box(quad: &Quad, width: usize, height: usize) -> PixelBox {
+    let (mut x0, mut y0) = (f32::INFINITY, f32::INFINITY);
+    let (mut x1, mut y1) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for p in quad {
+        x0 = x0.min(p[0]);
+        y0 = y0.min(p[1]);
+        x1 = x1.max(p[0]);
+        y1 = y1.max(p[1]);
+    }
+    let clamp = |v: f32, max: usize| v.clamp(0.0, max as f32) as u32;
+    [
+        clamp(x0.floor(), width),
+        clamp(y0.floor(), height),
+        clamp(x1.ceil(), width),
+        clamp(y1.ceil(), height),
+    ]
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than tab/newline are not allowed in XML 1.0.
+            c if c.is_control() && c != '\t' && c != '\n' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        Quad,
+        output::hierarchy::{escape_xml, export_blocks},
+        pipeline::{
+            rapid_ocr::filter_by_text_score_for_full,
+            types::{FullResult, OcrResult},
+        },
+        types::{LineResult, TextOrientation, WordBox},
+    };
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Quad {
+        [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+    }
+
+    fn line(text: &str, score: f32) -> LineResult {
+        LineResult {
+            text: text.to_string(),
+            score,
+            word_info: None,
+            orientation: TextOrientation::Horizontal,
+            chars: None,
+            lang: None,
+        }
+    }
+
+    fn word(text: &str, score: f32, bbox: Quad) -> WordBox {
+        WordBox {
+            text: text.to_string(),
+            score,
+            bbox,
+        }
+    }
+
+    // Goes through the pipeline's text score filter, which drops the noise
+    // line and keeps `<world>` without word boxes.
+    pub(crate) fn sample_result(with_words: bool) -> OcrResult {
+        let word_boxes = with_words.then(|| {
+            vec![
+                vec![
+                    word("Hello", 0.9, rect(10.0, 10.0, 50.0, 30.0)),
+                    word("R&D", 0.8, rect(60.0, 10.0, 90.0, 30.0)),
+                ],
+                Vec::new(),
+                vec![word("noise", 0.1, rect(120.0, 60.0, 180.0, 80.0))],
+                vec![word("end", 0.99, rect(10.0, 120.0, 60.0, 140.0))],
+            ]
+        });
+        let (boxes, det_scores, lines, word_boxes) = filter_by_text_score_for_full(
+            vec![
+                rect(10.0, 10.0, 90.0, 30.0),
+                rect(10.5, 32.0, 70.2, 52.0),
+                rect(120.0, 60.0, 180.0, 80.0),
+                rect(10.0, 120.0, 60.0, 140.0),
+            ],
+            vec![0.9; 4],
+            vec![
+                line("Hello R&D", 0.85),
+                line("<world>", 0.7),
+                line("noise", 0.1),
+                line("end", 0.99),
+            ],
+            word_boxes,
+            0.5,
+        );
+        OcrResult::Full(FullResult {
+            boxes,
+            det_scores,
+            txts: lines.iter().map(|l| l.text.clone()).collect(),
+            scores: lines.iter().map(|l| l.score).collect(),
+            lines,
+            word_boxes,
+            ..FullResult::default()
+        })
+    }
+
+    #[test]
+    fn blocks_split_on_large_vertical_gaps() {
+        let blocks = export_blocks(&sample_result(false), 200, 160).expect("valid result");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].bbox, [10, 10, 90, 52]);
+        assert_eq!(blocks[0].lines.len(), 2);
+        assert_eq!(blocks[0].lines[1].bbox, [10, 32, 71, 52]);
+        assert_eq!(blocks[0].lines[1].words[0].text, "<world>");
+        assert_eq!(blocks[1].lines[0].words[0].bbox, [10, 120, 60, 140]);
+    }
+
+    #[test]
+    fn word_boxes_replace_line_words_when_present() {
+        let blocks = export_blocks(&sample_result(true), 200, 160).expect("valid result");
+        let words = &blocks[0].lines[0].words;
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[1].bbox, [60, 10, 90, 30]);
+        // Lines without word boxes still fall back to the line quad, and
+        // later lines keep their own word boxes.
+        assert_eq!(blocks[0].lines[1].words.len(), 1);
+        assert_eq!(blocks[0].lines[1].words[0].text, "<world>");
+        assert_eq!(blocks[0].lines[1].words[0].bbox, [10, 32, 71, 52]);
+        let last = &blocks[1].lines[0].words;
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].text, "end");
+        assert_eq!(last[0].score, 0.99);
+    }
+
+    #[test]
+    fn xml_escaping_drops_control_characters() {
+        assert_eq!(escape_xml("a<b>&\"'\u{1}"), "a&lt;b&gt;&amp;&quot;&apos;");
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write as _;
+
+use crate::{
+    error::Result,
+    output::hierarchy::{PixelBox, escape_xml, export_blocks},
+    pipeline::types::OcrResult,
+};
+
+pub fn to_hocr(result: &OcrResult, width: usize, height: usize) -> Result<String> {
+    let blocks = export_blocks(result, width, height)?;
+
+    let mut out = String::new();
+    out.push_str(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \
+         \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\">\n\
+         <head>\n<title></title>\n\
+         <meta http-equiv=\"Content-Type\" content=\"text/html;charset=utf-8\"/>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<meta name=\"ocr-system\" content=\"rapid-ocr-rs {}\"/>",
+        env!("CARGO_PKG_VERSION")
+    );
+    out.push_str(
+        "<meta name=\"ocr-capabilities\" content=\"ocr_page ocr_carea ocr_line ocrx_word\"/>\n\
+         </head>\n<body>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<div class=\"ocr_page\" id=\"page_1\" title=\"bbox 0 0 {width} {height}\">"
+    );
+
+    let (mut line_id, mut word_id) = (0, 0);
+    for (block_idx, block) in blocks.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "<div class=\"ocr_carea\" id=\"block_1_{}\" title=\"{}\">",
+            block_idx + 1,
+            bbox_title(block.bbox)
+        );
+        for line in &block.lines {
+            line_id += 1;
+            let _ = write!(
+                out,
+                "<span class=\"ocr_line\" id=\"line_1_{line_id}\" title=\"{}; x_wconf {}\">",
+                bbox_title(line.bbox),
+                wconf(line.score)
+            );
+            for (idx, word) in line.words.iter().enumerate() {
+                word_id += 1;
+                if idx > 0 {
+                    out.push(' ');
+                }
+                let _ = write!(
+                    out,
+                    "<span class=\"ocrx_word\" id=\"word_1_{word_id}\" title=\"{}; x_wconf {}\">{}</span>",
+                    bbox_title(word.bbox),
+                    wconf(word.score),
+                    escape_xml(&word.text)
+                );
+            }
+            out.push_str("</span>\n");
+        }
+        out.push_str("</div>\n");
+    }
+    out.push_str("</div>\n</body>\n</html>\n");
+    Ok(out)
+}
+
+fn bbox_title(bbox: PixelBox) -> String {
+    format!("bbox {} {} {} {}", bbox[0], bbox[1], bbox[2], bbox[3])
+}
+
+// hOCR confidences are integer percentages.
+fn wconf(score: f32) -> u32 {
+    (score.clamp(0.0, 1.0) * 100.0).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        output::{hierarchy::tests::sample_result, hocr::to_hocr},
+        pipeline::types::OcrResult,
+    };
+
+    #[test]
+    fn hocr_nests_page_blocks_lines_and_words() {
+        let hocr = to_hocr(&sample_result(true), 200, 160).expect("valid result");
+        assert!(hocr.contains("<div class=\"ocr_page\" id=\"page_1\" title=\"bbox 0 0 200 160\">"));
+        assert_eq!(hocr.matches("class=\"ocr_carea\"").count(), 2);
+        assert_eq!(hocr.matches("class=\"ocr_line\"").count(), 3);
+        assert!(hocr.contains(
+            "<span class=\"ocr_line\" id=\"line_1_1\" title=\"bbox 10 10 90 30; x_wconf 85\">\
+             <span class=\"ocrx_word\" id=\"word_1_1\" title=\"bbox 10 10 50 30; x_wconf 90\">Hello</span> \
+             <span class=\"ocrx_word\" id=\"word_1_2\" title=\"bbox 60 10 90 30; x_wconf 80\">R&amp;D</span></span>"
+        ));
+        assert!(hocr.contains(">&lt;world&gt;</span>"));
+    }
+
+    #[test]
+    fn hocr_for_non_full_result_is_an_empty_page() {
+        let hocr = to_hocr(&OcrResult::Empty, 10, 20).expect("empty result");
+        assert!(hocr.contains("title=\"bbox 0 0 10 20\""));
+        assert!(!hocr.contains("ocr_line\""));
+    }
+}
@@ -1,6 +1,10 @@
 use serde::Serialize;
 
-use crate::{Quad, error::Result};
+use crate::{
+    Quad,
+    error::{RapidOcrError, Result},
+    types::{CharDetail, LineResult},
+};
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct OcrJsonItem {
@@ -8,6 +12,8 @@ pub struct OcrJsonItem {
     pub box_: Option<[[f64; 2]; 4]>,
     pub txt: String,
     pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chars: Option<Vec<CharDetail>>,
 }
 
 pub fn to_json_items(
@@ -48,14 +54,36 @@ pub fn to_json_items(
             box_: out_box,
             txt: txts[i].clone(),
             score: scores[i] as f64,
+            chars: None,
         });
     }
     Ok(out)
 }
 
+// Lines and items stay index-aligned through score filtering. Outputs built
+// without lines have no char details to attach; any other length mismatch
+// means the two were filtered apart.
+pub(crate) fn attach_char_details(items: &mut [OcrJsonItem], lines: &[LineResult]) -> Result<()> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    if items.len() != lines.len() {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "char details length mismatch: items={}, lines={}",
+            items.len(),
+            lines.len()
+        )));
+    }
+    for (item, line) in items.iter_mut().zip(lines) {
+        item.chars.clone_from(&line.chars);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::to_json_items;
+    use super::{attach_char_details, to_json_items};
+    use crate::types::{CharDetail, LineResult, TextOrientation};
 
     #[test]
     fn json_items_none_for_empty_inputs() {
@@ -64,4 +92,36 @@ mod tests {
             Vec::new()
         );
     }
+
+    #[test]
+    fn json_items_serialize_char_details_only_when_present() {
+        let mut items = to_json_items(None, &["a".to_string()], &[0.9]).expect("valid items");
+        let plain = serde_json::to_value(&items).expect("serialize");
+        assert!(plain[0].get("chars").is_none());
+
+        let line = LineResult {
+            text: "a".to_string(),
+            score: 0.9,
+            word_info: None,
+            orientation: TextOrientation::Horizontal,
+            chars: Some(vec![CharDetail {
+                text: "a".to_string(),
+                prob: 0.9,
+                start: 1,
+                end: 3,
+                alternatives: Vec::new(),
+            }]),
+            lang: None,
+        };
+        attach_char_details(&mut items, &[]).expect("no lines to attach");
+        assert!(items[0].chars.is_none());
+
+        attach_char_details(&mut items, std::slice::from_ref(&line)).expect("aligned lines");
+        let detailed = serde_json::to_value(&items).expect("serialize");
+        assert_eq!(detailed[0]["chars"][0]["end"], 3);
+
+        let err =
+            attach_char_details(&mut items, &[line.clone(), line]).expect_err("misaligned lines");
+        assert!(err.to_string().contains("items=1, lines=2"));
+    }
 }
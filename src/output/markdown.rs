@@ -1,13 +1,8 @@
-use crate::{Quad, error::Result};
-
-#[derive(Debug, Clone, Copy)]
-struct BoxProps {
-    top: f32,
-    bottom: f32,
-    left: f32,
-    height: f32,
-    center_y: f32,
-}
+use crate::{
+    Quad,
+    error::Result,
+    layout::{analyze_layout, render_page},
+};
 
 pub fn to_markdown(boxes: &[Quad], txts: &[String]) -> Result<String> {
     if boxes.len() != txts.len() {
@@ -21,56 +16,10 @@ pub fn to_markdown(boxes: &[Quad], txts: &[String]) -> Result<String> {
         return Ok("No text detected.".to_string());
     }
 
-    let mut combined: Vec<(Quad, String)> = (0..boxes.len())
-        .map(|i| (boxes[i], txts[i].clone()))
-        .collect();
-    combined.sort_by(|(a_box, _), (b_box, _)| {
-        let a = get_box_properties(a_box);
-        let b = get_box_properties(b_box);
-        a.top
-            .partial_cmp(&b.top)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then(
-                a.left
-                    .partial_cmp(&b.left)
-                    .unwrap_or(std::cmp::Ordering::Equal),
-            )
-    });
-
-    let mut output_lines: Vec<String> = Vec::new();
-    let mut current_line_parts: Vec<String> = vec![combined[0].1.clone()];
-    let mut prev_props = get_box_properties(&combined[0].0);
-
-    for (box_, text) in combined.iter().skip(1) {
-        let current_props = get_box_properties(box_);
-
-        let min_height = current_props.height.min(prev_props.height);
-        let centers_are_close =
-            (current_props.center_y - prev_props.center_y).abs() < (min_height * 0.5);
-
-        let overlap_top = prev_props.top.max(current_props.top);
-        let overlap_bottom = prev_props.bottom.min(current_props.bottom);
-        let has_vertical_overlap = overlap_bottom > overlap_top;
-
-        if centers_are_close || has_vertical_overlap {
-            current_line_parts.push("   ".to_string());
-            current_line_parts.push(text.clone());
-        } else {
-            output_lines.push(current_line_parts.join(""));
-
-            let vertical_gap = current_props.top - prev_props.bottom;
-            if vertical_gap > prev_props.height * 0.7 {
-                output_lines.push(String::new());
-            }
-
-            current_line_parts = vec![text.clone()];
-        }
-
-        prev_props = current_props;
-    }
-
-    output_lines.push(current_line_parts.join(""));
-    Ok(output_lines.join("\n"))
+    // Blocks become paragraphs separated by a blank line; boxes sharing a row
+    // keep the wide spacing they had on the page.
+    let page = analyze_layout(boxes, txts)?;
+    Ok(render_page(&page, "   "))
 }
 
 pub fn to_markdown_texts(txts: &[String]) -> String {
@@ -80,25 +29,6 @@ pub fn to_markdown_texts(txts: &[String]) -> String {
     txts.join("\n")
 }
 
-fn get_box_properties(box_: &Quad) -> BoxProps {
-    let mut top = f32::INFINITY;
-    let mut bottom = f32::NEG_INFINITY;
-    let mut left = f32::INFINITY;
-    for point in box_ {
-        top = top.min(point[1]);
-        bottom = bottom.max(point[1]);
-        left = left.min(point[0]);
-    }
-    let height = bottom - top;
-    BoxProps {
-        top,
-        bottom,
-        left,
-        height,
-        center_y: top + height / 2.0,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::to_markdown;
@@ -110,4 +40,20 @@ mod tests {
             "No text detected."
         );
     }
+
+    #[test]
+    fn markdown_keeps_columns_apart() {
+        let rect = |x0: f32, y0: f32, x1: f32, y1: f32| [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+        let boxes = vec![
+            rect(10.0, 10.0, 180.0, 30.0),
+            rect(220.0, 10.0, 390.0, 30.0),
+            rect(10.0, 35.0, 180.0, 55.0),
+            rect(220.0, 35.0, 390.0, 55.0),
+        ];
+        let txts = ["a1", "b1", "a2", "b2"].map(String::from).to_vec();
+        assert_eq!(
+            to_markdown(&boxes, &txts).expect("valid input"),
+            "a1\na2\n\nb1\nb2"
+        );
+    }
 }
@@ -1,7 +1,18 @@
+pub mod alto;
+pub(crate) mod hierarchy;
+pub mod hocr;
 pub mod json;
 pub mod markdown;
+#[cfg(feature = "searchable-pdf")]
+pub mod searchable_pdf;
+pub mod text;
 pub mod visualize;
 
+pub use alto::to_alto;
+pub use hocr::to_hocr;
 pub use json::{OcrJsonItem, to_json_items};
 pub use markdown::{to_markdown, to_markdown_texts};
+#[cfg(feature = "searchable-pdf")]
+pub use searchable_pdf::{to_searchable_pdf, to_searchable_pdf_with_dpi};
+pub use text::to_plain_text;
 pub use visualize::{draw_ocr_result, draw_word_boxes};
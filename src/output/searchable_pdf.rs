@@ -0,0 +1,487 @@
+use std::{fmt::Write as _, io::Write as _};
+
+use flate2::{Compression, write::ZlibEncoder};
+
+use crate::{
+    Quad,
+    config::RecImage,
+    error::{RapidOcrError, Result},
+    pipeline::types::OcrResult,
+};
+
+const DEFAULT_DPI: f32 = 300.0;
+// Glyph advance of the invisible font, in 1/1000 text space units.
+const GLYPH_WIDTH: f32 = 500.0;
+
+pub fn to_searchable_pdf(image: &RecImage, result: &OcrResult) -> Result<Vec<u8>> {
+    to_searchable_pdf_with_dpi(image, result, DEFAULT_DPI)
+}
+
+pub fn to_searchable_pdf_with_dpi(
+    image: &RecImage,
+    result: &OcrResult,
+    dpi: f32,
+) -> Result<Vec<u8>> {
+    if !(dpi.is_finite() && dpi > 0.0) {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "pdf dpi must be > 0, got {dpi}"
+        )));
+    }
+
+    let scale = 72.0 / dpi;
+    let page_w = image.width() as f32 * scale;
+    let page_h = image.height() as f32 * scale;
+
+    let mut content = String::new();
+    let _ = writeln!(
+        content,
+        "q\n{} 0 0 {} 0 0 cm\n/Im0 Do\nQ",
+        fmt_num(page_w),
+        fmt_num(page_h)
+    );
+    let spans = text_spans(result);
+    let mut codes = GlyphCodes::default();
+    if !spans.is_empty() {
+        // Render mode 3 keeps the text selectable but invisible over the scan.
+        content.push_str("BT\n3 Tr\n");
+        for (quad, text) in spans {
+            write_text_span(&mut content, &mut codes, quad, text, scale, page_h);
+        }
+        content.push_str("ET\n");
+    }
+
+    let mut writer = PdfWriter::default();
+    writer.object(1, b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    writer.object(2, b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec());
+    writer.object(
+        3,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /XObject << /Im0 5 0 R >> /Font << /F0 6 0 R >> >> \
+             /Contents 4 0 R >>",
+            fmt_num(page_w),
+            fmt_num(page_h)
+        )
+        .into_bytes(),
+    );
+    writer.stream(4, "", &deflate(content.as_bytes())?);
+    writer.stream(
+        5,
+        &format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} \
+             /ColorSpace /DeviceRGB /BitsPerComponent 8",
+            image.width(),
+            image.height()
+        ),
+        &deflate(&bgr_to_rgb(image))?,
+    );
+    writer.object(
+        6,
+        b"<< /Type /Font /Subtype /Type0 /BaseFont /GlyphLessFont /Encoding /Identity-H \
+          /DescendantFonts [7 0 R] /ToUnicode 9 0 R >>"
+            .to_vec(),
+    );
+    writer.object(
+        7,
+        format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /GlyphLessFont \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+             /FontDescriptor 8 0 R /DW {GLYPH_WIDTH} /CIDToGIDMap /Identity >>"
+        )
+        .into_bytes(),
+    );
+    writer.object(
+        8,
+        format!(
+            "<< /Type /FontDescriptor /FontName /GlyphLessFont /Flags 5 \
+             /FontBBox [0 0 {GLYPH_WIDTH} 1000] /ItalicAngle 0 /Ascent 1000 /Descent 0 \
+             /CapHeight 1000 /StemV 80 >>"
+        )
+        .into_bytes(),
+    );
+    writer.stream(9, "", &deflate(to_unicode_cmap(&codes.astral).as_bytes())?);
+    Ok(writer.finish(1))
+}
+
+// Two-byte glyph codes. A BMP character is its own code; characters beyond
+// the BMP get codes from the surrogate range, which no BMP character uses, and
+// an explicit ToUnicode entry each.
+#[derive(Debug, Default)]
+struct GlyphCodes {
+    astral: Vec<char>,
+}
+
+impl GlyphCodes {
+    const ASTRAL_BASE: u16 = 0xD800;
+    const ASTRAL_SLOTS: usize = 0x800;
+
+    fn code(&mut self, ch: char) -> u16 {
+        if let Ok(unit) = u16::try_from(u32::from(ch)) {
+            return unit;
+        }
+        let slot = match self.astral.iter().position(|c| *c == ch) {
+            Some(slot) => slot,
+            None if self.astral.len() < Self::ASTRAL_SLOTS => {
+                self.astral.push(ch);
+                self.astral.len() - 1
+            }
+            None => return 0xFFFD,
+        };
+        Self::ASTRAL_BASE + slot as u16
+    }
+}
+
+fn to_unicode_cmap(astral: &[char]) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def
+/CMapName /Adobe-Identity-UCS def
+/CMapType 2 def
+1 begincodespacerange
+<0000> <FFFF>
+endcodespacerange
+2 beginbfrange
+<0000> <D7FF> <0000>
+<E000> <FFFF> <E000>
+endbfrange
+",
+    );
+    // A bfchar block holds at most 100 entries.
+    for (block, chars) in astral.chunks(100).enumerate() {
+        let _ = writeln!(cmap, "{} beginbfchar", chars.len());
+        for (i, ch) in chars.iter().enumerate() {
+            let code = GlyphCodes::ASTRAL_BASE as usize + block * 100 + i;
+            let mut utf16 = [0_u16; 2];
+            let _ = write!(cmap, "<{code:04X}> <");
+            for unit in ch.encode_utf16(&mut utf16) {
+                let _ = write!(cmap, "{unit:04X}");
+            }
+            cmap.push_str(">\n");
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str(
+        "endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end
+",
+    );
+    cmap
+}
+
+// Lines with word boxes contribute one span per word; the rest (vertical
+// lines, or runs without `return_word_box`) fall back to the line box.
+fn text_spans(result: &OcrResult) -> Vec<(Quad, &str)> {
+    let OcrResult::Full(full) = result else {
+        return Vec::new();
+    };
+    let word_boxes = full.word_boxes.as_deref().unwrap_or_default();
+    full.boxes
+        .iter()
+        .zip(&full.txts)
+        .enumerate()
+        .flat_map(|(i, (quad, text))| match word_boxes.get(i) {
+            Some(words) if !words.is_empty() => words
+                .iter()
+                .map(|word| (word.bbox, word.text.as_str()))
+                .collect::<Vec<_>>(),
+            _ => vec![(*quad, text.as_str())],
+        })
+        .collect()
+}
+
+fn write_text_span(
+    content: &mut String,
+    codes: &mut GlyphCodes,
+    quad: Quad,
+    text: &str,
+    scale: f32,
+    page_h: f32,
+) {
+    let units: Vec<u16> = text.chars().map(|ch| codes.code(ch)).collect();
+    if units.is_empty() {
+        return;
+    }
+
+    let to_pdf = |p: [f32; 2]| [p[0] * scale, page_h - p[1] * scale];
+    // Quads run clockwise from the top-left corner; the baseline is the bottom edge.
+    let top_left = to_pdf(quad[0]);
+    let bottom_right = to_pdf(quad[2]);
+    let bottom_left = to_pdf(quad[3]);
+    let dx = bottom_right[0] - bottom_left[0];
+    let dy = bottom_right[1] - bottom_left[1];
+    let width = dx.hypot(dy);
+    let height = (top_left[0] - bottom_left[0]).hypot(top_left[1] - bottom_left[1]);
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+
+    let (sin, cos) = dy.atan2(dx).sin_cos();
+    let natural_width = units.len() as f32 * GLYPH_WIDTH / 1000.0 * height;
+    let _ = writeln!(
+        content,
+        "/F0 {} Tf\n{} Tz\n{} {} {} {} {} {} Tm",
+        fmt_num(height),
+        fmt_num(100.0 * width / natural_width),
+        fmt_num(cos),
+        fmt_num(sin),
+        fmt_num(-sin),
+        fmt_num(cos),
+        fmt_num(bottom_left[0]),
+        fmt_num(bottom_left[1]),
+    );
+    content.push('<');
+    for unit in units {
+        let _ = write!(content, "{unit:04X}");
+    }
+    content.push_str("> Tj\n");
+}
+
+fn fmt_num(v: f32) -> String {
+    let s = format!("{v:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn bgr_to_rgb(image: &RecImage) -> Vec<u8> {
+    let bgr = image.as_bgr_cow();
+    let mut rgb = Vec::with_capacity(bgr.len());
+    for px in bgr.chunks_exact(3) {
+        rgb.extend_from_slice(&[px[2], px[1], px[0]]);
+    }
+    rgb
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+#[derive(Debug, Default)]
+struct PdfWriter {
+    buf: Vec<u8>,
+    offsets: Vec<(u32, usize)>,
+}
+
+impl PdfWriter {
+    fn begin(&mut self, id: u32) {
+        if self.buf.is_empty() {
+            // The binary comment marks the file as 8-bit for transfer tools.
+            self.buf.extend_from_slice(b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n");
+        }
+        self.offsets.push((id, self.buf.len()));
+        self.buf
+            .extend_from_slice(format!("{id} 0 obj\n").as_bytes());
+    }
+
+    fn object(&mut self, id: u32, body: Vec<u8>) {
+        self.begin(id);
+        self.buf.extend_from_slice(&body);
+        self.buf.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, id: u32, dict_entries: &str, data: &[u8]) {
+        self.begin(id);
+        self.buf.extend_from_slice(
+            format!(
+                "<< {dict_entries} /Filter /FlateDecode /Length {} >>\nstream\n",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        self.buf.extend_from_slice(data);
+        self.buf.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self, root: u32) -> Vec<u8> {
+        self.offsets.sort_by_key(|(id, _)| *id);
+        let size = self.offsets.last().map_or(0, |(id, _)| *id) + 1;
+        let xref_offset = self.buf.len();
+        let mut xref = format!("xref\n0 {size}\n0000000000 65535 f \n");
+        let mut offsets = self.offsets.iter().peekable();
+        for id in 1..size {
+            match offsets.next_if(|(obj_id, _)| *obj_id == id) {
+                Some((_, offset)) => {
+                    let _ = writeln!(xref, "{offset:010} 00000 n ");
+                }
+                None => xref.push_str("0000000000 65535 f \n"),
+            }
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {size} /Root {root} 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n"
+        );
+        self.buf.extend_from_slice(xref.as_bytes());
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::{fmt_num, to_searchable_pdf};
+    use crate::{
+        Quad,
+        config::RecImage,
+        pipeline::{
+            rapid_ocr::filter_by_text_score_for_full,
+            types::{FullResult, OcrResult},
+        },
+        types::{LineResult, TextOrientation, WordBox},
+    };
+
+    fn line(text: &str) -> LineResult {
+        LineResult {
+            text: text.to_string(),
+            score: 0.9,
+            word_info: None,
+            orientation: TextOrientation::Horizontal,
+            chars: None,
+            lang: None,
+        }
+    }
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Quad {
+        [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).position(|w| w == needle)
+    }
+
+    fn content_stream(pdf: &[u8]) -> String {
+        stream_text(pdf, b"4 0 obj")
+    }
+
+    fn stream_text(pdf: &[u8], object: &[u8]) -> String {
+        let obj = find(pdf, object).expect("stream object");
+        let start = obj + find(&pdf[obj..], b"stream\n").expect("stream start") + 7;
+        let end = obj + find(&pdf[obj..], b"\nendstream").expect("stream end");
+        let mut out = String::new();
+        ZlibDecoder::new(&pdf[start..end])
+            .read_to_string(&mut out)
+            .expect("content should inflate");
+        out
+    }
+
+    #[test]
+    fn searchable_pdf_places_invisible_text_over_the_image() {
+        let image = RecImage::from_bgr_u8(300, 150, vec![255; 300 * 150 * 3]).expect("image");
+        let result = OcrResult::Full(FullResult {
+            boxes: vec![[[30.0, 30.0], [270.0, 30.0], [270.0, 60.0], [30.0, 60.0]]],
+            det_scores: vec![0.9],
+            lines: vec![LineResult {
+                text: "Hi 中".to_string(),
+                score: 0.9,
+                word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
+            }],
+            txts: vec!["Hi 中".to_string()],
+            scores: vec![0.9],
+            ..FullResult::default()
+        });
+
+        let pdf = to_searchable_pdf(&image, &result).expect("pdf should be written");
+        assert!(pdf.starts_with(b"%PDF-1.5"));
+        assert!(find(&pdf, b"/MediaBox [0 0 72 36]").is_some());
+
+        let content = content_stream(&pdf);
+        assert!(content.contains("3 Tr"));
+        assert!(content.contains("<0048006900204E2D> Tj"));
+        assert!(content.contains("/F0 7.2 Tf"));
+        assert!(content.contains("1 0 0 1 7.2 21.6 Tm"));
+
+        let startxref = find(&pdf, b"startxref\n").expect("startxref") + 10;
+        let xref_offset: usize = std::str::from_utf8(&pdf[startxref..])
+            .expect("ascii trailer")
+            .lines()
+            .next()
+            .and_then(|v| v.parse().ok())
+            .expect("xref offset");
+        assert!(pdf[xref_offset..].starts_with(b"xref\n0 10\n"));
+    }
+
+    #[test]
+    fn lines_without_word_boxes_keep_their_line_text() {
+        let image = RecImage::from_bgr_u8(300, 150, vec![255; 300 * 150 * 3]).expect("image");
+        let word = |text: &str, bbox| WordBox {
+            text: text.to_string(),
+            score: 0.9,
+            bbox,
+        };
+        let boxes = vec![
+            rect(30.0, 10.0, 270.0, 40.0),
+            rect(30.0, 60.0, 270.0, 90.0),
+            rect(30.0, 110.0, 270.0, 140.0),
+        ];
+        // The middle line is kept by the score filter but has no word boxes.
+        let (boxes, det_scores, lines, word_boxes) = filter_by_text_score_for_full(
+            boxes,
+            vec![0.9; 3],
+            vec![line("ab cd"), line("ef"), line("gh")],
+            Some(vec![
+                vec![
+                    word("ab", rect(30.0, 10.0, 140.0, 40.0)),
+                    word("cd", rect(160.0, 10.0, 270.0, 40.0)),
+                ],
+                Vec::new(),
+                vec![word("gh", rect(30.0, 110.0, 270.0, 140.0))],
+            ]),
+            0.5,
+        );
+        let result = OcrResult::Full(FullResult {
+            boxes,
+            det_scores,
+            txts: lines.iter().map(|l| l.text.clone()).collect(),
+            scores: lines.iter().map(|l| l.score).collect(),
+            lines,
+            word_boxes,
+            ..FullResult::default()
+        });
+
+        let content = content_stream(&to_searchable_pdf(&image, &result).expect("pdf"));
+        assert!(content.contains("<00610062> Tj"));
+        assert!(content.contains("<00630064> Tj"));
+        assert!(!content.contains("<0061006200200063"));
+        assert_eq!(content.matches("<00650066> Tj").count(), 1);
+        assert_eq!(content.matches("<00670068> Tj").count(), 1);
+    }
+
+    #[test]
+    fn characters_beyond_the_bmp_get_their_own_codes() {
+        let image = RecImage::from_bgr_u8(300, 150, vec![255; 300 * 150 * 3]).expect("image");
+        let result = OcrResult::Full(FullResult {
+            boxes: vec![rect(30.0, 30.0, 270.0, 60.0)],
+            det_scores: vec![0.9],
+            lines: vec![line("a\u{1F600}\u{20BB7}\u{1F600}")],
+            txts: vec!["a\u{1F600}\u{20BB7}\u{1F600}".to_string()],
+            scores: vec![0.9],
+            ..FullResult::default()
+        });
+
+        let pdf = to_searchable_pdf(&image, &result).expect("pdf");
+        assert!(content_stream(&pdf).contains("<0061D800D801D800> Tj"));
+        let cmap = stream_text(&pdf, b"9 0 obj");
+        assert!(cmap.contains("2 beginbfchar\n<D800> <D83DDE00>\n<D801> <D842DFB7>\nendbfchar"));
+    }
+
+    #[test]
+    fn number_formatting_is_compact() {
+        assert_eq!(fmt_num(72.0), "72");
+        assert_eq!(fmt_num(7.25), "7.25");
+        assert_eq!(fmt_num(-0.0001), "0");
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    Quad,
+    error::{RapidOcrError, Result},
+    layout::{analyze_layout, render_page},
+};
+
+pub fn to_plain_text(boxes: &[Quad], txts: &[String]) -> Result<String> {
+    if boxes.len() != txts.len() {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "text output length mismatch: boxes={}, txts={}",
+            boxes.len(),
+            txts.len()
+        )));
+    }
+    let page = analyze_layout(boxes, txts)?;
+    Ok(render_page(&page, " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_plain_text;
+
+    #[test]
+    fn plain_text_follows_reading_order() {
+        let rect = |x0: f32, y0: f32, x1: f32, y1: f32| [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+        let boxes = vec![
+            rect(120.0, 10.0, 200.0, 30.0),
+            rect(10.0, 12.0, 100.0, 32.0),
+        ];
+        let txts = ["world", "hello"].map(String::from).to_vec();
+        assert_eq!(
+            to_plain_text(&boxes, &txts).expect("valid input"),
+            "hello world"
+        );
+        assert_eq!(to_plain_text(&[], &[]).expect("empty input"), "");
+    }
+}
@@ -3,7 +3,7 @@ use std::{fs, path::Path};
 use serde_yaml::Value;
 
 use crate::{
-    config::ProviderPreference,
+    config::{ModelSource, ProviderPreference},
     error::{RapidOcrError, Result},
     pipeline::config::EngineConfig,
 };
@@ -72,6 +72,30 @@ fn apply_global_section(root: &Value, cfg: &mut EngineConfig) {
     if let Some(v) = mapping_get(global, "return_single_char_box").and_then(value_to_bool) {
         cfg.global.return_single_char_box = v;
     }
+    if let Some(v) = mapping_get(global, "use_tiled_det").and_then(value_to_bool) {
+        cfg.global.use_tiled_det = v;
+    }
+    if let Some(v) = mapping_get(global, "tile_size").and_then(value_to_usize) {
+        cfg.global.tile_size = v;
+    }
+    if let Some(v) = mapping_get(global, "tile_overlap").and_then(value_to_usize) {
+        cfg.global.tile_overlap = v;
+    }
+    if let Some(v) = mapping_get(global, "use_vertical_text").and_then(value_to_bool) {
+        cfg.global.use_vertical_text = v;
+    }
+    if let Some(v) = mapping_get(global, "vertical_text_ratio").and_then(value_to_f32) {
+        cfg.global.vertical_text_ratio = v;
+    }
+    if let Some(v) = mapping_get(global, "use_doc_orientation").and_then(value_to_bool) {
+        cfg.global.use_doc_orientation = v;
+    }
+    if let Some(v) = mapping_get(global, "use_deskew").and_then(value_to_bool) {
+        cfg.global.use_deskew = v;
+    }
+    if let Some(v) = mapping_get(global, "deskew_max_angle").and_then(value_to_f32) {
+        cfg.global.deskew_max_angle = v;
+    }
 }
 
 fn apply_det_section(root: &Value, cfg: &mut EngineConfig) -> Result<()> {
@@ -100,7 +124,7 @@ fn apply_det_section(root: &Value, cfg: &mut EngineConfig) -> Result<()> {
         cfg.det.model_type = v;
     }
     if let Some(v) = mapping_get(det, "model_path").and_then(value_to_pathbuf_string) {
-        cfg.det.model_path = Some(v.into());
+        cfg.det.model_source = ModelSource::Path(v.into());
     }
     if let Some(v) = mapping_get(det, "limit_side_len").and_then(value_to_usize) {
         cfg.det.limit_side_len = v;
@@ -162,7 +186,7 @@ fn apply_cls_section(root: &Value, cfg: &mut EngineConfig) -> Result<()> {
         cfg.cls.model_type = v;
     }
     if let Some(v) = mapping_get(cls, "model_path").and_then(value_to_pathbuf_string) {
-        cfg.cls.model_path = Some(v.into());
+        cfg.cls.model_source = ModelSource::Path(v.into());
     }
     if let Some(v) = mapping_get(cls, "cls_image_shape").and_then(value_to_usizex3) {
         cfg.cls.cls_image_shape = v;
@@ -206,7 +230,7 @@ fn apply_rec_section(root: &Value, cfg: &mut EngineConfig) -> Result<()> {
         cfg.rec.model.model_type = v;
     }
     if let Some(v) = mapping_get(rec, "model_path").and_then(value_to_pathbuf_string) {
-        cfg.rec.model.model_path = Some(v.into());
+        cfg.rec.model.model_source = ModelSource::Path(v.into());
     }
     if let Some(v) = mapping_get(rec, "rec_keys_path").and_then(value_to_pathbuf_string) {
         cfg.rec.model.rec_keys_path = Some(v.into());
@@ -343,6 +367,57 @@ EngineConfig:
         assert!(cfg.rec.runtime.enable_cpu_mem_arena);
     }
 
+    #[test]
+    fn parse_rapidocr_yaml_tiled_detection() {
+        let yaml = r#"
+Global:
+  use_tiled_det: true
+  tile_size: 1024
+  tile_overlap: 128
+"#;
+        let cfg = from_rapidocr_yaml_str(yaml).expect("compat parse should pass");
+        assert!(cfg.global.use_tiled_det);
+        assert_eq!(cfg.global.tile_size, 1024);
+        assert_eq!(cfg.global.tile_overlap, 128);
+
+        let err = from_rapidocr_yaml_str("Global:\n  tile_size: 128\n  tile_overlap: 128\n")
+            .expect_err("overlap must be smaller than the tile");
+        assert!(err.to_string().contains("global.tile_overlap"));
+    }
+
+    #[test]
+    fn parse_rapidocr_yaml_vertical_text() {
+        let cfg = from_rapidocr_yaml_str(
+            "Global:\n  use_vertical_text: false\n  vertical_text_ratio: 2.0\n",
+        )
+        .expect("compat parse should pass");
+        assert!(!cfg.global.use_vertical_text);
+        assert!((cfg.global.vertical_text_ratio - 2.0).abs() < f32::EPSILON);
+
+        let err = from_rapidocr_yaml_str("Global:\n  vertical_text_ratio: 0.5\n")
+            .expect_err("ratio below one must be rejected");
+        assert!(err.to_string().contains("global.vertical_text_ratio"));
+    }
+
+    #[test]
+    fn parse_rapidocr_yaml_doc_orientation_switch() {
+        let cfg = from_rapidocr_yaml_str("Global:\n  use_doc_orientation: true\n")
+            .expect("compat parse should pass");
+        assert!(cfg.global.use_doc_orientation);
+    }
+
+    #[test]
+    fn parse_rapidocr_yaml_deskew() {
+        let cfg = from_rapidocr_yaml_str("Global:\n  use_deskew: true\n  deskew_max_angle: 5\n")
+            .expect("compat parse should pass");
+        assert!(cfg.global.use_deskew);
+        assert!((cfg.global.deskew_max_angle - 5.0).abs() < f32::EPSILON);
+
+        let err = from_rapidocr_yaml_str("Global:\n  deskew_max_angle: 90\n")
+            .expect_err("angle above 45 must be rejected");
+        assert!(err.to_string().contains("global.deskew_max_angle"));
+    }
+
     #[test]
     fn parse_rapidocr_yaml_rejects_invalid_runtime_values() {
         let yaml = r#"
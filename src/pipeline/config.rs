@@ -1,14 +1,20 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 
 use crate::{
     cls::classifier::ClassifierConfig,
+    cls::doc_orientation::{DocOrientationConfig, parse_angle},
     config::RecognizerConfig,
     config::RuntimeConfig,
     det::detector::DetectorConfig,
     error::{RapidOcrError, Result},
+    model_store::DownloadConfig,
+    rec::router::MultiRecConfig,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +30,14 @@ pub struct GlobalConfig {
     pub min_side_len: usize,
     pub return_word_box: bool,
     pub return_single_char_box: bool,
+    pub use_tiled_det: bool,
+    pub tile_size: usize,
+    pub tile_overlap: usize,
+    pub use_vertical_text: bool,
+    pub vertical_text_ratio: f32,
+    pub use_doc_orientation: bool,
+    pub use_deskew: bool,
+    pub deskew_max_angle: f32,
 }
 
 impl Default for GlobalConfig {
@@ -39,6 +53,14 @@ impl Default for GlobalConfig {
             min_side_len: 30,
             return_word_box: false,
             return_single_char_box: false,
+            use_tiled_det: false,
+            tile_size: 1600,
+            tile_overlap: 200,
+            use_vertical_text: true,
+            vertical_text_ratio: 1.5,
+            use_doc_orientation: false,
+            use_deskew: false,
+            deskew_max_angle: 10.0,
         }
     }
 }
@@ -50,6 +72,12 @@ pub struct EngineConfig {
     pub det: DetectorConfig,
     pub cls: ClassifierConfig,
     pub rec: RecognizerConfig,
+    pub doc_ori: DocOrientationConfig,
+    pub multi_rec: MultiRecConfig,
+    // Extra model registry consulted before the compiled-in one; see
+    // `ModelRegistry::from_yaml_file`.
+    pub model_manifest: Option<PathBuf>,
+    pub download: DownloadConfig,
 }
 
 impl EngineConfig {
@@ -95,6 +123,29 @@ impl EngineConfig {
                 "global.width_height_ratio must be > 0".to_string(),
             ));
         }
+        if self.global.tile_size == 0 {
+            return Err(RapidOcrError::Config(
+                "global.tile_size must be greater than zero".to_string(),
+            ));
+        }
+        if self.global.tile_overlap >= self.global.tile_size {
+            return Err(RapidOcrError::Config(format!(
+                "global.tile_overlap ({}) must be < global.tile_size ({})",
+                self.global.tile_overlap, self.global.tile_size
+            )));
+        }
+        if self.global.vertical_text_ratio < 1.0 {
+            return Err(RapidOcrError::Config(format!(
+                "global.vertical_text_ratio must be >= 1, got {}",
+                self.global.vertical_text_ratio
+            )));
+        }
+        if !(self.global.deskew_max_angle > 0.0 && self.global.deskew_max_angle <= 45.0) {
+            return Err(RapidOcrError::Config(format!(
+                "global.deskew_max_angle must be in range (0, 45], got {}",
+                self.global.deskew_max_angle
+            )));
+        }
 
         if self.det.limit_side_len == 0 {
             return Err(RapidOcrError::Config(
@@ -144,10 +195,75 @@ impl EngineConfig {
                 self.rec.rec_img_shape
             )));
         }
+        validate_inclusive_range(
+            "rec.correction.min_char_prob",
+            self.rec.correction.min_char_prob,
+            0.0,
+            1.0,
+        )?;
+        if self.rec.correction.top_k == 0 {
+            return Err(RapidOcrError::Config(
+                "rec.correction.top_k must be greater than zero".to_string(),
+            ));
+        }
+
+        validate_inclusive_range(
+            "multi_rec.min_script_ratio",
+            self.multi_rec.min_script_ratio,
+            0.0,
+            1.0,
+        )?;
+        validate_inclusive_range(
+            "multi_rec.reroute_below_score",
+            self.multi_rec.reroute_below_score,
+            0.0,
+            1.0,
+        )?;
+
+        if self.download.timeout_secs == 0 {
+            return Err(RapidOcrError::Config(
+                "download.timeout_secs must be greater than zero".to_string(),
+            ));
+        }
+        if let Some(mirror) = self
+            .download
+            .mirrors
+            .iter()
+            .find(|m| !m.starts_with("http://") && !m.starts_with("https://"))
+        {
+            return Err(RapidOcrError::Config(format!(
+                "download.mirrors entries must be http(s) URLs, got `{mirror}`"
+            )));
+        }
+
+        if self.rec.model.rec_keys_path.is_some() && self.rec.model.rec_keys_text.is_some() {
+            return Err(RapidOcrError::Config(
+                "rec.model sets both rec_keys_path and rec_keys_text; keep one".to_string(),
+            ));
+        }
+
+        validate_inclusive_range("doc_ori.thresh", self.doc_ori.thresh, 0.0, 1.0)?;
+        if self.doc_ori.image_size == 0 || self.doc_ori.resize_short < self.doc_ori.image_size {
+            return Err(RapidOcrError::Config(format!(
+                "doc_ori.image_size must be > 0 and <= doc_ori.resize_short, got {} and {}",
+                self.doc_ori.image_size, self.doc_ori.resize_short
+            )));
+        }
+        if let Some(label) = self
+            .doc_ori
+            .label_list
+            .iter()
+            .find(|label| parse_angle(label).is_none())
+        {
+            return Err(RapidOcrError::Config(format!(
+                "doc_ori.label_list entries must be one of 0/90/180/270, got `{label}`"
+            )));
+        }
 
         validate_runtime_config("det.runtime", &self.det.runtime)?;
         validate_runtime_config("cls.runtime", &self.cls.runtime)?;
         validate_runtime_config("rec.runtime", &self.rec.runtime)?;
+        validate_runtime_config("doc_ori.runtime", &self.doc_ori.runtime)?;
 
         Ok(())
     }
@@ -188,6 +304,11 @@ fn validate_runtime_config(prefix: &str, runtime: &RuntimeConfig) -> Result<()>
             "{prefix}.rayon_threads must be greater than zero when set"
         )));
     }
+    if runtime.max_sessions.is_some_and(|v| v == 0) {
+        return Err(RapidOcrError::Config(format!(
+            "{prefix}.max_sessions must be greater than zero when set"
+        )));
+    }
     Ok(())
 }
 
@@ -226,4 +347,29 @@ mod tests {
         let err = EngineConfig::from_yaml_str(&yaml).expect_err("must reject unknown top-level");
         assert!(err.to_string().contains("unknown field `unexpected`"));
     }
+
+    #[test]
+    fn doc_ori_section_is_optional_and_validated() {
+        let mut yaml = serde_yaml::to_string(&EngineConfig::default())
+            .expect("default config should serialize");
+        let start = yaml.find("doc_ori:").expect("doc_ori section serialized");
+        yaml.truncate(start);
+        let cfg = EngineConfig::from_yaml_str(&yaml).expect("doc_ori may be omitted");
+        assert_eq!(cfg.doc_ori.label_list.len(), 4);
+
+        let mut cfg = EngineConfig::default();
+        cfg.doc_ori.label_list.push("45".to_string());
+        let err = cfg.validate().expect_err("non quarter-turn label");
+        assert!(err.to_string().contains("doc_ori.label_list"));
+    }
+
+    #[test]
+    fn rec_keys_path_and_text_are_exclusive() {
+        let mut cfg = EngineConfig::default();
+        cfg.rec.model.rec_keys_text = Some("a\nb\n".to_string());
+        cfg.validate().expect("text alone is valid");
+        cfg.rec.model.rec_keys_path = Some("keys.txt".into());
+        let err = cfg.validate().expect_err("both dictionaries set");
+        assert!(err.to_string().contains("rec_keys_text"));
+    }
 }
@@ -2,12 +2,17 @@ use crate::{
     Quad,
     config::{RecImage, VisionBackend},
     error::{RapidOcrError, Result},
+    types::TextOrientation,
     vision::{
         image_backend::resize_image as resize_image_with_backend, rotate_crop::rotate_crop_image,
     },
 };
 use rayon::prelude::*;
 
+// Long side the skew estimate works on; the angle does not need full resolution.
+const DESKEW_ANALYSIS_SIDE: usize = 1000;
+const DESKEW_MIN_ANGLE: f32 = 0.1;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct PreprocessRecord {
     pub ratio_h: f32,
@@ -72,12 +77,15 @@ pub fn apply_vertical_padding(
     Ok((padded, padding_h))
 }
 
+// `vertical_ratio` is the height/width ratio from which a crop is treated as a
+// vertical line and rotated; `None` keeps every crop as cut.
 pub fn crop_text_regions(
     img: &RecImage,
     det_boxes: &[Quad],
+    vertical_ratio: Option<f32>,
     backend: VisionBackend,
-) -> Result<Vec<RecImage>> {
-    let crops: Vec<Result<RecImage>> = det_boxes
+) -> Result<(Vec<RecImage>, Vec<TextOrientation>)> {
+    let crops: Vec<Result<(RecImage, TextOrientation)>> = det_boxes
         .par_iter()
         .map(|box_| {
             let mut pts = *box_;
@@ -85,19 +93,25 @@ pub fn crop_text_regions(
                 p[0] = p[0].clamp(0.0, img.width().saturating_sub(1) as f32);
                 p[1] = p[1].clamp(0.0, img.height().saturating_sub(1) as f32);
             }
-            let mut crop = rotate_crop_image(img, pts, backend)?;
-            if crop.height() as f32 / crop.width().max(1) as f32 >= 1.5 {
-                crop = rotate_90(crop)?;
+            let crop = rotate_crop_image(img, pts, backend)?;
+            let is_vertical = vertical_ratio
+                .is_some_and(|ratio| crop.height() as f32 / crop.width().max(1) as f32 >= ratio);
+            if is_vertical {
+                Ok((rotate_90(crop)?, TextOrientation::Vertical))
+            } else {
+                Ok((crop, TextOrientation::Horizontal))
             }
-            Ok(crop)
         })
         .collect();
 
     let mut out = Vec::with_capacity(crops.len());
+    let mut orientations = Vec::with_capacity(crops.len());
     for crop in crops {
-        out.push(crop?);
+        let (crop, orientation) = crop?;
+        out.push(crop);
+        orientations.push(orientation);
     }
-    Ok(out)
+    Ok((out, orientations))
 }
 
 pub fn map_boxes_to_original(
@@ -118,6 +132,201 @@ pub fn map_boxes_to_original(
     }
 }
 
+// Undo a page rotation: `angle` is the counter-clockwise turn that was applied
+// to the original page, and `rot_w`/`rot_h` are the rotated page size.
+pub fn map_boxes_from_rotated(boxes: &mut [Quad], angle: u32, rot_w: usize, rot_h: usize) {
+    let (rot_w, rot_h) = (rot_w as f32, rot_h as f32);
+    for box_ in boxes {
+        for p in box_ {
+            let [u, v] = *p;
+            *p = match angle {
+                90 => [rot_h - v, u],
+                180 => [rot_w - u, rot_h - v],
+                270 => [v, rot_w - u],
+                _ => [u, v],
+            };
+        }
+    }
+}
+
+// Rotates the page counter-clockwise by a quarter-turn multiple in one pass.
+pub fn rotate_page(img: RecImage, angle: u32) -> Result<RecImage> {
+    let (w, h) = (img.width(), img.height());
+    let (new_w, new_h) = match angle {
+        0 => return Ok(img),
+        90 | 270 => (h, w),
+        180 => (w, h),
+        _ => {
+            return Err(RapidOcrError::InvalidInput(format!(
+                "page rotation must be a multiple of 90 degrees, got {angle}"
+            )));
+        }
+    };
+    let src = img.as_bgr_cow();
+    let src = src.as_ref();
+    let mut out = vec![0_u8; src.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let (dst_x, dst_y) = match angle {
+                90 => (y, w - 1 - x),
+                180 => (w - 1 - x, h - 1 - y),
+                _ => (h - 1 - y, x),
+            };
+            let src_idx = (y * w + x) * 3;
+            let dst_idx = (dst_y * new_w + dst_x) * 3;
+            out[dst_idx..dst_idx + 3].copy_from_slice(&src[src_idx..src_idx + 3]);
+        }
+    }
+    RecImage::from_bgr_u8(new_w, new_h, out)
+}
+
+// `angle` is the skew of the text lines in degrees (clockwise positive, y down);
+// the deskewed page was rotated by `-angle` onto a canvas of `dst_w`x`dst_h`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeskewRecord {
+    pub angle: f32,
+    pub src_w: usize,
+    pub src_h: usize,
+    pub dst_w: usize,
+    pub dst_h: usize,
+}
+
+impl DeskewRecord {
+    fn source_point(self, p: [f32; 2]) -> [f32; 2] {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let x = p[0] - self.dst_w as f32 / 2.0;
+        let y = p[1] - self.dst_h as f32 / 2.0;
+        [
+            x * cos - y * sin + self.src_w as f32 / 2.0,
+            x * sin + y * cos + self.src_h as f32 / 2.0,
+        ]
+    }
+}
+
+// Projection profile search: the angle whose row histogram of dark pixels is
+// the most peaked is the one the text lines run along.
+pub fn estimate_skew_angle(img: &RecImage, max_angle: f32, backend: VisionBackend) -> Result<f32> {
+    let long_side = img.width().max(img.height());
+    let small;
+    let img = if long_side > DESKEW_ANALYSIS_SIDE {
+        let scale = DESKEW_ANALYSIS_SIDE as f32 / long_side as f32;
+        let w = ((img.width() as f32 * scale).round() as usize).max(1);
+        let h = ((img.height() as f32 * scale).round() as usize).max(1);
+        small = resize_image(img, w, h, backend)?;
+        &small
+    } else {
+        img
+    };
+
+    let bgr = img.as_bgr_cow();
+    let gray: Vec<u8> = bgr
+        .chunks_exact(3)
+        .map(|px| ((px[0] as u32 * 29 + px[1] as u32 * 150 + px[2] as u32 * 77) >> 8) as u8)
+        .collect();
+    if gray.is_empty() {
+        return Ok(0.0);
+    }
+    let mean = gray.iter().map(|v| *v as u64).sum::<u64>() / gray.len() as u64;
+    let threshold = (mean as f32 * 0.75) as u8;
+    let (cx, cy) = (img.width() as f32 / 2.0, img.height() as f32 / 2.0);
+    let points: Vec<[f32; 2]> = gray
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v < threshold)
+        .map(|(i, _)| {
+            let x = (i % img.width()) as f32 + 0.5 - cx;
+            let y = (i / img.width()) as f32 + 0.5 - cy;
+            [x, y]
+        })
+        .collect();
+    if points.len() < gray.len() / 1000 + 1 {
+        return Ok(0.0);
+    }
+
+    let diag = (cx * cx + cy * cy).sqrt();
+    let score = |angle: f32| {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let last = (diag * 2.0) as usize + 1;
+        let mut rows = vec![0_u32; last + 1];
+        for p in &points {
+            let r = (-p[0] * sin + p[1] * cos + diag) as usize;
+            rows[r.min(last)] += 1;
+        }
+        rows.iter().map(|c| (*c as f64) * (*c as f64)).sum::<f64>()
+    };
+    let search = |center: f32, half_range: f32, step: f32| {
+        let steps = (half_range / step).round() as i32;
+        (-steps..=steps)
+            .map(|i| center + i as f32 * step)
+            .map(|angle| (angle, score(angle)))
+            .fold(
+                (center, f64::MIN),
+                |best, cur| {
+                    if cur.1 > best.1 { cur } else { best }
+                },
+            )
+            .0
+    };
+    let coarse = search(0.0, max_angle, 0.5);
+    let fine = search(coarse, 0.5, 0.1).clamp(-max_angle, max_angle);
+    Ok(fine)
+}
+
+// Rotates the page by `-angle` around its center onto a canvas that keeps every
+// source pixel; uncovered corners are filled white.
+pub fn deskew_page(img: RecImage, angle: f32) -> Result<(RecImage, Option<DeskewRecord>)> {
+    if angle.abs() < DESKEW_MIN_ANGLE {
+        return Ok((img, None));
+    }
+    let (src_w, src_h) = (img.width(), img.height());
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (sin_a, cos_a) = (sin.abs(), cos.abs());
+    let dst_w = (src_w as f32 * cos_a + src_h as f32 * sin_a).ceil() as usize;
+    let dst_h = (src_w as f32 * sin_a + src_h as f32 * cos_a).ceil() as usize;
+    let record = DeskewRecord {
+        angle,
+        src_w,
+        src_h,
+        dst_w,
+        dst_h,
+    };
+
+    let src = img.as_bgr_cow();
+    let src = src.as_ref();
+    let mut out = vec![255_u8; dst_w * dst_h * 3];
+    out.par_chunks_mut(dst_w * 3)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..dst_w {
+                let [sx, sy] = record.source_point([x as f32 + 0.5, y as f32 + 0.5]);
+                let (sx, sy) = (sx - 0.5, sy - 0.5);
+                if sx < 0.0 || sy < 0.0 || sx > (src_w - 1) as f32 || sy > (src_h - 1) as f32 {
+                    continue;
+                }
+                let (x0, y0) = (sx as usize, sy as usize);
+                let (x1, y1) = ((x0 + 1).min(src_w - 1), (y0 + 1).min(src_h - 1));
+                let (fx, fy) = (sx - x0 as f32, sy - y0 as f32);
+                for c in 0..3 {
+                    let at = |xx: usize, yy: usize| src[(yy * src_w + xx) * 3 + c] as f32;
+                    let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+                    let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+                    row[x * 3 + c] = (top * (1.0 - fy) + bottom * fy).round() as u8;
+                }
+            }
+        });
+    Ok((RecImage::from_bgr_u8(dst_w, dst_h, out)?, Some(record)))
+}
+
+pub fn map_boxes_from_deskewed(boxes: &mut [Quad], record: DeskewRecord) {
+    for box_ in boxes {
+        for p in box_ {
+            let [x, y] = record.source_point(*p);
+            p[0] = x.clamp(0.0, record.src_w as f32);
+            p[1] = y.clamp(0.0, record.src_h as f32);
+        }
+    }
+}
+
 pub fn map_img_to_original(
     imgs: &[RecImage],
     ratio_h: f32,
@@ -215,3 +424,100 @@ fn rotate_90(img: RecImage) -> Result<RecImage> {
     }
     RecImage::from_bgr_u8(new_w, new_h, out)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{RecImage, VisionBackend},
+        pipeline::image_ops::{
+            deskew_page, estimate_skew_angle, map_boxes_from_deskewed, map_boxes_from_rotated,
+            rotate_page,
+        },
+    };
+
+    // White page with dark horizontal bars, then skewed by `angle` degrees.
+    fn skewed_lines(angle: f32) -> RecImage {
+        let (w, h) = (240, 160);
+        let mut data = vec![255_u8; w * h * 3];
+        for y in (20..140).step_by(24) {
+            for yy in y..y + 6 {
+                data[(yy * w + 30) * 3..(yy * w + 210) * 3].fill(0);
+            }
+        }
+        let page = RecImage::from_bgr_u8(w, h, data).expect("image");
+        deskew_page(page, -angle).expect("skew").0
+    }
+
+    #[test]
+    fn rotated_page_boxes_map_back_to_original_pixels() {
+        // 3x2 page with a marker at (2, 0); each rotation must move the marker
+        // to a point that maps back onto it.
+        let mut data = vec![0_u8; 3 * 2 * 3];
+        data[2 * 3] = 255;
+        for angle in [90, 180, 270] {
+            let rotated = rotate_page(
+                RecImage::from_bgr_u8(3, 2, data.clone()).expect("image"),
+                angle,
+            )
+            .expect("rotate");
+            let pos = rotated
+                .as_bytes()
+                .chunks(3)
+                .position(|px| px[0] == 255)
+                .expect("marker kept");
+            let (x, y) = (pos % rotated.width(), pos / rotated.width());
+            // Pixel centers map exactly under the continuous transform.
+            let center = [x as f32 + 0.5, y as f32 + 0.5];
+            let mut boxes = vec![[center; 4]];
+            map_boxes_from_rotated(&mut boxes, angle, rotated.width(), rotated.height());
+            assert_eq!(boxes[0][0], [2.5, 0.5], "angle {angle}");
+        }
+    }
+
+    #[test]
+    fn projection_profile_recovers_skew_angle() {
+        for angle in [-4.0_f32, 2.5] {
+            let img = skewed_lines(angle);
+            let found = estimate_skew_angle(&img, 10.0, VisionBackend::PureRust).expect("angle");
+            assert!(
+                (found - angle).abs() <= 0.2,
+                "expected {angle}, got {found}"
+            );
+        }
+        let flat = skewed_lines(0.0);
+        let found = estimate_skew_angle(&flat, 10.0, VisionBackend::PureRust).expect("angle");
+        assert!(found.abs() <= 0.1);
+    }
+
+    #[test]
+    fn deskewed_boxes_map_back_to_source_pixels() {
+        let page = RecImage::from_bgr_u8(40, 20, vec![255; 40 * 20 * 3]).expect("image");
+        let (deskewed, record) = deskew_page(page, 8.0).expect("deskew");
+        let record = record.expect("angle above the minimum");
+        assert_eq!(
+            (record.dst_w, record.dst_h),
+            (deskewed.width(), deskewed.height())
+        );
+
+        // The deskewed center and corners of the source must round-trip.
+        let (sin, cos) = (-8.0_f32).to_radians().sin_cos();
+        let forward = |x: f32, y: f32| {
+            let (dx, dy) = (x - 20.0, y - 10.0);
+            [
+                dx * cos - dy * sin + record.dst_w as f32 / 2.0,
+                dx * sin + dy * cos + record.dst_h as f32 / 2.0,
+            ]
+        };
+        let mut boxes = vec![[
+            forward(20.0, 10.0),
+            forward(2.0, 3.0),
+            forward(38.0, 3.0),
+            forward(2.0, 17.0),
+        ]];
+        map_boxes_from_deskewed(&mut boxes, record);
+        let expected = [[20.0, 10.0], [2.0, 3.0], [38.0, 3.0], [2.0, 17.0]];
+        for (got, want) in boxes[0].iter().zip(expected) {
+            assert!((got[0] - want[0]).abs() < 1e-3 && (got[1] - want[1]).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::error::Result;
+
+// A pipeline stage built on first use. Concurrent first calls through
+// `SharedRapidOcr` serialize on `init`, so a model is only loaded once.
+#[derive(Debug)]
+pub(crate) struct LazyStage<T> {
+    cell: OnceLock<T>,
+    init: Mutex<()>,
+}
+
+impl<T> LazyStage<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            cell: OnceLock::new(),
+            init: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn loaded(value: T) -> Self {
+        Self {
+            cell: OnceLock::from(value),
+            init: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    pub(crate) fn get_mut(&mut self) -> Option<&mut T> {
+        self.cell.get_mut()
+    }
+
+    pub(crate) fn get_or_try_init(&self, init: impl FnOnce() -> Result<T>) -> Result<&T> {
+        if let Some(value) = self.cell.get() {
+            return Ok(value);
+        }
+        let _guard = self.init.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = self.cell.get() {
+            return Ok(value);
+        }
+        let value = init()?;
+        Ok(self.cell.get_or_init(|| value))
+    }
+
+    pub(crate) fn replace(&mut self, value: T) {
+        self.cell = OnceLock::from(value);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.cell = OnceLock::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{error::RapidOcrError, pipeline::lazy_stage::LazyStage};
+
+    #[test]
+    fn failed_init_is_retried_and_success_is_cached() {
+        let calls = AtomicUsize::new(0);
+        let stage = LazyStage::<usize>::new();
+        let err = stage
+            .get_or_try_init(|| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(RapidOcrError::Config("model missing".to_string()))
+            })
+            .expect_err("first init fails");
+        assert!(err.to_string().contains("model missing"));
+        assert!(stage.get().is_none());
+
+        for _ in 0..2 {
+            let value = stage
+                .get_or_try_init(|| Ok(calls.fetch_add(1, Ordering::SeqCst)))
+                .expect("init succeeds");
+            assert_eq!(*value, 1);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,5 +1,8 @@
 pub mod compat_rapidocr;
 pub mod config;
 pub mod image_ops;
+pub mod lazy_stage;
+pub mod observer;
 pub mod rapid_ocr;
+pub mod tiling;
 pub mod types;
@@ -0,0 +1,106 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+    mpsc::Sender,
+};
+
+use crate::{
+    Quad,
+    error::{RapidOcrError, Result},
+    types::LineResult,
+};
+
+#[derive(Debug, Clone)]
+pub enum OcrEvent {
+    // Boxes are already mapped to original image coordinates.
+    Detection { boxes: Vec<Quad>, scores: Vec<f32> },
+    // `index` is the position of the line's box in the preceding `Detection` event.
+    // Lines are emitted before empty-text and `text_score` filtering.
+    Line { index: usize, line: LineResult },
+}
+
+pub trait OcrObserver {
+    fn on_event(&mut self, event: OcrEvent);
+}
+
+impl<F> OcrObserver for F
+where
+    F: FnMut(OcrEvent),
+{
+    fn on_event(&mut self, event: OcrEvent) {
+        self(event)
+    }
+}
+
+impl OcrObserver for Sender<OcrEvent> {
+    fn on_event(&mut self, event: OcrEvent) {
+        // A dropped receiver only means nobody is listening anymore.
+        let _ = self.send(event);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(RapidOcrError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use crate::{
+        error::RapidOcrError,
+        pipeline::observer::{CancellationToken, OcrEvent, OcrObserver},
+    };
+
+    #[test]
+    fn cancellation_is_shared_between_token_clones() {
+        let token = CancellationToken::new();
+        let handle = token.clone();
+        assert!(token.check().is_ok());
+
+        handle.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(RapidOcrError::Cancelled)));
+    }
+
+    #[test]
+    fn sender_observer_forwards_events_and_ignores_closed_channel() {
+        let (mut tx, rx) = mpsc::channel();
+        tx.on_event(OcrEvent::Detection {
+            boxes: Vec::new(),
+            scores: Vec::new(),
+        });
+        assert!(matches!(
+            rx.try_recv().expect("event should be forwarded"),
+            OcrEvent::Detection { .. }
+        ));
+
+        drop(rx);
+        tx.on_event(OcrEvent::Detection {
+            boxes: Vec::new(),
+            scores: Vec::new(),
+        });
+    }
+}
@@ -1,40 +1,67 @@
-use std::time::Instant;
-use std::{sync::Once, thread};
+use std::{
+    sync::{Arc, Mutex, Once},
+    thread,
+};
+
+use rayon::prelude::*;
 
 use crate::{
-    cls::classifier::{Classifier, ClassifierConfig},
-    config::RecognizeOptions,
-    det::detector::{Detector, DetectorConfig},
-    error::Result,
-    input::image_loader::{LoadImage, OcrInput},
+    clock::Instant,
+    cls::{
+        classifier::{Classifier, ClassifierConfig},
+        doc_orientation::DocOrientationClassifier,
+    },
+    config::{LangRec, RecognizeOptions, RecognizerConfig},
+    det::detector::{DetOutput, DetScratch, DetTimingBreakdown, Detector, DetectorConfig},
+    error::{RapidOcrError, Result},
+    input::image_loader::{LoadImage, OcrInput, PageImage, PageImages},
+    model_registry::ModelRegistry,
+    model_store::{DownloadProgress, ModelDownloader},
     pipeline::{
         config::EngineConfig,
         image_ops::{
-            PreprocessRecord, apply_vertical_padding, crop_text_regions, map_boxes_to_original,
-            map_img_to_original, resize_image_within_bounds,
+            DeskewRecord, PreprocessRecord, apply_vertical_padding, crop_text_regions, deskew_page,
+            estimate_skew_angle, map_boxes_from_deskewed, map_boxes_from_rotated,
+            map_boxes_to_original, map_img_to_original, resize_image_within_bounds, rotate_page,
         },
-        types::{OcrCallOptions, OcrOutput, OcrResult, RunOptions},
+        lazy_stage::LazyStage,
+        observer::{OcrEvent, OcrObserver},
+        tiling::{crop_tile, merge_tile_boxes, tile_grid},
+        types::{OcrCallOptions, OcrOutput, OcrPageOutput, OcrPageResult, OcrResult, RunOptions},
     },
-    rec::recognizer::Recognizer,
+    rec::{correction::LineCorrector, router::RecognizerRouter},
     runtime::provider::ProviderResolution,
-    types::{LineResult, WordBox},
+    types::{LineResult, TextOrientation, WordBox},
 };
 
+// A stage reports `None` until its model has been loaded.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PipelineProviderResolutions {
-    pub det: ProviderResolution,
-    pub cls: ProviderResolution,
-    pub rec: ProviderResolution,
+    pub det: Option<ProviderResolution>,
+    pub cls: Option<ProviderResolution>,
+    pub rec: Option<ProviderResolution>,
+    pub doc_ori: Option<ProviderResolution>,
+}
+
+// Replacement section for `RapidOcr::swap_model`.
+#[derive(Debug, Clone)]
+pub enum StageModel {
+    Det(DetectorConfig),
+    Cls(ClassifierConfig),
+    Rec(RecognizerConfig),
 }
 
 #[derive(Debug, Clone, Copy)]
 struct RunSwitches {
     use_det: bool,
+    // Only ever set together with `use_det`; rec-only inputs are single lines.
+    use_doc_orientation: bool,
     use_cls: bool,
     use_rec: bool,
     need_stage_images: bool,
     return_word_box: bool,
     return_single_char_box: bool,
+    char_top_k: Option<usize>,
     text_score: f32,
 }
 
@@ -46,6 +73,27 @@ struct PreparedImage {
     ratio_w: f32,
     preprocess_record: PreprocessRecord,
     proc_img: crate::config::RecImage,
+    tiled: bool,
+    // Counter-clockwise turn applied to the loaded page, then the deskew applied
+    // on top of it; `ori_h`/`ori_w` are the size after both.
+    page_angle: Option<u32>,
+    page_angle_score: Option<f32>,
+    deskew: Option<DeskewRecord>,
+}
+
+impl PreparedImage {
+    // Undo resize/padding, deskew and page rotation, in reverse order of prepare_image.
+    fn map_boxes_to_page(&self, boxes: &mut [crate::Quad]) {
+        map_boxes_to_original(boxes, self.preprocess_record, self.ori_h, self.ori_w);
+        let (mut rot_w, mut rot_h) = (self.ori_w, self.ori_h);
+        if let Some(record) = self.deskew {
+            map_boxes_from_deskewed(boxes, record);
+            (rot_w, rot_h) = (record.src_w, record.src_h);
+        }
+        if let Some(angle) = self.page_angle {
+            map_boxes_from_rotated(boxes, angle, rot_w, rot_h);
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -53,47 +101,177 @@ struct RunBuffers {
     det_boxes: Vec<crate::Quad>,
     det_scores: Vec<f32>,
     stage_images: Vec<crate::config::RecImage>,
+    orientations: Vec<TextOrientation>,
     lines: Vec<LineResult>,
 }
 
+#[derive(Debug, Default)]
+struct RunScratch {
+    det: DetScratch,
+    cls: Vec<f32>,
+    rec: Vec<f32>,
+}
+
+#[derive(Debug)]
+struct BatchSlot {
+    prepared: PreparedImage,
+    buffers: RunBuffers,
+    output: OcrOutput,
+}
+
+type BatchResults = Vec<Option<Result<OcrOutput>>>;
+// A page with the index of its input and its slot among the batched images;
+// `None` when the page is skipped.
+type BatchPage = (usize, PageImage, Option<usize>);
+
+// Most page images `run_batch_pages` holds at once.
+const PAGES_PER_BATCH: usize = 8;
+
 #[derive(Debug)]
 pub struct RapidOcr {
     config: EngineConfig,
-    detector: Detector,
-    classifier: Classifier,
-    recognizer: Recognizer,
+    registry: ModelRegistry,
+    detector: LazyStage<Detector>,
+    classifier: LazyStage<Classifier>,
+    recognizer: LazyStage<RecognizerRouter>,
+    line_corrector: Option<Arc<dyn LineCorrector>>,
+    doc_orientation: LazyStage<DocOrientationClassifier>,
     loader: LoadImage,
+    scratch: RunScratch,
 }
 
 impl RapidOcr {
     pub fn new(config: EngineConfig) -> Result<Self> {
+        Self::build(config, None)
+    }
+
+    // Model downloads, at construction or when a stage first loads, report to
+    // `progress`.
+    pub fn with_download_progress(
+        config: EngineConfig,
+        progress: Arc<dyn DownloadProgress>,
+    ) -> Result<Self> {
+        Self::build(config, Some(progress))
+    }
+
+    fn build(config: EngineConfig, progress: Option<Arc<dyn DownloadProgress>>) -> Result<Self> {
         init_rayon_global_pool(&config);
-        let det = Detector::new(detector_cfg_from_pipeline(&config))?;
-        let cls = Classifier::new(classifier_cfg_from_pipeline(&config))?;
-        let rec = Recognizer::new(config.rec.clone())?;
+        let mut downloader = ModelDownloader::new(config.download.clone());
+        if let Some(progress) = progress {
+            downloader = downloader.with_progress(progress);
+        }
+        let registry = ModelRegistry::with_manifest(config.model_manifest.as_deref())?
+            .with_downloader(downloader);
+        // Stages enabled in `global` load now so a bad model fails here; the
+        // others load the first time a call switches them on.
+        let detector = if config.global.use_det {
+            LazyStage::loaded(Detector::new(
+                detector_cfg_from_pipeline(&config),
+                &registry,
+            )?)
+        } else {
+            LazyStage::new()
+        };
+        let classifier = if config.global.use_cls {
+            LazyStage::loaded(Classifier::new(
+                classifier_cfg_from_pipeline(&config),
+                &registry,
+            )?)
+        } else {
+            LazyStage::new()
+        };
+        let recognizer = if config.global.use_rec {
+            LazyStage::loaded(build_recognizer(&config, &registry, None)?)
+        } else {
+            LazyStage::new()
+        };
+        let doc_orientation = if config.global.use_doc_orientation {
+            LazyStage::loaded(DocOrientationClassifier::new(
+                config.doc_ori.clone(),
+                &registry,
+            )?)
+        } else {
+            LazyStage::new()
+        };
         Ok(Self {
             config,
-            detector: det,
-            classifier: cls,
-            recognizer: rec,
+            registry,
+            detector,
+            classifier,
+            recognizer,
+            line_corrector: None,
+            doc_orientation,
             loader: LoadImage,
+            scratch: RunScratch::default(),
         })
     }
 
     pub fn run(&mut self, input: OcrInput, opts: OcrCallOptions) -> Result<OcrOutput> {
+        self.run_with_observer(input, opts, |_: OcrEvent| {})
+    }
+
+    pub fn run_with_observer(
+        &mut self,
+        input: OcrInput,
+        opts: OcrCallOptions,
+        mut observer: impl OcrObserver,
+    ) -> Result<OcrOutput> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let out = self.run_with_scratch(input, &opts, &mut scratch, &mut observer);
+        self.scratch = scratch;
+        out
+    }
+
+    // Boxes of each page are in that page's own pixel coordinates; for PDF pages,
+    // `points_per_pixel` converts them to PDF points.
+    pub fn run_pages(
+        &mut self,
+        input: OcrInput,
+        opts: OcrCallOptions,
+    ) -> Result<Vec<OcrPageOutput>> {
+        let pages = self.loader.load_pages(input)?;
+        run_each_page(pages, |image| {
+            self.run(OcrInput::Image(image), opts.clone())
+        })
+    }
+
+    fn run_with_scratch(
+        &self,
+        input: OcrInput,
+        opts: &OcrCallOptions,
+        scratch: &mut RunScratch,
+        observer: &mut dyn OcrObserver,
+    ) -> Result<OcrOutput> {
         let e2e_start = Instant::now();
         let mut output = OcrOutput::default();
-        let switches = self.resolve_run_switches(&opts);
-        let mut prepared = self.prepare_image(input, switches.use_det)?;
+        let switches = self.resolve_run_switches(opts);
+        let mut prepared = self.prepare_image(input, switches)?;
+        output.page_angle = prepared.page_angle;
+        output.page_angle_score = prepared.page_angle_score;
+        output.skew_angle = prepared.deskew.map(|record| record.angle);
         let mut buffers = RunBuffers::default();
 
-        if !self.run_detection_stage(&opts, switches, &mut prepared, &mut buffers, &mut output)? {
+        opts.check_interrupted()?;
+        let has_boxes = self.run_detection_stage(
+            opts,
+            switches,
+            &mut prepared,
+            &mut buffers,
+            &mut output,
+            scratch,
+        )?;
+        if switches.use_det {
+            emit_detection_event(&prepared, &buffers, observer);
+        }
+        if !has_boxes {
             output.e2e_ms = Some(e2e_start.elapsed().as_secs_f32() * 1000.0);
             return Ok(output);
         }
 
-        self.run_classification_stage(switches, &mut buffers, &mut output)?;
-        self.run_recognition_stage(switches, &mut buffers, &mut output)?;
+        opts.check_interrupted()?;
+        self.run_classification_stage(switches, &mut buffers, &mut output, scratch)?;
+        opts.check_interrupted()?;
+        self.run_recognition_stage(opts, switches, &mut buffers, &mut output, scratch, observer)?;
         self.finalize_detection_outputs(switches, &prepared, &mut buffers, &mut output)?;
         self.finalize_recognition_outputs(switches, &buffers.lines, &mut output);
 
@@ -107,6 +285,10 @@ impl RapidOcr {
         let use_rec = opts.use_rec.unwrap_or(self.config.global.use_rec);
         RunSwitches {
             use_det,
+            use_doc_orientation: use_det
+                && opts
+                    .use_doc_orientation
+                    .unwrap_or(self.config.global.use_doc_orientation),
             use_cls,
             use_rec,
             need_stage_images: use_cls || use_rec,
@@ -116,14 +298,242 @@ impl RapidOcr {
             return_single_char_box: opts
                 .return_single_char_box
                 .unwrap_or(self.config.global.return_single_char_box),
+            char_top_k: opts.char_top_k,
             text_score: opts.text_score.unwrap_or(self.config.global.text_score),
         }
     }
 
-    fn prepare_image(&mut self, input: OcrInput, use_det: bool) -> Result<PreparedImage> {
-        let ori_img = self.loader.load(input)?;
+    pub fn run_batch(
+        &mut self,
+        inputs: Vec<OcrInput>,
+        opts: OcrCallOptions,
+    ) -> Vec<Result<OcrOutput>> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let out = self.run_batch_with_scratch(inputs, &opts, &mut scratch);
+        self.scratch = scratch;
+        out
+    }
+
+    // Like `run_batch`, but every input is expanded into its pages (PDF pages,
+    // TIFF pages, animation frames) and all pages share the cls/rec batches.
+    pub fn run_batch_pages(
+        &mut self,
+        inputs: Vec<OcrInput>,
+        opts: OcrCallOptions,
+    ) -> Vec<Result<Vec<OcrPageOutput>>> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let out = self.run_batch_pages_with_scratch(inputs, &opts, &mut scratch);
+        self.scratch = scratch;
+        out
+    }
+
+    fn run_batch_pages_with_scratch(
+        &self,
+        inputs: Vec<OcrInput>,
+        opts: &OcrCallOptions,
+        scratch: &mut RunScratch,
+    ) -> Vec<Result<Vec<OcrPageOutput>>> {
+        let loaded: Vec<Result<PageImages>> = inputs
+            .into_par_iter()
+            .map(|input| self.loader.load_pages(input))
+            .collect();
+        let mut results = Vec::with_capacity(loaded.len());
+        let mut sources = Vec::with_capacity(loaded.len());
+        for pages in loaded {
+            match pages {
+                Ok(pages) => {
+                    results.push(Ok(Vec::new()));
+                    sources.push(Some(pages));
+                }
+                Err(err) => {
+                    results.push(Err(err));
+                    sources.push(None);
+                }
+            }
+        }
+
+        // Pages are pulled `PAGES_PER_BATCH` images at a time, so PDF pages are
+        // rendered only as their batch comes up.
+        let mut current = 0;
+        loop {
+            let mut images = Vec::new();
+            let mut pending: Vec<BatchPage> = Vec::new();
+            while images.len() < PAGES_PER_BATCH && current < sources.len() {
+                let Some(pages) = sources[current].as_mut() else {
+                    current += 1;
+                    continue;
+                };
+                match pages.next() {
+                    Some(Ok(mut page)) => {
+                        let slot = page.image.take().map(|image| {
+                            images.push(OcrInput::Image(image));
+                            images.len() - 1
+                        });
+                        pending.push((current, page, slot));
+                    }
+                    Some(Err(err)) => {
+                        results[current] = Err(err);
+                        sources[current] = None;
+                    }
+                    None => sources[current] = None,
+                }
+            }
+            if pending.is_empty() {
+                break;
+            }
+
+            let mut outputs: BatchResults = if images.is_empty() {
+                Vec::new()
+            } else {
+                self.run_batch_with_scratch(images, opts, scratch)
+                    .into_iter()
+                    .map(Some)
+                    .collect()
+            };
+            for (input, page, slot) in pending {
+                let output = match slot {
+                    Some(slot) => outputs[slot].take().expect("each page runs once"),
+                    None => Ok(OcrOutput::default()),
+                };
+                let Ok(pages) = &mut results[input] else {
+                    continue;
+                };
+                match output {
+                    Ok(output) => pages.push(OcrPageOutput {
+                        page_index: page.index,
+                        skipped: slot.is_none(),
+                        points_per_pixel: page.points_per_pixel,
+                        output,
+                    }),
+                    // The input has failed; stop rendering its remaining pages.
+                    Err(err) => {
+                        results[input] = Err(err);
+                        sources[input] = None;
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    fn run_batch_with_scratch(
+        &self,
+        inputs: Vec<OcrInput>,
+        opts: &OcrCallOptions,
+        scratch: &mut RunScratch,
+    ) -> Vec<Result<OcrOutput>> {
+        let e2e_start = Instant::now();
+        let switches = self.resolve_run_switches(opts);
+        let prepared: Vec<Result<PreparedImage>> = inputs
+            .into_par_iter()
+            .map(|input| self.prepare_image(input, switches))
+            .collect();
+
+        let mut results: BatchResults = (0..prepared.len()).map(|_| None).collect();
+        let mut slots = Vec::with_capacity(prepared.len());
+        for (idx, prepared) in prepared.into_iter().enumerate() {
+            let mut prepared = match prepared {
+                Ok(v) => v,
+                Err(err) => {
+                    results[idx] = Some(Err(err));
+                    continue;
+                }
+            };
+            let mut buffers = RunBuffers::default();
+            let mut output = OcrOutput {
+                page_angle: prepared.page_angle,
+                page_angle_score: prepared.page_angle_score,
+                skew_angle: prepared.deskew.map(|record| record.angle),
+                ..OcrOutput::default()
+            };
+            let detected = opts.check_interrupted().and_then(|()| {
+                self.run_detection_stage(
+                    opts,
+                    switches,
+                    &mut prepared,
+                    &mut buffers,
+                    &mut output,
+                    scratch,
+                )
+            });
+            match detected {
+                Ok(true) => slots.push((
+                    idx,
+                    BatchSlot {
+                        prepared,
+                        buffers,
+                        output,
+                    },
+                )),
+                Ok(false) => results[idx] = Some(Ok(output)),
+                Err(err) => results[idx] = Some(Err(err)),
+            }
+        }
+
+        if let Err(err) = opts.check_interrupted() {
+            abort_interrupted_batch(&mut slots, &mut results, &err);
+        }
+        self.run_batch_classification_stage(switches, &mut slots, &mut results, scratch);
+        if let Err(err) = opts.check_interrupted() {
+            abort_interrupted_batch(&mut slots, &mut results, &err);
+        }
+        self.run_batch_recognition_stage(opts, switches, &mut slots, &mut results, scratch);
+
+        for (idx, mut slot) in slots {
+            let finalized = self.finalize_detection_outputs(
+                switches,
+                &slot.prepared,
+                &mut slot.buffers,
+                &mut slot.output,
+            );
+            results[idx] = Some(finalized.map(|()| {
+                self.finalize_recognition_outputs(switches, &slot.buffers.lines, &mut slot.output);
+                slot.output
+            }));
+        }
+
+        // Stages are shared across the batch, so e2e reports the batch-wide wall time.
+        let e2e_ms = e2e_start.elapsed().as_secs_f32() * 1000.0;
+        results
+            .into_iter()
+            .map(|result| {
+                let mut output = result.expect("every batch input must produce a result")?;
+                output.e2e_ms = Some(e2e_ms);
+                Ok(output)
+            })
+            .collect()
+    }
+
+    fn prepare_image(&self, input: OcrInput, switches: RunSwitches) -> Result<PreparedImage> {
+        let use_det = switches.use_det;
+        let mut ori_img = self.loader.load(input)?;
+        let (mut page_angle, mut page_angle_score) = (None, None);
+        if switches.use_doc_orientation {
+            let (angle, score) = self.doc_orientation()?.predict(&ori_img)?;
+            ori_img = rotate_page(ori_img, angle)?;
+            page_angle = Some(angle);
+            page_angle_score = Some(score);
+        }
+        let mut deskew = None;
+        if use_det && self.config.global.use_deskew {
+            let angle = estimate_skew_angle(
+                &ori_img,
+                self.config.global.deskew_max_angle,
+                self.config.det.runtime.vision_backend,
+            )?;
+            (ori_img, deskew) = deskew_page(ori_img, angle)?;
+        }
         let ori_h = ori_img.height();
         let ori_w = ori_img.width();
+        // Tiled detection keeps full resolution; each tile is sized for the detector instead.
+        let tiled = use_det
+            && self.config.global.use_tiled_det
+            && ori_h.max(ori_w) > self.config.global.max_side_len;
+        let max_side_len = if tiled {
+            usize::MAX
+        } else {
+            self.config.global.max_side_len
+        };
         let preprocessing_backend = if use_det {
             self.config.det.runtime.vision_backend
         } else {
@@ -132,7 +542,7 @@ impl RapidOcr {
         let (proc_img, ratio_h, ratio_w) = resize_image_within_bounds(
             ori_img,
             self.config.global.min_side_len,
-            self.config.global.max_side_len,
+            max_side_len,
             preprocessing_backend,
         )?;
 
@@ -147,29 +557,41 @@ impl RapidOcr {
                 ..PreprocessRecord::default()
             },
             proc_img,
+            tiled,
+            page_angle,
+            page_angle_score,
+            deskew,
         })
     }
 
     fn run_detection_stage(
-        &mut self,
+        &self,
         opts: &OcrCallOptions,
         switches: RunSwitches,
         prepared: &mut PreparedImage,
         buffers: &mut RunBuffers,
         output: &mut OcrOutput,
+        scratch: &mut RunScratch,
     ) -> Result<bool> {
         if switches.use_det {
-            let (padded, pad_top) = apply_vertical_padding(
-                prepared.proc_img.clone(),
-                self.config.global.width_height_ratio,
-                self.config.global.min_height,
-            )?;
-            prepared.proc_img = padded;
-            prepared.preprocess_record.pad_top = pad_top;
+            let det_out = if prepared.tiled {
+                self.detect_tiled(opts, &prepared.proc_img, scratch)?
+            } else {
+                let (padded, pad_top) = apply_vertical_padding(
+                    prepared.proc_img.clone(),
+                    self.config.global.width_height_ratio,
+                    self.config.global.min_height,
+                )?;
+                prepared.proc_img = padded;
+                prepared.preprocess_record.pad_top = pad_top;
 
-            self.detector
-                .update_postprocess(opts.box_thresh, opts.unclip_ratio);
-            let det_out = self.detector.detect(&prepared.proc_img)?;
+                self.detector()?.detect_with_overrides(
+                    &prepared.proc_img,
+                    opts.box_thresh,
+                    opts.unclip_ratio,
+                    &mut scratch.det,
+                )?
+            };
             if det_out.boxes.is_empty() {
                 return Ok(false);
             }
@@ -180,29 +602,92 @@ impl RapidOcr {
             buffers.det_scores = det_out.scores;
 
             if switches.need_stage_images {
-                buffers.stage_images = crop_text_regions(
+                let global = &self.config.global;
+                (buffers.stage_images, buffers.orientations) = crop_text_regions(
                     &prepared.proc_img,
                     &buffers.det_boxes,
+                    global
+                        .use_vertical_text
+                        .then_some(global.vertical_text_ratio),
                     self.config.det.runtime.vision_backend,
                 )?;
             }
         } else if switches.need_stage_images {
             buffers.stage_images.push(prepared.proc_img.clone());
+            buffers.orientations.push(TextOrientation::Horizontal);
         }
 
         Ok(true)
     }
 
+    fn detect_tiled(
+        &self,
+        opts: &OcrCallOptions,
+        img: &crate::config::RecImage,
+        scratch: &mut RunScratch,
+    ) -> Result<DetOutput> {
+        let mut boxes = Vec::new();
+        let mut scores = Vec::new();
+        let mut tile_ids = Vec::new();
+        let mut elapsed_ms = 0.0_f32;
+        let mut breakdown = DetTimingBreakdown::default();
+
+        let tiles = tile_grid(
+            img.width(),
+            img.height(),
+            self.config.global.tile_size,
+            self.config.global.tile_overlap,
+        )?;
+        for (tile_idx, &tile) in tiles.iter().enumerate() {
+            opts.check_interrupted()?;
+            let (tile_img, pad_top) = apply_vertical_padding(
+                crop_tile(img, tile)?,
+                self.config.global.width_height_ratio,
+                self.config.global.min_height,
+            )?;
+            let det_out = self.detector()?.detect_with_overrides(
+                &tile_img,
+                opts.box_thresh,
+                opts.unclip_ratio,
+                &mut scratch.det,
+            )?;
+            elapsed_ms += det_out.elapsed_ms;
+            if let Some(v) = det_out.breakdown {
+                breakdown.preprocess_ms += v.preprocess_ms;
+                breakdown.infer_ms += v.infer_ms;
+                breakdown.postprocess_ms += v.postprocess_ms;
+            }
+            for (mut box_, score) in det_out.boxes.into_iter().zip(det_out.scores) {
+                for p in &mut box_ {
+                    p[0] += tile.x as f32;
+                    p[1] += tile.y as f32 - pad_top as f32;
+                }
+                boxes.push(box_);
+                scores.push(score);
+                tile_ids.push(tile_idx);
+            }
+        }
+
+        let (boxes, scores) = merge_tile_boxes(boxes, scores, tile_ids, &tiles);
+        Ok(DetOutput {
+            boxes,
+            scores,
+            elapsed_ms,
+            breakdown: Some(breakdown),
+        })
+    }
+
     fn run_classification_stage(
-        &mut self,
+        &self,
         switches: RunSwitches,
         buffers: &mut RunBuffers,
         output: &mut OcrOutput,
+        scratch: &mut RunScratch,
     ) -> Result<()> {
         if switches.use_cls {
             let cls_result = self
-                .classifier
-                .classify_in_place(&mut buffers.stage_images)?;
+                .classifier()?
+                .classify_in_place(&mut buffers.stage_images, &mut scratch.cls)?;
             output.cls_res = Some(cls_result.cls_res);
             output.elapsed_ms[1] = Some(cls_result.elapsed_ms);
         }
@@ -210,29 +695,138 @@ impl RapidOcr {
     }
 
     fn run_recognition_stage(
-        &mut self,
+        &self,
+        opts: &OcrCallOptions,
         switches: RunSwitches,
         buffers: &mut RunBuffers,
         output: &mut OcrOutput,
+        scratch: &mut RunScratch,
+        observer: &mut dyn OcrObserver,
     ) -> Result<()> {
         if !switches.use_rec {
             return Ok(());
         }
 
-        let rec = self.recognizer.recognize(
+        let rec = self.recognizer()?.recognize(
             &buffers.stage_images,
             RecognizeOptions {
                 return_word_box: switches.return_word_box,
                 return_single_char_box: switches.return_single_char_box,
+                char_top_k: switches.char_top_k,
+            },
+            opts.decode.as_ref(),
+            &mut scratch.rec,
+            &mut |indices, lines| {
+                for (index, line) in indices.iter().zip(lines) {
+                    let mut line = line.clone();
+                    line.orientation = buffers.orientations[*index];
+                    observer.on_event(OcrEvent::Line {
+                        index: *index,
+                        line,
+                    });
+                }
+                opts.check_interrupted()
             },
         )?;
         output.elapsed_ms[2] = Some(rec.elapsed.as_secs_f32() * 1000.0);
         buffers.lines = rec.lines;
+        apply_line_orientations(&mut buffers.lines, &buffers.orientations);
         Ok(())
     }
 
+    fn run_batch_classification_stage(
+        &self,
+        switches: RunSwitches,
+        slots: &mut Vec<(usize, BatchSlot)>,
+        results: &mut BatchResults,
+        scratch: &mut RunScratch,
+    ) {
+        if !switches.use_cls || slots.is_empty() {
+            return;
+        }
+
+        let (mut pooled, counts) = take_pooled_stage_images(slots);
+        let pooled_result = self
+            .classifier()
+            .and_then(|classifier| classifier.classify_in_place(&mut pooled, &mut scratch.cls));
+        restore_pooled_stage_images(slots, pooled, &counts);
+        match pooled_result {
+            Ok(cls_result) => {
+                let mut cls_res = cls_result.cls_res.into_iter();
+                for ((_, slot), count) in slots.iter_mut().zip(counts) {
+                    slot.output.cls_res = Some(cls_res.by_ref().take(count).collect());
+                    slot.output.elapsed_ms[1] = Some(cls_result.elapsed_ms);
+                }
+            }
+            // Retry image by image so a single failing input does not fail the whole batch.
+            Err(_) => retry_batch_stage_per_slot(slots, results, |slot| {
+                self.run_classification_stage(
+                    switches,
+                    &mut slot.buffers,
+                    &mut slot.output,
+                    scratch,
+                )
+            }),
+        }
+    }
+
+    fn run_batch_recognition_stage(
+        &self,
+        opts: &OcrCallOptions,
+        switches: RunSwitches,
+        slots: &mut Vec<(usize, BatchSlot)>,
+        results: &mut BatchResults,
+        scratch: &mut RunScratch,
+    ) {
+        if !switches.use_rec || slots.is_empty() {
+            return;
+        }
+
+        let (pooled, counts) = take_pooled_stage_images(slots);
+        let pooled_result = self.recognizer().and_then(|recognizer| {
+            recognizer.recognize(
+                &pooled,
+                RecognizeOptions {
+                    return_word_box: switches.return_word_box,
+                    return_single_char_box: switches.return_single_char_box,
+                    char_top_k: switches.char_top_k,
+                },
+                opts.decode.as_ref(),
+                &mut scratch.rec,
+                &mut |_, _| opts.check_interrupted(),
+            )
+        });
+        restore_pooled_stage_images(slots, pooled, &counts);
+        match pooled_result {
+            Ok(rec) => {
+                let rec_ms = rec.elapsed.as_secs_f32() * 1000.0;
+                let mut lines = rec.lines.into_iter();
+                for ((_, slot), count) in slots.iter_mut().zip(counts) {
+                    slot.buffers.lines = lines.by_ref().take(count).collect();
+                    apply_line_orientations(&mut slot.buffers.lines, &slot.buffers.orientations);
+                    slot.output.elapsed_ms[2] = Some(rec_ms);
+                }
+            }
+            Err(err) => {
+                if abort_interrupted_batch(slots, results, &err) {
+                    return;
+                }
+                retry_batch_stage_per_slot(slots, results, |slot| {
+                    self.run_recognition_stage(
+                        opts,
+                        switches,
+                        &mut slot.buffers,
+                        &mut slot.output,
+                        scratch,
+                        &mut |_: OcrEvent| {},
+                    )
+                })
+            }
+        }
+    }
+
     fn finalize_detection_outputs(
-        &mut self,
+        &self,
         switches: RunSwitches,
         prepared: &PreparedImage,
         buffers: &mut RunBuffers,
@@ -243,12 +837,7 @@ impl RapidOcr {
         }
 
         let mut mapped_boxes = std::mem::take(&mut buffers.det_boxes);
-        map_boxes_to_original(
-            &mut mapped_boxes,
-            prepared.preprocess_record,
-            prepared.ori_h,
-            prepared.ori_w,
-        );
+        prepared.map_boxes_to_page(&mut mapped_boxes);
 
         if !switches.use_rec {
             output.boxes = Some(mapped_boxes);
@@ -320,11 +909,216 @@ impl RapidOcr {
 
     pub fn provider_resolutions(&self) -> PipelineProviderResolutions {
         PipelineProviderResolutions {
-            det: self.detector.provider_resolution(),
-            cls: self.classifier.provider_resolution(),
-            rec: self.recognizer.provider_resolution(),
+            det: self.detector.get().map(Detector::provider_resolution),
+            cls: self.classifier.get().map(Classifier::provider_resolution),
+            rec: self
+                .recognizer
+                .get()
+                .map(RecognizerRouter::provider_resolution),
+            doc_ori: self
+                .doc_orientation
+                .get()
+                .map(DocOrientationClassifier::provider_resolution),
+        }
+    }
+
+    pub fn set_line_corrector(&mut self, corrector: Option<Arc<dyn LineCorrector>>) {
+        if let Some(recognizer) = self.recognizer.get_mut() {
+            recognizer.set_line_corrector(corrector.clone());
+        }
+        self.line_corrector = corrector;
+    }
+
+    // Replaces one stage in place; the other stages keep their sessions. A
+    // loaded stage is rebuilt immediately and left untouched if that fails, an
+    // unloaded one picks up the new section on first use.
+    pub fn swap_model(&mut self, model: StageModel) -> Result<()> {
+        let mut config = self.config.clone();
+        match &model {
+            StageModel::Det(det) => config.det = det.clone(),
+            StageModel::Cls(cls) => config.cls = cls.clone(),
+            StageModel::Rec(rec) => config.rec = rec.clone(),
+        }
+        config.validate()?;
+
+        match model {
+            StageModel::Det(_) => {
+                if self.detector.get().is_some() {
+                    let detector =
+                        Detector::new(detector_cfg_from_pipeline(&config), &self.registry)?;
+                    self.detector.replace(detector);
+                } else {
+                    self.detector.reset();
+                }
+            }
+            StageModel::Cls(_) => {
+                if self.classifier.get().is_some() {
+                    let classifier =
+                        Classifier::new(classifier_cfg_from_pipeline(&config), &self.registry)?;
+                    self.classifier.replace(classifier);
+                } else {
+                    self.classifier.reset();
+                }
+            }
+            StageModel::Rec(_) => {
+                if self.recognizer.get().is_some() {
+                    let recognizer =
+                        build_recognizer(&config, &self.registry, self.line_corrector.clone())?;
+                    self.recognizer.replace(recognizer);
+                } else {
+                    self.recognizer.reset();
+                }
+            }
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    // Switches the primary recognition model to the registry model for `lang`.
+    pub fn set_recognizer_lang(&mut self, lang: LangRec) -> Result<()> {
+        if self.config.rec.model.lang == lang {
+            return Ok(());
+        }
+        let mut rec = self.config.rec.clone();
+        rec.model.lang = lang;
+        rec.model.use_registry();
+        self.swap_model(StageModel::Rec(rec))
+    }
+
+    fn detector(&self) -> Result<&Detector> {
+        self.detector.get_or_try_init(|| {
+            Detector::new(detector_cfg_from_pipeline(&self.config), &self.registry)
+        })
+    }
+
+    fn classifier(&self) -> Result<&Classifier> {
+        self.classifier.get_or_try_init(|| {
+            Classifier::new(classifier_cfg_from_pipeline(&self.config), &self.registry)
+        })
+    }
+
+    fn doc_orientation(&self) -> Result<&DocOrientationClassifier> {
+        self.doc_orientation.get_or_try_init(|| {
+            DocOrientationClassifier::new(self.config.doc_ori.clone(), &self.registry)
+        })
+    }
+
+    fn recognizer(&self) -> Result<&RecognizerRouter> {
+        self.recognizer.get_or_try_init(|| {
+            build_recognizer(&self.config, &self.registry, self.line_corrector.clone())
+        })
+    }
+}
+
+fn build_recognizer(
+    config: &EngineConfig,
+    registry: &ModelRegistry,
+    corrector: Option<Arc<dyn LineCorrector>>,
+) -> Result<RecognizerRouter> {
+    let mut recognizer = RecognizerRouter::new(config.rec.clone(), &config.multi_rec, registry)?;
+    // Keep the corrector built from `rec.correction` unless one was installed.
+    if corrector.is_some() {
+        recognizer.set_line_corrector(corrector);
+    }
+    Ok(recognizer)
+}
+
+fn run_each_page<F>(pages: PageImages, mut run: F) -> Result<Vec<OcrPageOutput>>
+where
+    F: FnMut(crate::config::RecImage) -> Result<OcrOutput>,
+{
+    let mut out = Vec::with_capacity(pages.len());
+    for page in pages {
+        let page = page?;
+        let (skipped, output) = match page.image {
+            Some(image) => (false, run(image)?),
+            None => (true, OcrOutput::default()),
+        };
+        out.push(OcrPageOutput {
+            page_index: page.index,
+            skipped,
+            points_per_pixel: page.points_per_pixel,
+            output,
+        });
+    }
+    Ok(out)
+}
+
+fn emit_detection_event(
+    prepared: &PreparedImage,
+    buffers: &RunBuffers,
+    observer: &mut dyn OcrObserver,
+) {
+    let mut boxes = buffers.det_boxes.clone();
+    prepared.map_boxes_to_page(&mut boxes);
+    observer.on_event(OcrEvent::Detection {
+        boxes,
+        scores: buffers.det_scores.clone(),
+    });
+}
+
+fn apply_line_orientations(lines: &mut [LineResult], orientations: &[TextOrientation]) {
+    for (line, orientation) in lines.iter_mut().zip(orientations) {
+        line.orientation = *orientation;
+    }
+}
+
+fn take_pooled_stage_images(
+    slots: &mut [(usize, BatchSlot)],
+) -> (Vec<crate::config::RecImage>, Vec<usize>) {
+    let counts = slots
+        .iter()
+        .map(|(_, slot)| slot.buffers.stage_images.len())
+        .collect();
+    let pooled = slots
+        .iter_mut()
+        .flat_map(|(_, slot)| std::mem::take(&mut slot.buffers.stage_images))
+        .collect();
+    (pooled, counts)
+}
+
+fn restore_pooled_stage_images(
+    slots: &mut [(usize, BatchSlot)],
+    pooled: Vec<crate::config::RecImage>,
+    counts: &[usize],
+) {
+    let mut pooled = pooled.into_iter();
+    for ((_, slot), count) in slots.iter_mut().zip(counts) {
+        slot.buffers.stage_images = pooled.by_ref().take(*count).collect();
+    }
+}
+
+fn abort_interrupted_batch(
+    slots: &mut Vec<(usize, BatchSlot)>,
+    results: &mut BatchResults,
+    err: &RapidOcrError,
+) -> bool {
+    let interrupted: fn() -> RapidOcrError = match err {
+        RapidOcrError::Cancelled => || RapidOcrError::Cancelled,
+        RapidOcrError::Timeout => || RapidOcrError::Timeout,
+        _ => return false,
+    };
+    for (idx, _) in slots.drain(..) {
+        results[idx] = Some(Err(interrupted()));
+    }
+    true
+}
+
+fn retry_batch_stage_per_slot<F>(
+    slots: &mut Vec<(usize, BatchSlot)>,
+    results: &mut BatchResults,
+    mut stage: F,
+) where
+    F: FnMut(&mut BatchSlot) -> Result<()>,
+{
+    let mut kept = Vec::with_capacity(slots.len());
+    for (idx, mut slot) in slots.drain(..) {
+        match stage(&mut slot) {
+            Ok(()) => kept.push((idx, slot)),
+            Err(err) => results[idx] = Some(Err(err)),
         }
     }
+    *slots = kept;
 }
 
 fn init_rayon_global_pool(config: &EngineConfig) {
@@ -364,7 +1158,7 @@ fn available_parallelism() -> Option<usize> {
         .map(|v| v.get().clamp(1, physical_cores))
 }
 
-type FullFilterOutput = (
+pub(crate) type FullFilterOutput = (
     Vec<crate::Quad>,
     Vec<f32>,
     Vec<LineResult>,
@@ -414,7 +1208,7 @@ fn select_items_by_indices<T>(items: Vec<T>, indices: &[usize]) -> Vec<T> {
     out
 }
 
-fn filter_by_text_score_for_full(
+pub(crate) fn filter_by_text_score_for_full(
     boxes: Vec<crate::Quad>,
     det_scores: Vec<f32>,
     lines: Vec<LineResult>,
@@ -435,10 +1229,10 @@ fn filter_by_text_score_for_full(
         }
         out_boxes.push(boxes[idx]);
         out_scores.push(det_scores[idx]);
-        if let Some(word_line) = word_boxes.as_ref().and_then(|v| v.get(idx))
-            && !word_line.is_empty()
-        {
-            out_word_boxes.push(word_line.clone());
+        // One entry per kept line, empty or not, so outputs can index word
+        // boxes by line.
+        if let Some(word_boxes) = &word_boxes {
+            out_word_boxes.push(word_boxes.get(idx).cloned().unwrap_or_default());
         }
         out_lines.push(line);
     }
@@ -472,23 +1266,487 @@ impl RapidOcrEngine {
         })
     }
 
+    pub fn with_download_progress(
+        config: EngineConfig,
+        progress: Arc<dyn DownloadProgress>,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: RapidOcr::with_download_progress(config, progress)?,
+        })
+    }
+
     pub fn run(&mut self, input: OcrInput, options: RunOptions) -> Result<OcrResult> {
         let out = self.inner.run(input, options)?;
         OcrResult::try_from(out)
     }
 
+    pub fn run_with_observer(
+        &mut self,
+        input: OcrInput,
+        options: RunOptions,
+        observer: impl OcrObserver,
+    ) -> Result<OcrResult> {
+        let out = self.inner.run_with_observer(input, options, observer)?;
+        OcrResult::try_from(out)
+    }
+
+    pub fn run_pages(
+        &mut self,
+        input: OcrInput,
+        options: RunOptions,
+    ) -> Result<Vec<OcrPageResult>> {
+        self.inner
+            .run_pages(input, options)?
+            .into_iter()
+            .map(OcrPageResult::try_from)
+            .collect()
+    }
+
+    pub fn run_batch(
+        &mut self,
+        inputs: Vec<OcrInput>,
+        options: RunOptions,
+    ) -> Vec<Result<OcrResult>> {
+        self.inner
+            .run_batch(inputs, options)
+            .into_iter()
+            .map(|out| out.and_then(OcrResult::try_from))
+            .collect()
+    }
+
+    pub fn run_batch_pages(
+        &mut self,
+        inputs: Vec<OcrInput>,
+        options: RunOptions,
+    ) -> Vec<Result<Vec<OcrPageResult>>> {
+        self.inner
+            .run_batch_pages(inputs, options)
+            .into_iter()
+            .map(|pages| pages?.into_iter().map(OcrPageResult::try_from).collect())
+            .collect()
+    }
+
     pub fn provider_resolutions(&self) -> PipelineProviderResolutions {
         self.inner.provider_resolutions()
     }
+
+    pub fn set_line_corrector(&mut self, corrector: Option<Arc<dyn LineCorrector>>) {
+        self.inner.set_line_corrector(corrector);
+    }
+
+    pub fn swap_model(&mut self, model: StageModel) -> Result<()> {
+        self.inner.swap_model(model)
+    }
+
+    pub fn set_recognizer_lang(&mut self, lang: LangRec) -> Result<()> {
+        self.inner.set_recognizer_lang(lang)
+    }
+}
+
+#[derive(Debug)]
+pub struct SharedRapidOcr {
+    inner: RapidOcr,
+    scratch_pool: Mutex<Vec<RunScratch>>,
+}
+
+impl SharedRapidOcr {
+    pub fn new(config: EngineConfig) -> Result<Self> {
+        Ok(Self {
+            inner: RapidOcr::new(config)?,
+            scratch_pool: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn with_download_progress(
+        config: EngineConfig,
+        progress: Arc<dyn DownloadProgress>,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: RapidOcr::with_download_progress(config, progress)?,
+            scratch_pool: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn run(&self, input: OcrInput, opts: OcrCallOptions) -> Result<OcrOutput> {
+        self.run_with_observer(input, opts, |_: OcrEvent| {})
+    }
+
+    pub fn run_with_observer(
+        &self,
+        input: OcrInput,
+        opts: OcrCallOptions,
+        mut observer: impl OcrObserver,
+    ) -> Result<OcrOutput> {
+        let mut scratch = self.acquire_scratch();
+        let out = self
+            .inner
+            .run_with_scratch(input, &opts, &mut scratch, &mut observer);
+        self.release_scratch(scratch);
+        out
+    }
+
+    pub fn run_pages(&self, input: OcrInput, opts: OcrCallOptions) -> Result<Vec<OcrPageOutput>> {
+        let pages = self.inner.loader.load_pages(input)?;
+        run_each_page(pages, |image| {
+            self.run(OcrInput::Image(image), opts.clone())
+        })
+    }
+
+    pub fn run_batch(&self, inputs: Vec<OcrInput>, opts: OcrCallOptions) -> Vec<Result<OcrOutput>> {
+        let mut scratch = self.acquire_scratch();
+        let out = self
+            .inner
+            .run_batch_with_scratch(inputs, &opts, &mut scratch);
+        self.release_scratch(scratch);
+        out
+    }
+
+    pub fn run_batch_pages(
+        &self,
+        inputs: Vec<OcrInput>,
+        opts: OcrCallOptions,
+    ) -> Vec<Result<Vec<OcrPageOutput>>> {
+        let mut scratch = self.acquire_scratch();
+        let out = self
+            .inner
+            .run_batch_pages_with_scratch(inputs, &opts, &mut scratch);
+        self.release_scratch(scratch);
+        out
+    }
+
+    pub fn provider_resolutions(&self) -> PipelineProviderResolutions {
+        self.inner.provider_resolutions()
+    }
+
+    pub fn set_line_corrector(&mut self, corrector: Option<Arc<dyn LineCorrector>>) {
+        self.inner.set_line_corrector(corrector);
+    }
+
+    pub fn swap_model(&mut self, model: StageModel) -> Result<()> {
+        self.inner.swap_model(model)
+    }
+
+    pub fn set_recognizer_lang(&mut self, lang: LangRec) -> Result<()> {
+        self.inner.set_recognizer_lang(lang)
+    }
+
+    fn acquire_scratch(&self) -> RunScratch {
+        self.scratch_pool
+            .lock()
+            .ok()
+            .and_then(|mut pool| pool.pop())
+            .unwrap_or_default()
+    }
+
+    fn release_scratch(&self, scratch: RunScratch) {
+        if let Ok(mut pool) = self.scratch_pool.lock() {
+            pool.push(scratch);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use tiff::encoder::{TiffEncoder, colortype};
+
     use crate::{
-        pipeline::rapid_ocr::{filter_by_text_score_for_full, filter_empty_lines_boxes_and_scores},
-        types::{LineResult, WordBox},
+        config::{ModelSource, RecImage},
+        error::RapidOcrError,
+        input::image_loader::OcrInput,
+        pipeline::{
+            config::EngineConfig,
+            image_ops::PreprocessRecord,
+            rapid_ocr::{
+                BatchResults, BatchSlot, PreparedImage, RunBuffers, SharedRapidOcr,
+                abort_interrupted_batch, filter_by_text_score_for_full,
+                filter_empty_lines_boxes_and_scores, restore_pooled_stage_images,
+                take_pooled_stage_images,
+            },
+            types::{OcrCallOptions, OcrOutput},
+        },
+        types::{LineResult, TextOrientation, WordBox},
     };
 
+    fn batch_slot_with_crops(widths: &[usize]) -> BatchSlot {
+        let proc_img = RecImage::from_bgr_u8(4, 4, vec![0; 4 * 4 * 3]).expect("valid image");
+        BatchSlot {
+            prepared: PreparedImage {
+                ori_h: 4,
+                ori_w: 4,
+                ratio_h: 1.0,
+                ratio_w: 1.0,
+                preprocess_record: PreprocessRecord::default(),
+                proc_img,
+                tiled: false,
+                page_angle: None,
+                page_angle_score: None,
+                deskew: None,
+            },
+            buffers: RunBuffers {
+                stage_images: widths
+                    .iter()
+                    .map(|w| RecImage::from_bgr_u8(*w, 1, vec![0; w * 3]).expect("valid crop"))
+                    .collect(),
+                ..RunBuffers::default()
+            },
+            output: OcrOutput::default(),
+        }
+    }
+
+    // White page with `bars` black bars stacked vertically; the tiny fixture
+    // models under `test/models` detect each bar and read it as "a".
+    fn page_with_bars(bars: usize) -> OcrInput {
+        let (width, height) = (240, 40 + bars * 40);
+        let mut data = vec![255_u8; width * height * 3];
+        for bar in 0..bars {
+            for y in 30 + bar * 40..50 + bar * 40 {
+                for x in 40..200 {
+                    data[(y * width + x) * 3..][..3].fill(0);
+                }
+            }
+        }
+        OcrInput::Bgr {
+            width,
+            height,
+            data,
+        }
+    }
+
+    fn tiny_model_config() -> EngineConfig {
+        let models = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/models");
+        let mut config = EngineConfig::default();
+        config.global.use_cls = false;
+        config.det.model_source = ModelSource::Path(models.join("tiny_det.onnx"));
+        config.rec.model.model_source = ModelSource::Path(models.join("tiny_rec.onnx"));
+        config
+    }
+
+    #[test]
+    fn batch_pages_expands_multi_page_inputs_in_order() {
+        let page_bgr = |bars| match page_with_bars(bars) {
+            OcrInput::Bgr { width, data, .. } => (width, data),
+            _ => unreachable!(),
+        };
+        const TIFF_BARS: [usize; 9] = [2, 1, 3, 1, 2, 1, 3, 1, 2];
+        let mut tiff = Vec::new();
+        {
+            let mut encoder =
+                TiffEncoder::new(std::io::Cursor::new(&mut tiff)).expect("tiff encoder");
+            // With the first input these pages run as two batches.
+            for bars in TIFF_BARS {
+                // Gray bars look the same in BGR and RGB.
+                let (width, data) = page_bgr(bars);
+                let height = data.len() / 3 / width;
+                encoder
+                    .write_image::<colortype::RGB8>(width as u32, height as u32, &data)
+                    .expect("tiff page");
+            }
+        }
+
+        let engine = SharedRapidOcr::new(tiny_model_config()).expect("engine");
+        let results = engine.run_batch_pages(
+            vec![
+                page_with_bars(3),
+                OcrInput::Bytes(tiff),
+                OcrInput::Bytes(b"not an image".to_vec()),
+            ],
+            OcrCallOptions::default(),
+        );
+        let lines_per_page = results
+            .into_iter()
+            .map(|pages| {
+                pages.map(|pages| {
+                    pages
+                        .iter()
+                        .map(|p| (p.page_index, p.output.len()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines_per_page[0].as_ref().ok(), Some(&vec![(0, 3)]));
+        assert_eq!(
+            lines_per_page[1].as_ref().ok(),
+            Some(&TIFF_BARS.into_iter().enumerate().collect::<Vec<_>>())
+        );
+        assert!(lines_per_page[2].is_err());
+    }
+
+    #[test]
+    fn shared_engine_runs_concurrent_callers_with_their_own_options() {
+        let engine = SharedRapidOcr::new(tiny_model_config()).expect("engine");
+        std::thread::scope(|scope| {
+            for bars in 1..=4 {
+                let engine = &engine;
+                scope.spawn(move || {
+                    for round in 0..3 {
+                        let opts = OcrCallOptions {
+                            box_thresh: Some(0.5 + 0.1 * round as f32),
+                            ..OcrCallOptions::default()
+                        };
+                        let out = engine.run(page_with_bars(bars), opts).expect("run");
+                        assert_eq!(out.txts, Some(vec!["a".to_string(); bars]));
+                        assert_eq!(out.boxes.map(|b| b.len()), Some(bars));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn decode_overrides_are_compiled_once_and_unsatisfiable_ones_fail() {
+        let engine = SharedRapidOcr::new(tiny_model_config()).expect("engine");
+        let words = std::env::temp_dir().join(format!("rapidocr-words-{}.txt", std::process::id()));
+        std::fs::write(&words, "a\n").expect("word list");
+        let opts = OcrCallOptions {
+            decode: Some(crate::config::CtcDecodeConfig {
+                beam_width: 3,
+                forbidden_chars: Some(" ".to_string()),
+                word_list_path: Some(words.clone()),
+                ..Default::default()
+            }),
+            // The tiny model is unsure of itself once a beam spreads its mass.
+            text_score: Some(0.0),
+            ..OcrCallOptions::default()
+        };
+        let first = engine.run(page_with_bars(1), opts.clone()).expect("run");
+        std::fs::remove_file(&words).expect("remove word list");
+        // The word list is not read again once its decoding is cached.
+        let second = engine.run(page_with_bars(1), opts).expect("cached run");
+        assert_eq!(first.txts, Some(vec!["a".to_string()]));
+        assert_eq!(second.txts, first.txts);
+
+        let only_b = OcrCallOptions {
+            decode: Some(crate::config::CtcDecodeConfig {
+                pattern: Some("b+".to_string()),
+                ..Default::default()
+            }),
+            ..OcrCallOptions::default()
+        };
+        assert!(matches!(
+            engine.run(page_with_bars(1), only_b),
+            Err(RapidOcrError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn doc_orientation_loads_on_the_first_call_that_enables_it() {
+        let mut config = tiny_model_config();
+        config.doc_ori.model_source =
+            ModelSource::Path(PathBuf::from("test/models/missing_doc_ori.onnx"));
+        let engine = SharedRapidOcr::new(config).expect("doc orientation is not loaded yet");
+        let out = engine
+            .run(page_with_bars(1), OcrCallOptions::default())
+            .expect("run");
+        assert_eq!(out.page_angle, None);
+
+        let opts = OcrCallOptions {
+            use_doc_orientation: Some(true),
+            ..OcrCallOptions::default()
+        };
+        let err = engine
+            .run(page_with_bars(1), opts)
+            .expect_err("enabling the stage loads its model");
+        assert!(err.to_string().contains("missing_doc_ori"));
+    }
+
+    #[test]
+    fn registry_models_from_a_local_manifest_load_without_downloads() {
+        use sha2::{Digest, Sha256};
+
+        let models = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/models");
+        let entry = |name: &str, file: &str| {
+            let path = models.join(file);
+            let sha = Sha256::digest(std::fs::read(&path).expect("fixture"));
+            format!(
+                "      {name}:\n        model_dir: file://{}\n        SHA256: {sha:x}\n",
+                path.display()
+            )
+        };
+        let manifest =
+            std::env::temp_dir().join(format!("rapid-ocr-airgapped-{}.yaml", std::process::id()));
+        std::fs::write(
+            &manifest,
+            format!(
+                "onnxruntime:\n  PP-OCRv4:\n    det:\n{}    rec:\n{}",
+                entry("ch_PP-OCRv4_det_infer.onnx", "tiny_det.onnx"),
+                entry("ch_PP-OCRv4_rec_infer.onnx", "tiny_rec.onnx"),
+            ),
+        )
+        .expect("write manifest");
+
+        let mut config = EngineConfig::default();
+        config.global.use_cls = false;
+        config.model_manifest = Some(manifest.clone());
+        config.det.allow_download = false;
+        config.rec.model.allow_download = false;
+        let engine = SharedRapidOcr::new(config);
+        let _ = std::fs::remove_file(&manifest);
+        let out = engine
+            .expect("local manifest entries need no download")
+            .run(page_with_bars(1), OcrCallOptions::default())
+            .expect("run");
+        assert_eq!(out.txts, Some(vec!["a".to_string()]));
+    }
+
+    #[test]
+    fn pooled_stage_images_are_restored_to_their_source_slots() {
+        let mut slots = vec![
+            (0, batch_slot_with_crops(&[1, 2])),
+            (2, batch_slot_with_crops(&[])),
+            (3, batch_slot_with_crops(&[3, 4, 5])),
+        ];
+
+        let (pooled, counts) = take_pooled_stage_images(&mut slots);
+        assert_eq!(counts, vec![2, 0, 3]);
+        assert_eq!(
+            pooled.iter().map(RecImage::width).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert!(slots.iter().all(|(_, s)| s.buffers.stage_images.is_empty()));
+
+        restore_pooled_stage_images(&mut slots, pooled, &counts);
+        let widths = slots
+            .iter()
+            .map(|(_, s)| {
+                s.buffers
+                    .stage_images
+                    .iter()
+                    .map(RecImage::width)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(widths, vec![vec![1, 2], vec![], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn interrupted_batch_fails_every_pending_slot() {
+        let mut slots = vec![
+            (0, batch_slot_with_crops(&[1])),
+            (2, batch_slot_with_crops(&[2])),
+        ];
+        let mut results: BatchResults = (0..3).map(|_| None).collect();
+
+        let decode_err = RapidOcrError::Decode("boom".to_string());
+        assert!(!abort_interrupted_batch(
+            &mut slots,
+            &mut results,
+            &decode_err
+        ));
+        assert_eq!(slots.len(), 2);
+
+        assert!(abort_interrupted_batch(
+            &mut slots,
+            &mut results,
+            &RapidOcrError::Timeout
+        ));
+        assert!(slots.is_empty());
+        assert!(matches!(results[0], Some(Err(RapidOcrError::Timeout))));
+        assert!(results[1].is_none());
+        assert!(matches!(results[2], Some(Err(RapidOcrError::Timeout))));
+    }
+
     #[test]
     fn filter_empty_lines_keeps_boxes_scores_lines_aligned() {
         let boxes = vec![
@@ -501,11 +1759,17 @@ mod tests {
                 text: "ok".to_string(),
                 score: 0.9,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             },
             LineResult {
                 text: "   ".to_string(),
                 score: 0.8,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             },
         ];
 
@@ -532,16 +1796,25 @@ mod tests {
                 text: "a".to_string(),
                 score: 0.95,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             },
             LineResult {
                 text: "b".to_string(),
                 score: 0.50,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             },
             LineResult {
                 text: "c".to_string(),
                 score: 0.99,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             },
         ];
         let words = Some(vec![
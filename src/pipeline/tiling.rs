@@ -0,0 +1,399 @@
+use crate::{
+    Quad,
+    config::RecImage,
+    det::postprocess::{mini_box_from_points_pure, sort_boxes_like_python},
+    error::{RapidOcrError, Result},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tile {
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+}
+
+impl Rect {
+    fn from_quad(quad: &Quad) -> Self {
+        let mut rect = Self {
+            min_x: f32::MAX,
+            min_y: f32::MAX,
+            max_x: f32::MIN,
+            max_y: f32::MIN,
+        };
+        for p in quad {
+            rect.min_x = rect.min_x.min(p[0]);
+            rect.min_y = rect.min_y.min(p[1]);
+            rect.max_x = rect.max_x.max(p[0]);
+            rect.max_y = rect.max_y.max(p[1]);
+        }
+        rect
+    }
+
+    fn width(&self) -> f32 {
+        (self.max_x - self.min_x).max(0.0)
+    }
+
+    fn height(&self) -> f32 {
+        (self.max_y - self.min_y).max(0.0)
+    }
+
+    fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    fn from_tile(tile: Tile) -> Self {
+        Self {
+            min_x: tile.x as f32,
+            min_y: tile.y as f32,
+            max_x: (tile.x + tile.width) as f32,
+            max_y: (tile.y + tile.height) as f32,
+        }
+    }
+
+    fn intersects(&self, other: &Rect) -> bool {
+        self.min_x < other.max_x
+            && other.min_x < self.max_x
+            && self.min_y < other.max_y
+            && other.min_y < self.max_y
+    }
+}
+
+#[derive(Debug)]
+struct MergedBox {
+    quad: Quad,
+    score: f32,
+    tiles: Vec<usize>,
+}
+
+// Configs built in code skip `EngineConfig::validate`, and a stride that is
+// not positive would place a tile at every pixel, so the sizes are checked here.
+pub(crate) fn tile_grid(
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    overlap: usize,
+) -> Result<Vec<Tile>> {
+    if overlap >= tile_size {
+        return Err(RapidOcrError::Config(format!(
+            "global.tile_overlap ({overlap}) must be < global.tile_size ({tile_size})"
+        )));
+    }
+    let xs = tile_starts(width, tile_size, overlap);
+    let ys = tile_starts(height, tile_size, overlap);
+    let mut tiles = Vec::with_capacity(xs.len() * ys.len());
+    for &y in &ys {
+        for &x in &xs {
+            tiles.push(Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+            });
+        }
+    }
+    Ok(tiles)
+}
+
+// Uses the fewest tiles that keep at least `overlap` pixels between
+// neighbours, spread evenly so the last one ends on the far edge without
+// leaving a sliver-sized step before it.
+fn tile_starts(len: usize, tile_size: usize, overlap: usize) -> Vec<usize> {
+    if len <= tile_size {
+        return vec![0];
+    }
+    let stride = tile_size - overlap;
+    let steps = (len - tile_size).div_ceil(stride);
+    (0..=steps).map(|i| i * (len - tile_size) / steps).collect()
+}
+
+pub(crate) fn crop_tile(img: &RecImage, tile: Tile) -> Result<RecImage> {
+    if tile.x + tile.width > img.width() || tile.y + tile.height > img.height() {
+        return Err(RapidOcrError::InvalidInput(format!(
+            "tile {tile:?} exceeds image bounds {}x{}",
+            img.width(),
+            img.height()
+        )));
+    }
+    let src = img.as_bgr_cow();
+    let src = src.as_ref();
+    let row_bytes = tile.width * 3;
+    let mut out = Vec::with_capacity(row_bytes * tile.height);
+    for y in tile.y..tile.y + tile.height {
+        let start = (y * img.width() + tile.x) * 3;
+        out.extend_from_slice(&src[start..start + row_bytes]);
+    }
+    RecImage::from_bgr_u8(tile.width, tile.height, out)
+}
+
+// Only boxes coming from different tiles are merged, so in-tile detections stay
+// exactly as the detector produced them. A seam duplicate of a box lies inside
+// another tile, so each box is only compared with the boxes filed under the
+// tiles it reaches into; boxes that stay inside their own tile skip the search.
+pub(crate) fn merge_tile_boxes(
+    boxes: Vec<Quad>,
+    scores: Vec<f32>,
+    tile_ids: Vec<usize>,
+    tiles: &[Tile],
+) -> (Vec<Quad>, Vec<f32>) {
+    let tile_rects: Vec<Rect> = tiles.iter().copied().map(Rect::from_tile).collect();
+    let touched_tiles = |quad: &Quad| {
+        let rect = Rect::from_quad(quad);
+        (0..tile_rects.len())
+            .filter(|&t| tile_rects[t].intersects(&rect))
+            .collect::<Vec<_>>()
+    };
+
+    let mut merged: Vec<Option<MergedBox>> = Vec::with_capacity(boxes.len());
+    let mut by_tile: Vec<Vec<usize>> = vec![Vec::new(); tiles.len()];
+    for ((quad, score), tile) in boxes.into_iter().zip(scores).zip(tile_ids) {
+        let mut current = MergedBox {
+            quad,
+            score,
+            tiles: vec![tile],
+        };
+        let mut touched = touched_tiles(&current.quad);
+        if touched.len() > 1 {
+            while let Some(idx) = touched.iter().find_map(|&t| {
+                by_tile[t].iter().copied().find(|&idx| {
+                    merged[idx]
+                        .as_ref()
+                        .is_some_and(|other| is_seam_duplicate(other, &current))
+                })
+            }) {
+                let other = merged[idx].take().expect("candidate is still live");
+                current = merge_pair(other, current);
+                touched = touched_tiles(&current.quad);
+            }
+            for &t in &touched {
+                by_tile[t].push(merged.len());
+            }
+        }
+        merged.push(Some(current));
+    }
+
+    let (mut out_boxes, mut out_scores): (Vec<Quad>, Vec<f32>) = merged
+        .into_iter()
+        .flatten()
+        .map(|v| (v.quad, v.score))
+        .unzip();
+    sort_boxes_like_python(&mut out_boxes, &mut out_scores, 10.0);
+    (out_boxes, out_scores)
+}
+
+fn is_seam_duplicate(a: &MergedBox, b: &MergedBox) -> bool {
+    if a.tiles.iter().any(|t| b.tiles.contains(t)) {
+        return false;
+    }
+    let ra = Rect::from_quad(&a.quad);
+    let rb = Rect::from_quad(&b.quad);
+    let inter_w = ra.max_x.min(rb.max_x) - ra.min_x.max(rb.min_x);
+    let inter_h = ra.max_y.min(rb.max_y) - ra.min_y.max(rb.min_y);
+    if inter_w <= 0.0 || inter_h <= 0.0 {
+        return false;
+    }
+    if is_contained(ra, rb, inter_w * inter_h) {
+        return true;
+    }
+
+    // A line cut by a seam shows up as two pieces on the same baseline that
+    // share the overlap band; require that band to be at least one line thick
+    // so neighbouring words with touching unclipped boxes are not joined.
+    let min_h = ra.height().min(rb.height());
+    let min_w = ra.width().min(rb.width());
+    let horizontal = inter_h / ra.height().max(rb.height()).max(f32::EPSILON) >= 0.5;
+    let vertical = inter_w / ra.width().max(rb.width()).max(f32::EPSILON) >= 0.5;
+    (horizontal && inter_w >= min_h) || (vertical && inter_h >= min_w)
+}
+
+fn is_contained(ra: Rect, rb: Rect, inter_area: f32) -> bool {
+    inter_area / ra.area().min(rb.area()).max(f32::EPSILON) >= 0.8
+}
+
+fn merge_pair(a: MergedBox, b: MergedBox) -> MergedBox {
+    let ra = Rect::from_quad(&a.quad);
+    let rb = Rect::from_quad(&b.quad);
+    let inter_w = ra.max_x.min(rb.max_x) - ra.min_x.max(rb.min_x);
+    let inter_h = ra.max_y.min(rb.max_y) - ra.min_y.max(rb.min_y);
+    let quad = if is_contained(ra, rb, inter_w.max(0.0) * inter_h.max(0.0)) {
+        // Keep the fuller detection as the detector produced it.
+        if ra.area() >= rb.area() {
+            a.quad
+        } else {
+            b.quad
+        }
+    } else {
+        // The smallest rotated box around both pieces, so a tilted line cut
+        // by a seam keeps its angle.
+        let points: Vec<[f32; 2]> = a.quad.iter().chain(&b.quad).copied().collect();
+        mini_box_from_points_pure(&points).map_or(a.quad, |(quad, _)| quad)
+    };
+    let mut tiles = a.tiles;
+    tiles.extend(b.tiles);
+    MergedBox {
+        quad,
+        score: a.score.max(b.score),
+        tiles,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Quad,
+        config::RecImage,
+        error::RapidOcrError,
+        pipeline::tiling::{Tile, crop_tile, merge_tile_boxes, tile_grid, tile_starts},
+    };
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Quad {
+        [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+    }
+
+    fn two_tiles() -> Vec<Tile> {
+        tile_grid(1800, 1000, 1000, 200).expect("valid tile sizes")
+    }
+
+    fn assert_quad_near(actual: &Quad, expected: &Quad, tol: f32) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a[0] - e[0]).abs() <= tol && (a[1] - e[1]).abs() <= tol,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn tile_starts_cover_the_whole_axis_with_overlap() {
+        assert_eq!(tile_starts(500, 1000, 100), vec![0]);
+        assert_eq!(tile_starts(1000, 1000, 100), vec![0]);
+        assert_eq!(tile_starts(2500, 1000, 200), vec![0, 750, 1500]);
+        assert_eq!(tile_starts(1800, 1000, 200), vec![0, 800]);
+    }
+
+    #[test]
+    fn tile_starts_do_not_end_with_a_near_duplicate_tile() {
+        let starts = tile_starts(1801, 1000, 200);
+        assert_eq!(starts, vec![0, 400, 801]);
+        for pair in starts.windows(2) {
+            assert!(pair[1] - pair[0] <= 800);
+            assert!(pair[1] - pair[0] >= 200);
+        }
+    }
+
+    #[test]
+    fn tile_grid_clamps_tiles_to_image() {
+        let tiles = tile_grid(1500, 600, 1000, 100).expect("valid tile sizes");
+        assert_eq!(
+            tiles,
+            vec![
+                Tile {
+                    x: 0,
+                    y: 0,
+                    width: 1000,
+                    height: 600,
+                },
+                Tile {
+                    x: 500,
+                    y: 0,
+                    width: 1000,
+                    height: 600,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tile_grid_rejects_overlap_that_leaves_no_stride() {
+        for (tile_size, overlap) in [(100, 100), (100, 150), (0, 0)] {
+            let err = tile_grid(2000, 2000, tile_size, overlap).expect_err("no positive stride");
+            assert!(
+                matches!(&err, RapidOcrError::Config(msg) if msg.contains("global.tile_overlap")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn crop_tile_copies_the_requested_region() {
+        let pixels: Vec<u8> = (0..4 * 3).flat_map(|v| [v as u8; 3]).collect();
+        let img = RecImage::from_bgr_u8(4, 3, pixels).expect("valid image");
+        let tile = crop_tile(
+            &img,
+            Tile {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 2,
+            },
+        )
+        .expect("tile inside image");
+        assert_eq!(
+            tile.as_bgr_cow().as_ref(),
+            &[5, 5, 5, 6, 6, 6, 9, 9, 9, 10, 10, 10]
+        );
+    }
+
+    #[test]
+    fn merge_joins_line_split_across_seam_and_drops_duplicates() {
+        let boxes = vec![
+            rect(100.0, 10.0, 1000.0, 40.0),
+            rect(10.0, 100.0, 60.0, 130.0),
+            rect(66.0, 100.0, 120.0, 130.0),
+            rect(800.0, 11.0, 1400.0, 41.0),
+            rect(850.0, 300.0, 950.0, 330.0),
+            rect(849.0, 299.0, 951.0, 331.0),
+        ];
+        let scores = vec![0.9, 0.8, 0.7, 0.6, 0.5, 0.95];
+        let tile_ids = vec![0, 0, 0, 1, 0, 1];
+
+        let (out_boxes, out_scores) = merge_tile_boxes(boxes, scores, tile_ids, &two_tiles());
+        assert_eq!(out_boxes.len(), 4);
+        assert_quad_near(&out_boxes[0], &rect(100.0, 10.0, 1400.0, 41.0), 1.0);
+        assert_eq!(
+            out_boxes[1..],
+            [
+                rect(10.0, 100.0, 60.0, 130.0),
+                rect(66.0, 100.0, 120.0, 130.0),
+                rect(849.0, 299.0, 951.0, 331.0),
+            ]
+        );
+        assert_eq!(out_scores, vec![0.9, 0.8, 0.7, 0.95]);
+    }
+
+    #[test]
+    fn merge_keeps_the_angle_of_a_rotated_line_cut_by_a_seam() {
+        // A 30px high line rising 1px every 10px, cut at the seam into two
+        // pieces that share the 800..1000 overlap; `along` spans `x0..x1`.
+        let along = |x0: f32, x1: f32| -> Quad {
+            let norm = 101.0_f32.sqrt();
+            let (dx, dy) = (10.0 / norm, -1.0 / norm);
+            let at = |x: f32, off: f32| [x - dy * off, 500.0 - x / 10.0 + dx * off];
+            [at(x0, 0.0), at(x1, 0.0), at(x1, 30.0), at(x0, 30.0)]
+        };
+        let (out_boxes, _) = merge_tile_boxes(
+            vec![along(600.0, 1000.0), along(800.0, 1300.0)],
+            vec![0.9, 0.8],
+            vec![0, 1],
+            &two_tiles(),
+        );
+        assert_eq!(out_boxes.len(), 1);
+        assert_quad_near(&out_boxes[0], &along(600.0, 1300.0), 1.0);
+    }
+
+    #[test]
+    fn merge_keeps_touching_boxes_from_the_same_tile() {
+        let boxes = vec![rect(0.0, 0.0, 100.0, 30.0), rect(90.0, 0.0, 200.0, 30.0)];
+        let (out_boxes, _) =
+            merge_tile_boxes(boxes.clone(), vec![0.9, 0.9], vec![1, 1], &two_tiles());
+        assert_eq!(out_boxes, boxes);
+    }
+}
@@ -1,13 +1,17 @@
 use crate::{
     Quad,
-    config::RecImage,
+    clock::Instant,
+    config::{CtcDecodeConfig, RecImage},
     det::detector::DetTimingBreakdown,
     error::{RapidOcrError, Result},
+    layout::{Page, analyze_layout},
+    output::json::attach_char_details,
     output::{
-        OcrJsonItem, draw_ocr_result, draw_word_boxes, to_json_items, to_markdown,
-        to_markdown_texts,
+        OcrJsonItem, draw_ocr_result, draw_word_boxes, to_alto, to_hocr, to_json_items,
+        to_markdown, to_markdown_texts, to_plain_text,
     },
-    types::{LineResult, WordBox},
+    pipeline::observer::CancellationToken,
+    types::{LineResult, TextOrientation, WordBox},
 };
 use serde_json::{Value, json};
 
@@ -23,6 +27,14 @@ pub struct OcrOutput {
     pub elapsed_ms: [Option<f32>; 3], // [det, cls, rec]
     pub e2e_ms: Option<f32>,
     pub det_breakdown_ms: Option<DetTimingBreakdown>,
+    // Counter-clockwise rotation applied to the page before detection, set when
+    // the page orientation stage is enabled.
+    pub page_angle: Option<u32>,
+    // Classifier score behind `page_angle`; below the configured threshold the
+    // page is left unrotated and `page_angle` is 0.
+    pub page_angle_score: Option<f32>,
+    // Skew in degrees removed by the deskew stage, when it rotated the page.
+    pub skew_angle: Option<f32>,
 }
 
 impl OcrOutput {
@@ -38,7 +50,9 @@ impl OcrOutput {
         let txts = self.txts.as_deref().unwrap_or(&[]);
         let scores = self.scores.as_deref().unwrap_or(&[]);
         let boxes = self.boxes.as_deref();
-        to_json_items(boxes, txts, scores)
+        let mut items = to_json_items(boxes, txts, scores)?;
+        attach_char_details(&mut items, self.lines.as_deref().unwrap_or(&[]))?;
+        Ok(items)
     }
 
     pub fn to_markdown(&self) -> Result<String> {
@@ -52,6 +66,13 @@ impl OcrOutput {
         }
     }
 
+    pub fn to_text(&self) -> Result<String> {
+        match (self.boxes.as_deref(), self.txts.as_deref()) {
+            (Some(boxes), Some(txts)) => to_plain_text(boxes, txts),
+            (_, txts) => Ok(txts.unwrap_or(&[]).join("\n")),
+        }
+    }
+
     pub fn visualize(&self, image: &RecImage, use_word_boxes: bool) -> Option<image::RgbImage> {
         if use_word_boxes && let Some(word_boxes) = &self.word_boxes {
             return Some(draw_word_boxes(image, word_boxes));
@@ -106,6 +127,9 @@ impl StageTimings {
 pub struct DetResult {
     pub boxes: Vec<Quad>,
     pub scores: Vec<f32>,
+    pub page_angle: Option<u32>,
+    pub page_angle_score: Option<f32>,
+    pub skew_angle: Option<f32>,
     pub timings: StageTimings,
 }
 
@@ -134,6 +158,11 @@ pub struct FullResult {
     pub scores: Vec<f32>,
     pub word_boxes: Option<Vec<Vec<WordBox>>>,
     pub cls_res: Option<Vec<(String, f32)>>,
+    // Page rotation and deskew the boxes were mapped back through, as in
+    // `OcrOutput`.
+    pub page_angle: Option<u32>,
+    pub page_angle_score: Option<f32>,
+    pub skew_angle: Option<f32>,
     pub timings: StageTimings,
 }
 
@@ -162,12 +191,14 @@ impl OcrResult {
     }
 
     pub fn to_json_items(&self) -> Result<Vec<OcrJsonItem>> {
-        match self {
-            Self::Empty => Ok(Vec::new()),
-            Self::Det(_) | Self::Cls(_) => Ok(Vec::new()),
-            Self::Rec(v) => to_json_items(None, &v.txts, &v.scores),
-            Self::Full(v) => to_json_items(Some(&v.boxes), &v.txts, &v.scores),
-        }
+        let (mut items, lines) = match self {
+            Self::Empty => return Ok(Vec::new()),
+            Self::Det(_) | Self::Cls(_) => return Ok(Vec::new()),
+            Self::Rec(v) => (to_json_items(None, &v.txts, &v.scores)?, &v.lines),
+            Self::Full(v) => (to_json_items(Some(&v.boxes), &v.txts, &v.scores)?, &v.lines),
+        };
+        attach_char_details(&mut items, lines)?;
+        Ok(items)
     }
 
     pub fn to_json(&self) -> Result<Value> {
@@ -177,6 +208,9 @@ impl OcrResult {
                 "kind": "det",
                 "boxes": v.boxes,
                 "det_scores": v.scores,
+                "page_angle": v.page_angle,
+                "page_angle_score": v.page_angle_score,
+                "skew_angle": v.skew_angle,
             })),
             Self::Cls(v) => Ok(json!({
                 "kind": "cls",
@@ -184,14 +218,18 @@ impl OcrResult {
             })),
             Self::Rec(v) => Ok(json!({
                 "kind": "rec",
-                "items": to_json_items(None, &v.txts, &v.scores)?,
+                "items": self.to_json_items()?,
                 "word_boxes": v.word_boxes,
             })),
             Self::Full(v) => Ok(json!({
                 "kind": "full",
-                "items": to_json_items(Some(&v.boxes), &v.txts, &v.scores)?,
+                "items": self.to_json_items()?,
                 "det_scores": v.det_scores,
                 "word_boxes": v.word_boxes,
+                "page_angle": v.page_angle,
+                "page_angle_score": v.page_angle_score,
+                "skew_angle": v.skew_angle,
+                "layout": analyze_layout(&v.boxes, &v.txts)?,
             })),
         }
     }
@@ -206,6 +244,31 @@ impl OcrResult {
         }
     }
 
+    pub fn to_text(&self) -> Result<String> {
+        match self {
+            Self::Empty | Self::Det(_) | Self::Cls(_) => Ok(String::new()),
+            Self::Rec(v) => Ok(v.txts.join("\n")),
+            Self::Full(v) => to_plain_text(&v.boxes, &v.txts),
+        }
+    }
+
+    // Only full results have boxes to lay out; other modes give an empty page.
+    pub fn layout(&self) -> Result<Page> {
+        match self {
+            Self::Full(v) => analyze_layout(&v.boxes, &v.txts),
+            _ => Ok(Page::default()),
+        }
+    }
+
+    // `width` and `height` are the source image size, used for the page element.
+    pub fn to_hocr(&self, width: usize, height: usize) -> Result<String> {
+        to_hocr(self, width, height)
+    }
+
+    pub fn to_alto(&self, width: usize, height: usize) -> Result<String> {
+        to_alto(self, width, height)
+    }
+
     pub fn visualize(&self, image: &RecImage, use_word_boxes: bool) -> Option<image::RgbImage> {
         match self {
             Self::Full(v) => {
@@ -218,6 +281,11 @@ impl OcrResult {
             _ => None,
         }
     }
+
+    #[cfg(feature = "searchable-pdf")]
+    pub fn to_searchable_pdf(&self, image: &RecImage) -> Result<Vec<u8>> {
+        crate::output::to_searchable_pdf(image, self)
+    }
 }
 
 impl TryFrom<OcrOutput> for OcrResult {
@@ -235,6 +303,9 @@ impl TryFrom<OcrOutput> for OcrResult {
             elapsed_ms,
             e2e_ms,
             det_breakdown_ms,
+            page_angle,
+            page_angle_score,
+            skew_angle,
         } = value;
 
         let timings = StageTimings::from_elapsed_ms(elapsed_ms, e2e_ms, det_breakdown_ms);
@@ -269,6 +340,9 @@ impl TryFrom<OcrOutput> for OcrResult {
                     text,
                     score,
                     word_info: None,
+                    orientation: TextOrientation::Horizontal,
+                    chars: None,
+                    lang: None,
                 })
                 .collect();
         }
@@ -313,6 +387,9 @@ impl TryFrom<OcrOutput> for OcrResult {
                 scores,
                 word_boxes,
                 cls_res,
+                page_angle,
+                page_angle_score,
+                skew_angle,
                 timings,
             }));
         }
@@ -321,6 +398,9 @@ impl TryFrom<OcrOutput> for OcrResult {
             return Ok(OcrResult::Det(DetResult {
                 boxes,
                 scores: det_scores,
+                page_angle,
+                page_angle_score,
+                skew_angle,
                 timings,
             }));
         }
@@ -347,16 +427,149 @@ impl TryFrom<OcrOutput> for OcrResult {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct OcrPageOutput {
+    pub page_index: usize,
+    // Set when the page was not OCRed because it already carries a text layer.
+    pub skipped: bool,
+    // PDF points per pixel of the rendered page (`72 / dpi`); multiply box
+    // coordinates by it to place them on the PDF page. `None` for images.
+    pub points_per_pixel: Option<f32>,
+    pub output: OcrOutput,
+}
+
+#[derive(Debug, Clone)]
+pub struct OcrPageResult {
+    pub page_index: usize,
+    pub skipped: bool,
+    pub points_per_pixel: Option<f32>,
+    pub result: OcrResult,
+}
+
+impl TryFrom<OcrPageOutput> for OcrPageResult {
+    type Error = RapidOcrError;
+
+    fn try_from(value: OcrPageOutput) -> Result<Self> {
+        Ok(Self {
+            page_index: value.page_index,
+            skipped: value.skipped,
+            points_per_pixel: value.points_per_pixel,
+            result: OcrResult::try_from(value.output)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OcrCallOptions {
     pub use_det: Option<bool>,
     pub use_cls: Option<bool>,
     pub use_rec: Option<bool>,
+    // Loads the page orientation model on first use when `global` left it off.
+    pub use_doc_orientation: Option<bool>,
     pub return_word_box: Option<bool>,
     pub return_single_char_box: Option<bool>,
     pub text_score: Option<f32>,
     pub box_thresh: Option<f32>,
     pub unclip_ratio: Option<f32>,
+    // Overrides `rec.decode` for this call.
+    pub decode: Option<CtcDecodeConfig>,
+    // Adds per-character spans, probabilities and this many alternatives to lines.
+    pub char_top_k: Option<usize>,
+    pub cancel_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+}
+
+impl OcrCallOptions {
+    pub(crate) fn check_interrupted(&self) -> Result<()> {
+        if let Some(token) = &self.cancel_token {
+            token.check()?;
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(RapidOcrError::Timeout);
+        }
+        Ok(())
+    }
 }
 
 pub type RunOptions = OcrCallOptions;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::clock::Instant;
+
+    use crate::{
+        error::RapidOcrError,
+        pipeline::{
+            observer::CancellationToken,
+            types::{OcrCallOptions, OcrOutput, OcrResult},
+        },
+    };
+
+    #[test]
+    fn page_transform_survives_conversion_to_result() {
+        let quad = [[0.0, 0.0], [4.0, 0.0], [4.0, 2.0], [0.0, 2.0]];
+        let output = OcrOutput {
+            boxes: Some(vec![quad]),
+            det_scores: Some(vec![0.9]),
+            page_angle: Some(90),
+            page_angle_score: Some(0.97),
+            skew_angle: Some(1.5),
+            ..OcrOutput::default()
+        };
+        let OcrResult::Det(det) = OcrResult::try_from(output.clone()).expect("det result") else {
+            panic!("expected a det result");
+        };
+        assert_eq!(det.page_angle, Some(90));
+        assert_eq!(det.page_angle_score, Some(0.97));
+        assert_eq!(det.skew_angle, Some(1.5));
+
+        let full = OcrOutput {
+            txts: Some(vec!["ab".to_string()]),
+            scores: Some(vec![0.8]),
+            ..output
+        };
+        let json = OcrResult::try_from(full)
+            .expect("full result")
+            .to_json()
+            .expect("json");
+        assert_eq!(json["kind"], "full");
+        assert_eq!(json["page_angle"], 90);
+        assert_eq!(json["skew_angle"], 1.5);
+    }
+
+    #[test]
+    fn call_options_report_cancellation_before_deadline() {
+        assert!(OcrCallOptions::default().check_interrupted().is_ok());
+
+        let future = OcrCallOptions {
+            deadline: Some(Instant::now() + Duration::from_secs(60)),
+            ..OcrCallOptions::default()
+        };
+        assert!(future.check_interrupted().is_ok());
+
+        let expired = OcrCallOptions {
+            deadline: Some(Instant::now()),
+            ..OcrCallOptions::default()
+        };
+        assert!(matches!(
+            expired.check_interrupted(),
+            Err(RapidOcrError::Timeout)
+        ));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = OcrCallOptions {
+            cancel_token: Some(token),
+            ..expired
+        };
+        assert!(matches!(
+            cancelled.check_interrupted(),
+            Err(RapidOcrError::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,318 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use crate::{
+    config::CorrectionConfig,
+    error::{RapidOcrError, Result},
+    types::{CharDetail, LineResult},
+};
+
+// Runs on every recognized line before it leaves the recognizer. `chars` is
+// filled with at least `char_top_k()` alternatives per character even when the
+// caller did not ask for character details; it is cut back afterwards.
+pub trait LineCorrector: Send + Sync {
+    fn char_top_k(&self) -> usize {
+        0
+    }
+
+    fn correct(&self, line: &mut LineResult);
+}
+
+impl fmt::Debug for dyn LineCorrector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LineCorrector")
+    }
+}
+
+// Word-level corrector backed by a frequency list (`word [count]` per line).
+// Only characters below `min_char_prob` may be swapped for one of their
+// alternatives, and a swap is kept only when the dictionary prior outweighs
+// the confidence given up.
+#[derive(Debug, Clone)]
+pub struct WordFrequencyCorrector {
+    freqs: HashMap<String, f64>,
+    min_char_prob: f32,
+    max_edits: usize,
+    top_k: usize,
+}
+
+impl WordFrequencyCorrector {
+    pub fn from_file(path: &Path, config: &CorrectionConfig) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str_with_config(&text, config)
+    }
+
+    pub fn from_str_with_config(text: &str, config: &CorrectionConfig) -> Result<Self> {
+        let mut freqs = HashMap::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let mut parts = line.split_whitespace();
+            let Some(word) = parts.next() else {
+                continue;
+            };
+            let count = match parts.next() {
+                Some(v) => v.parse::<f64>().map_err(|e| {
+                    RapidOcrError::Config(format!(
+                        "word frequency line {} has invalid count `{v}`: {e}",
+                        line_no + 1
+                    ))
+                })?,
+                None => 1.0,
+            };
+            *freqs.entry(word.to_string()).or_insert(0.0) += count;
+        }
+        Ok(Self {
+            freqs,
+            min_char_prob: config.min_char_prob,
+            max_edits: config.max_edits,
+            top_k: config.top_k,
+        })
+    }
+
+    // Log prior of a word plus the log confidence of the characters spelling it.
+    fn score(&self, word: &str, probs: impl Iterator<Item = f32>) -> f64 {
+        let prior = self.freqs.get(word).map_or(0.0, |count| count.ln_1p());
+        prior
+            + probs
+                .map(|p| f64::from(p.max(f32::MIN_POSITIVE)).ln())
+                .sum::<f64>()
+    }
+
+    fn correct_word(&self, chars: &mut [CharDetail]) {
+        let word: String = chars.iter().map(|c| c.text.as_str()).collect();
+        if self.freqs.contains_key(&word) {
+            return;
+        }
+        let mut uncertain: Vec<usize> = (0..chars.len())
+            .filter(|i| chars[*i].prob < self.min_char_prob && !chars[*i].alternatives.is_empty())
+            .collect();
+        uncertain.sort_by(|a, b| chars[*a].prob.total_cmp(&chars[*b].prob));
+        uncertain.truncate(self.max_edits);
+        if uncertain.is_empty() {
+            return;
+        }
+
+        let mut best: Option<(f64, Vec<(usize, usize)>)> = None;
+        let original = self.score(&word, chars.iter().map(|c| c.prob));
+        // Each uncertain position keeps its char (None) or takes an alternative.
+        let mut choice: Vec<Option<usize>> = vec![None; uncertain.len()];
+        loop {
+            let edits: Vec<(usize, usize)> = uncertain
+                .iter()
+                .zip(&choice)
+                .filter_map(|(pos, alt)| alt.map(|alt| (*pos, alt)))
+                .collect();
+            if !edits.is_empty() {
+                let candidate: String = chars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| match edits.iter().find(|(pos, _)| *pos == i) {
+                        Some((_, alt)) => c.alternatives[*alt].text.as_str(),
+                        None => c.text.as_str(),
+                    })
+                    .collect();
+                if self.freqs.contains_key(&candidate) {
+                    let probs = chars.iter().enumerate().map(|(i, c)| {
+                        match edits.iter().find(|(pos, _)| *pos == i) {
+                            Some((_, alt)) => c.alternatives[*alt].prob,
+                            None => c.prob,
+                        }
+                    });
+                    let score = self.score(&candidate, probs);
+                    if score > original && best.as_ref().is_none_or(|(b, _)| score > *b) {
+                        best = Some((score, edits));
+                    }
+                }
+            }
+            if !next_choice(&mut choice, &uncertain, chars) {
+                break;
+            }
+        }
+
+        if let Some((_, edits)) = best {
+            for (pos, alt) in edits {
+                let c = &mut chars[pos];
+                let replacement = c.alternatives.remove(alt);
+                c.alternatives.insert(
+                    0,
+                    crate::types::CharAlternative {
+                        text: std::mem::replace(&mut c.text, replacement.text),
+                        prob: c.prob,
+                    },
+                );
+                c.prob = replacement.prob;
+            }
+        }
+    }
+}
+
+// Cuts character details widened for the corrector back to what the caller
+// asked for: none at all, or `char_top_k` alternatives per character.
+pub(crate) fn restore_char_top_k(line: &mut LineResult, char_top_k: Option<usize>) {
+    match (char_top_k, line.chars.as_mut()) {
+        (None, _) => line.chars = None,
+        (Some(k), Some(chars)) => {
+            for c in chars {
+                c.alternatives.truncate(k);
+            }
+        }
+        (Some(_), None) => {}
+    }
+}
+
+// Odometer over `None, Some(0), .., Some(len - 1)` for every uncertain position.
+fn next_choice(choice: &mut [Option<usize>], uncertain: &[usize], chars: &[CharDetail]) -> bool {
+    for (slot, pos) in choice.iter_mut().zip(uncertain) {
+        let alts = chars[*pos].alternatives.len();
+        match slot {
+            None => {
+                *slot = Some(0);
+                return true;
+            }
+            Some(i) if *i + 1 < alts => {
+                *i += 1;
+                return true;
+            }
+            _ => *slot = None,
+        }
+    }
+    false
+}
+
+impl LineCorrector for WordFrequencyCorrector {
+    fn char_top_k(&self) -> usize {
+        self.top_k
+    }
+
+    fn correct(&self, line: &mut LineResult) {
+        let Some(chars) = line.chars.as_mut() else {
+            return;
+        };
+        let before: String = chars.iter().map(|c| c.text.as_str()).collect();
+        for word in chars.split_mut(|c| c.text.trim().is_empty()) {
+            if !word.is_empty() {
+                self.correct_word(word);
+            }
+        }
+        let after: String = chars.iter().map(|c| c.text.as_str()).collect();
+        if after == before {
+            return;
+        }
+
+        let char_count = |s: &str| s.chars().count();
+        // Word info holds one entry per non-space char; only rewrite it when
+        // the correction kept that layout.
+        if let Some(info) = line.word_info.as_mut()
+            && char_count(&before) == char_count(&after)
+        {
+            let mut new_chars = after.chars().filter(|ch| !ch.is_whitespace());
+            for slot in info.words.iter_mut().flatten() {
+                if let Some(ch) = new_chars.next() {
+                    *slot = ch.to_string();
+                }
+            }
+        }
+        line.score = chars.iter().map(|c| c.prob).sum::<f32>() / chars.len().max(1) as f32;
+        line.text = after;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::CorrectionConfig,
+        rec::correction::{LineCorrector, WordFrequencyCorrector, restore_char_top_k},
+        types::{CharAlternative, CharDetail, LineResult, TextOrientation},
+    };
+
+    fn detail(text: &str, prob: f32, alternatives: &[(&str, f32)]) -> CharDetail {
+        CharDetail {
+            text: text.to_string(),
+            prob,
+            start: 0,
+            end: 1,
+            alternatives: alternatives
+                .iter()
+                .map(|(text, prob)| CharAlternative {
+                    text: (*text).to_string(),
+                    prob: *prob,
+                })
+                .collect(),
+        }
+    }
+
+    fn line(chars: Vec<CharDetail>) -> LineResult {
+        LineResult {
+            text: chars.iter().map(|c| c.text.as_str()).collect(),
+            score: 0.0,
+            word_info: None,
+            orientation: TextOrientation::Horizontal,
+            chars: Some(chars),
+            lang: None,
+        }
+    }
+
+    fn corrector() -> WordFrequencyCorrector {
+        WordFrequencyCorrector::from_str_with_config(
+            "BOX 120\nINVOICE 40\n\n",
+            &CorrectionConfig::default(),
+        )
+        .expect("valid word list")
+    }
+
+    #[test]
+    fn low_confidence_chars_take_dictionary_alternatives() {
+        let mut result = line(vec![
+            detail("B", 0.99, &[("8", 0.01)]),
+            detail("0", 0.55, &[("O", 0.4)]),
+            detail("X", 0.98, &[]),
+            detail(" ", 0.99, &[]),
+            detail("1", 0.97, &[("l", 0.02)]),
+        ]);
+        corrector().correct(&mut result);
+        assert_eq!(result.text, "BOX 1");
+        let chars = result.chars.expect("chars kept");
+        assert_eq!(chars[1].alternatives[0].text, "0");
+        assert!((chars[1].prob - 0.4).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn confident_chars_are_never_overridden() {
+        let mut result = line(vec![
+            detail("B", 0.99, &[]),
+            detail("0", 0.95, &[("O", 0.04)]),
+            detail("X", 0.98, &[]),
+        ]);
+        corrector().correct(&mut result);
+        assert_eq!(result.text, "B0X");
+    }
+
+    #[test]
+    fn corrected_lines_keep_only_the_requested_alternatives() {
+        let chars = vec![
+            detail("B", 0.99, &[("8", 0.005), ("3", 0.003)]),
+            detail("0", 0.55, &[("O", 0.4), ("Q", 0.03), ("D", 0.01)]),
+            detail("X", 0.98, &[("K", 0.01)]),
+        ];
+        let mut result = line(chars.clone());
+        corrector().correct(&mut result);
+        restore_char_top_k(&mut result, Some(1));
+        assert_eq!(result.text, "BOX");
+        let kept = result.chars.expect("chars requested");
+        assert!(kept.iter().all(|c| c.alternatives.len() <= 1));
+        assert_eq!(kept[1].alternatives[0].text, "0");
+
+        let mut hidden = line(chars);
+        corrector().correct(&mut hidden);
+        restore_char_top_k(&mut hidden, None);
+        assert!(hidden.chars.is_none());
+    }
+
+    #[test]
+    fn malformed_counts_are_rejected() {
+        let err = WordFrequencyCorrector::from_str_with_config(
+            "BOX many\n",
+            &CorrectionConfig::default(),
+        )
+        .expect_err("count must be numeric");
+        assert!(err.to_string().contains("line 1"));
+    }
+}
@@ -0,0 +1,457 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs,
+};
+
+use ndarray::{ArrayView2, Axis};
+use regex_automata::{
+    Anchored, MatchKind,
+    dfa::{Automaton, StartKind, dense},
+    util::{primitives::StateID, start},
+};
+
+use crate::{
+    config::CtcDecodeConfig,
+    error::{RapidOcrError, Result},
+};
+
+// Classes below this probability are never proposed as extensions; a
+// constraint that rules out every likely class still reaches the next best.
+const MIN_CANDIDATE_PROB: f32 = 1e-6;
+
+// A line emitted by the beam search: `(timestep, class, prob)` per character.
+pub(crate) type BeamPath = Vec<(usize, usize, f32)>;
+
+#[derive(Debug, Default)]
+pub struct CtcDecoding {
+    beam_width: usize,
+    // Per-class mask from allowed/forbidden chars; the blank is always allowed.
+    allowed: Option<Vec<bool>>,
+    regex: Option<dense::DFA<Vec<u32>>>,
+    trie: Option<WordTrie>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConstraintState {
+    regex: Option<StateID>,
+    trie: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Beam {
+    blank: f64,
+    non_blank: f64,
+    state: ConstraintState,
+    path: BeamPath,
+}
+
+impl Beam {
+    fn total(&self) -> f64 {
+        self.blank + self.non_blank
+    }
+}
+
+impl CtcDecoding {
+    // `character` is the decoder's class list, blank included at index 0.
+    pub(crate) fn new(config: &CtcDecodeConfig, character: &[String]) -> Result<Self> {
+        let allowed =
+            (config.allowed_chars.is_some() || config.forbidden_chars.is_some()).then(|| {
+                character
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, class)| {
+                        idx == 0
+                            || class.chars().all(|ch| {
+                                config
+                                    .allowed_chars
+                                    .as_ref()
+                                    .is_none_or(|set| set.contains(ch))
+                                    && !config
+                                        .forbidden_chars
+                                        .as_ref()
+                                        .is_some_and(|set| set.contains(ch))
+                            })
+                    })
+                    .collect()
+            });
+
+        let regex = config
+            .pattern
+            .as_deref()
+            .map(|pattern| {
+                dense::Builder::new()
+                    .configure(
+                        dense::Config::new()
+                            .match_kind(MatchKind::All)
+                            .start_kind(StartKind::Anchored),
+                    )
+                    .build(pattern)
+                    .map_err(|e| {
+                        RapidOcrError::Config(format!("invalid decode pattern `{pattern}`: {e}"))
+                    })
+            })
+            .transpose()?;
+
+        let mut words = config.word_list.clone();
+        if let Some(path) = &config.word_list_path {
+            let text = fs::read_to_string(path)?;
+            words.extend(text.lines().map(str::trim).map(str::to_string));
+        }
+        let trie = (!words.is_empty()).then(|| WordTrie::from_words(&words));
+
+        Ok(Self {
+            beam_width: config.beam_width,
+            allowed,
+            regex,
+            trie,
+        })
+    }
+
+    // Charset limits alone do not need a beam: masking the argmax already lets
+    // the best allowed class win each timestep.
+    pub(crate) fn is_greedy(&self) -> bool {
+        self.beam_width <= 1 && self.regex.is_none() && self.trie.is_none()
+    }
+
+    pub(crate) fn class_mask(&self) -> Option<&[bool]> {
+        self.allowed.as_deref()
+    }
+
+    // CTC prefix beam search over softmax outputs `[timesteps, classes]`. Returns
+    // the best prefix whose constraints accept the full line. A line that only
+    // reads as blank gives an empty path; one where every surviving prefix is
+    // rejected is an error rather than a silently dropped line.
+    pub(crate) fn beam_search(
+        &self,
+        probs: ArrayView2<'_, f32>,
+        character: &[String],
+    ) -> Result<BeamPath> {
+        let width = self.beam_width.max(1);
+        let mut beams = vec![(
+            Vec::<usize>::new(),
+            Beam {
+                blank: 1.0,
+                non_blank: 0.0,
+                state: self.start_state()?,
+                path: Vec::new(),
+            },
+        )];
+
+        for (t, row) in probs.axis_iter(Axis(0)).enumerate() {
+            let mut candidates: Vec<usize> = (1..row.len().min(character.len()))
+                .filter(|c| row[*c] >= MIN_CANDIDATE_PROB)
+                .filter(|c| self.allowed.as_ref().is_none_or(|mask| mask[*c]))
+                .collect();
+            candidates.sort_by(|a, b| row[*b].total_cmp(&row[*a]));
+
+            let blank_prob = f64::from(row[0]);
+            let mut next: HashMap<Vec<usize>, Beam> = HashMap::new();
+            for (prefix, beam) in &beams {
+                add_beam(
+                    &mut next,
+                    prefix.clone(),
+                    beam,
+                    beam.total() * blank_prob,
+                    0.0,
+                );
+                let last = prefix.last().copied();
+                // A repeated class without a blank in between collapses.
+                if let Some(last) = last {
+                    let p = f64::from(row[last]);
+                    add_beam(&mut next, prefix.clone(), beam, 0.0, beam.non_blank * p);
+                }
+
+                let mut extended = 0;
+                for &class in &candidates {
+                    if extended == width {
+                        break;
+                    }
+                    let Some(state) = self.advance(beam.state, &character[class]) else {
+                        continue;
+                    };
+                    extended += 1;
+                    let from = if last == Some(class) {
+                        beam.blank
+                    } else {
+                        beam.total()
+                    };
+                    if from <= 0.0 {
+                        continue;
+                    }
+                    let mut new_prefix = prefix.clone();
+                    new_prefix.push(class);
+                    let mut extension = beam.clone();
+                    extension.state = state;
+                    extension.path.push((t, class, row[class]));
+                    add_beam(
+                        &mut next,
+                        new_prefix,
+                        &extension,
+                        0.0,
+                        from * f64::from(row[class]),
+                    );
+                }
+            }
+
+            let mut ranked: Vec<(Vec<usize>, Beam)> = next.into_iter().collect();
+            ranked.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
+            ranked.truncate(width);
+            // Rescale so long lines do not underflow.
+            if let Some(top) = ranked.first().map(|(_, beam)| beam.total())
+                && top > 0.0
+            {
+                for (_, beam) in &mut ranked {
+                    beam.blank /= top;
+                    beam.non_blank /= top;
+                }
+            }
+            beams = ranked;
+        }
+
+        if let Some(idx) = beams.iter().position(|(_, beam)| self.accepts(beam.state)) {
+            return Ok(beams.swap_remove(idx).1.path);
+        }
+        match beams.first() {
+            Some((prefix, _)) if !prefix.is_empty() => {
+                let best: String = prefix.iter().map(|c| character[*c].as_str()).collect();
+                Err(RapidOcrError::Decode(format!(
+                    "no reading satisfies the decode constraints (best rejected reading `{best}`)"
+                )))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn start_state(&self) -> Result<ConstraintState> {
+        let regex = self
+            .regex
+            .as_ref()
+            .map(|dfa| {
+                dfa.start_state(&start::Config::new().anchored(Anchored::Yes))
+                    .map_err(|e| RapidOcrError::Config(format!("decode pattern start: {e}")))
+            })
+            .transpose()?;
+        Ok(ConstraintState { regex, trie: 0 })
+    }
+
+    fn advance(&self, mut state: ConstraintState, class: &str) -> Option<ConstraintState> {
+        if let (Some(dfa), Some(id)) = (&self.regex, state.regex.as_mut()) {
+            for byte in class.bytes() {
+                *id = dfa.next_state(*id, byte);
+                if dfa.is_dead_state(*id) || dfa.is_quit_state(*id) {
+                    return None;
+                }
+            }
+        }
+        if let Some(trie) = &self.trie {
+            for ch in class.chars() {
+                state.trie = trie.step(state.trie, ch)?;
+            }
+        }
+        Some(state)
+    }
+
+    fn accepts(&self, state: ConstraintState) -> bool {
+        let regex_ok = match (&self.regex, state.regex) {
+            (Some(dfa), Some(id)) => dfa.is_match_state(dfa.next_eoi_state(id)),
+            _ => true,
+        };
+        regex_ok
+            && self
+                .trie
+                .as_ref()
+                .is_none_or(|trie| trie.accepts(state.trie))
+    }
+}
+
+fn add_beam(
+    next: &mut HashMap<Vec<usize>, Beam>,
+    prefix: Vec<usize>,
+    source: &Beam,
+    blank: f64,
+    non_blank: f64,
+) {
+    if blank + non_blank <= 0.0 {
+        return;
+    }
+    match next.entry(prefix) {
+        Entry::Occupied(mut entry) => {
+            let beam = entry.get_mut();
+            // Keep the character timing of the most likely alignment.
+            if blank + non_blank > beam.total() {
+                beam.path.clone_from(&source.path);
+            }
+            beam.blank += blank;
+            beam.non_blank += non_blank;
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(Beam {
+                blank,
+                non_blank,
+                state: source.state,
+                path: source.path.clone(),
+            });
+        }
+    }
+}
+
+// Node 0 is the root; a space is only accepted after a complete word and
+// returns to the root, so lines may hold several listed words.
+#[derive(Debug, Default)]
+struct WordTrie {
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    terminal: bool,
+}
+
+impl WordTrie {
+    fn from_words(words: &[String]) -> Self {
+        let mut trie = Self {
+            nodes: vec![TrieNode::default()],
+        };
+        for word in words.iter().filter(|word| !word.is_empty()) {
+            let mut node = 0;
+            for ch in word.chars() {
+                node = match trie.nodes[node].children.get(&ch) {
+                    Some(child) => *child,
+                    None => {
+                        trie.nodes.push(TrieNode::default());
+                        let child = trie.nodes.len() - 1;
+                        trie.nodes[node].children.insert(ch, child);
+                        child
+                    }
+                };
+            }
+            trie.nodes[node].terminal = true;
+        }
+        trie
+    }
+
+    fn step(&self, node: usize, ch: char) -> Option<usize> {
+        if ch == ' ' {
+            return self.accepts(node).then_some(0);
+        }
+        self.nodes[node].children.get(&ch).copied()
+    }
+
+    fn accepts(&self, node: usize) -> bool {
+        node == 0 || self.nodes[node].terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use crate::{config::CtcDecodeConfig, error::RapidOcrError, rec::ctc_beam::CtcDecoding};
+
+    fn classes() -> Vec<String> {
+        ["blank", "0", "O", "1", "l", " "]
+            .iter()
+            .map(|v| (*v).to_string())
+            .collect()
+    }
+
+    // "O" narrowly beats "0", then "l" beats "1".
+    fn preds() -> Array2<f32> {
+        Array2::from_shape_vec(
+            (4, 6),
+            vec![
+                0.05, 0.40, 0.50, 0.02, 0.02, 0.01, //
+                0.90, 0.02, 0.04, 0.02, 0.01, 0.01, //
+                0.05, 0.02, 0.02, 0.40, 0.50, 0.01, //
+                0.90, 0.02, 0.02, 0.02, 0.03, 0.01, //
+            ],
+        )
+        .expect("shape should match")
+    }
+
+    fn text(path: &[(usize, usize, f32)]) -> String {
+        let classes = classes();
+        path.iter().map(|(_, c, _)| classes[*c].as_str()).collect()
+    }
+
+    fn decode(config: CtcDecodeConfig) -> String {
+        let decoding = CtcDecoding::new(&config, &classes()).expect("valid config");
+        text(
+            &decoding
+                .beam_search(preds().view(), &classes())
+                .expect("beam search"),
+        )
+    }
+
+    #[test]
+    fn unconstrained_beam_matches_greedy_reading() {
+        let config = CtcDecodeConfig {
+            beam_width: 5,
+            ..CtcDecodeConfig::default()
+        };
+        assert_eq!(decode(config), "Ol");
+    }
+
+    #[test]
+    fn digit_pattern_forces_next_best_classes() {
+        let config = CtcDecodeConfig {
+            beam_width: 5,
+            pattern: Some(r"[0-9]+".to_string()),
+            ..CtcDecodeConfig::default()
+        };
+        assert_eq!(decode(config), "01");
+
+        let allowed = CtcDecodeConfig {
+            allowed_chars: Some("0123456789".to_string()),
+            ..CtcDecodeConfig::default()
+        };
+        assert_eq!(decode(allowed), "01");
+    }
+
+    #[test]
+    fn word_list_limits_output_to_listed_words() {
+        let config = CtcDecodeConfig {
+            beam_width: 5,
+            word_list: vec!["0l".to_string(), "ll".to_string()],
+            ..CtcDecodeConfig::default()
+        };
+        let decoding = CtcDecoding::new(&config, &classes()).expect("valid config");
+        let path = decoding
+            .beam_search(preds().view(), &classes())
+            .expect("beam search");
+        assert_eq!(text(&path), "0l");
+        // Character timing follows the frames the classes were emitted on.
+        assert_eq!(path.iter().map(|p| p.0).collect::<Vec<_>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn unsatisfiable_constraint_is_a_decode_error() {
+        let config = CtcDecodeConfig {
+            beam_width: 5,
+            pattern: Some(r"[0-9]{3}".to_string()),
+            ..CtcDecodeConfig::default()
+        };
+        let decoding = CtcDecoding::new(&config, &classes()).expect("valid config");
+        let err = decoding
+            .beam_search(preds().view(), &classes())
+            .expect_err("two characters cannot match three digits");
+        assert!(matches!(err, RapidOcrError::Decode(_)));
+
+        let blank = Array2::from_shape_fn((3, 6), |(_, c)| if c == 0 { 0.99 } else { 0.002 });
+        let path = decoding
+            .beam_search(blank.view(), &classes())
+            .expect("blank line");
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn invalid_pattern_is_a_config_error() {
+        let config = CtcDecodeConfig {
+            pattern: Some("(".to_string()),
+            ..CtcDecodeConfig::default()
+        };
+        let err = CtcDecoding::new(&config, &classes()).expect_err("bad regex");
+        assert!(err.to_string().contains("invalid decode pattern"));
+    }
+}
@@ -1,16 +1,14 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
-};
+use std::path::Path;
 
 #[cfg(test)]
 use ndarray::Array3;
 use ndarray::{ArrayView2, ArrayView3, Axis, s};
 
 use crate::{
+    config::{CtcDecodeConfig, RecognizeOptions},
     error::{RapidOcrError, Result},
-    types::{WordInfo, WordType},
+    rec::ctc_beam::CtcDecoding,
+    types::{CharAlternative, CharDetail, WordInfo, WordType},
 };
 
 #[derive(Debug, Clone)]
@@ -19,7 +17,8 @@ pub struct CtcLabelDecoder {
 }
 
 pub type DecodedLine = (String, f32);
-pub type DecodeOutput = (Vec<DecodedLine>, Vec<WordInfo>);
+// Char details are only filled when `RecognizeOptions::char_top_k` is set.
+pub type DecodeOutput = (Vec<DecodedLine>, Vec<WordInfo>, Vec<Vec<CharDetail>>);
 
 impl CtcLabelDecoder {
     pub fn new(character: Option<Vec<String>>, character_path: Option<&Path>) -> Result<Self> {
@@ -44,6 +43,10 @@ impl CtcLabelDecoder {
         })
     }
 
+    pub fn decoding(&self, config: &CtcDecodeConfig) -> Result<CtcDecoding> {
+        CtcDecoding::new(config, &self.character)
+    }
+
     #[cfg(test)]
     pub fn decode(
         &self,
@@ -52,16 +55,28 @@ impl CtcLabelDecoder {
         wh_ratio_list: &[f32],
         max_wh_ratio: f32,
     ) -> Result<DecodeOutput> {
-        self.decode_view(preds.view(), return_word_box, wh_ratio_list, max_wh_ratio)
+        let options = RecognizeOptions {
+            return_word_box,
+            ..RecognizeOptions::default()
+        };
+        self.decode_view(
+            preds.view(),
+            &CtcDecoding::default(),
+            options,
+            wh_ratio_list,
+            max_wh_ratio,
+        )
     }
 
     pub fn decode_view(
         &self,
         preds: ArrayView3<'_, f32>,
-        return_word_box: bool,
+        decoding: &CtcDecoding,
+        options: RecognizeOptions,
         wh_ratio_list: &[f32],
         max_wh_ratio: f32,
     ) -> Result<DecodeOutput> {
+        let return_word_box = options.return_word_box;
         if preds.ndim() != 3 {
             return Err(RapidOcrError::Decode(format!(
                 "preds must be rank 3, got rank {}",
@@ -85,23 +100,15 @@ impl CtcLabelDecoder {
 
         let mut line_results = Vec::with_capacity(batch_size);
         let mut word_results = Vec::with_capacity(batch_size);
+        let mut char_results = Vec::new();
 
         for batch_idx in 0..batch_size {
             let probs = preds.slice(s![batch_idx, .., ..]);
-            let (token_indices, token_probs) = argmax_with_prob(probs);
-
-            let mut selection = vec![true; token_indices.len()];
-            if token_indices.len() >= 2 {
-                for i in 1..token_indices.len() {
-                    selection[i] = token_indices[i] != token_indices[i - 1];
-                }
-            }
-
-            for (idx, token) in token_indices.iter().enumerate() {
-                if *token == 0 {
-                    selection[idx] = false;
-                }
-            }
+            let (token_indices, token_probs, selection) = if decoding.is_greedy() {
+                greedy_selection(probs, decoding.class_mask())
+            } else {
+                beam_selection(probs, decoding, &self.character)?
+            };
 
             let mut conf_list: Vec<f32> = token_probs
                 .iter()
@@ -139,14 +146,128 @@ impl CtcLabelDecoder {
                 info.confs = conf_list;
                 word_results.push(info);
             }
+            if let Some(top_k) = options.char_top_k {
+                char_results.push(self.char_details(
+                    probs,
+                    &token_indices,
+                    &token_probs,
+                    &selection,
+                    top_k,
+                ));
+            }
             line_results.push((text, mean_conf));
         }
 
-        Ok((line_results, word_results))
+        Ok((line_results, word_results, char_results))
+    }
+
+    // A character spans the run of timesteps its class keeps winning after it
+    // was emitted.
+    fn char_details(
+        &self,
+        probs: ArrayView2<'_, f32>,
+        token_indices: &[usize],
+        token_probs: &[f32],
+        selection: &[bool],
+        top_k: usize,
+    ) -> Vec<CharDetail> {
+        let class_text = |idx: usize| self.character.get(idx).cloned().unwrap_or_default();
+        let mut details = Vec::new();
+        for (start, keep) in selection.iter().enumerate() {
+            if !*keep {
+                continue;
+            }
+            let class = token_indices[start];
+            let end = (start + 1..token_indices.len())
+                .find(|t| selection[*t] || token_indices[*t] != class)
+                .unwrap_or(token_indices.len());
+
+            let row = probs.row(start);
+            let mut others: Vec<usize> = (1..row.len().min(self.character.len()))
+                .filter(|idx| *idx != class)
+                .collect();
+            let k = top_k.min(others.len());
+            if k > 0 {
+                others.select_nth_unstable_by(k - 1, |a, b| row[*b].total_cmp(&row[*a]));
+                others.truncate(k);
+                others.sort_by(|a, b| row[*b].total_cmp(&row[*a]));
+            } else {
+                others.clear();
+            }
+
+            details.push(CharDetail {
+                text: class_text(class),
+                prob: token_probs[start],
+                start,
+                end,
+                alternatives: others
+                    .into_iter()
+                    .map(|idx| CharAlternative {
+                        text: class_text(idx),
+                        prob: row[idx],
+                    })
+                    .collect(),
+            });
+        }
+        details
     }
 }
 
-fn argmax_with_prob(probs: ArrayView2<'_, f32>) -> (Vec<usize>, Vec<f32>) {
+fn greedy_selection(
+    probs: ArrayView2<'_, f32>,
+    mask: Option<&[bool]>,
+) -> (Vec<usize>, Vec<f32>, Vec<bool>) {
+    let (token_indices, token_probs) = argmax_with_prob(probs, mask);
+
+    let mut selection = vec![true; token_indices.len()];
+    if token_indices.len() >= 2 {
+        for i in 1..token_indices.len() {
+            selection[i] = token_indices[i] != token_indices[i - 1];
+        }
+    }
+
+    for (idx, token) in token_indices.iter().enumerate() {
+        if *token == 0 {
+            selection[idx] = false;
+        }
+    }
+    (token_indices, token_probs, selection)
+}
+
+// Lays the beam path out per timestep so the rest of decoding (confidences,
+// word info) treats it exactly like a greedy selection.
+fn beam_selection(
+    probs: ArrayView2<'_, f32>,
+    decoding: &CtcDecoding,
+    character: &[String],
+) -> Result<(Vec<usize>, Vec<f32>, Vec<bool>)> {
+    let timesteps = probs.len_of(Axis(0));
+    let mut token_indices = vec![0_usize; timesteps];
+    let mut token_probs = vec![0.0_f32; timesteps];
+    let mut selection = vec![false; timesteps];
+    let path = decoding.beam_search(probs, character)?;
+    for &(t, class, prob) in &path {
+        token_indices[t] = class;
+        token_probs[t] = prob;
+        selection[t] = true;
+    }
+    // Carry each class over the frames it still wins, so char spans match the
+    // greedy layout.
+    let (argmax, _) = argmax_with_prob(probs, None);
+    for &(t, class, _) in &path {
+        for next in t + 1..timesteps {
+            if selection[next] || argmax[next] != class {
+                break;
+            }
+            token_indices[next] = class;
+        }
+    }
+    Ok((token_indices, token_probs, selection))
+}
+
+// With a mask, disallowed classes are skipped so the best allowed class wins
+// the timestep instead of being dropped after decoding.
+fn argmax_with_prob(probs: ArrayView2<'_, f32>, mask: Option<&[bool]>) -> (Vec<usize>, Vec<f32>) {
     let rows = probs.len_of(Axis(0));
     let cols = probs.len_of(Axis(1));
     let mut idxs = Vec::with_capacity(rows);
@@ -155,15 +276,7 @@ fn argmax_with_prob(probs: ArrayView2<'_, f32>) -> (Vec<usize>, Vec<f32>) {
     if let Some(slice) = probs.as_slice_memory_order() {
         for r in 0..rows {
             let row_start = r * cols;
-            let row = &slice[row_start..row_start + cols];
-            let mut max_idx = 0usize;
-            let mut max_val = f32::NEG_INFINITY;
-            for (idx, value) in row.iter().enumerate() {
-                if *value > max_val {
-                    max_val = *value;
-                    max_idx = idx;
-                }
-            }
+            let (max_idx, max_val) = row_argmax(&slice[row_start..row_start + cols], mask);
             idxs.push(max_idx);
             vals.push(max_val);
         }
@@ -171,32 +284,50 @@ fn argmax_with_prob(probs: ArrayView2<'_, f32>) -> (Vec<usize>, Vec<f32>) {
     }
 
     for row in probs.axis_iter(Axis(0)) {
-        let mut max_idx = 0_usize;
-        let mut max_val = f32::NEG_INFINITY;
-        for (idx, value) in row.iter().enumerate() {
-            if *value > max_val {
-                max_val = *value;
-                max_idx = idx;
-            }
-        }
+        let (max_idx, max_val) = match row.as_slice() {
+            Some(row) => row_argmax(row, mask),
+            None => row_argmax(&row.to_vec(), mask),
+        };
         idxs.push(max_idx);
         vals.push(max_val);
     }
     (idxs, vals)
 }
 
-fn read_character_file(path: &Path) -> Result<Vec<String>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut out = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        let trimmed = line.trim();
-        if !trimmed.is_empty() {
-            out.push(trimmed.to_string());
+fn row_argmax(row: &[f32], mask: Option<&[bool]>) -> (usize, f32) {
+    let mut max_idx = 0_usize;
+    let mut max_val = f32::NEG_INFINITY;
+    match mask {
+        None => {
+            for (idx, value) in row.iter().enumerate() {
+                if *value > max_val {
+                    max_val = *value;
+                    max_idx = idx;
+                }
+            }
+        }
+        Some(mask) => {
+            for (idx, (value, allowed)) in row.iter().zip(mask).enumerate() {
+                if *allowed && *value > max_val {
+                    max_val = *value;
+                    max_idx = idx;
+                }
+            }
         }
     }
-    Ok(out)
+    (max_idx, max_val)
+}
+
+fn read_character_file(path: &Path) -> Result<Vec<String>> {
+    Ok(parse_character_text(&std::fs::read_to_string(path)?))
+}
+
+pub(crate) fn parse_character_text(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 fn get_word_info(text: &str, selection: &[bool]) -> WordInfo {
@@ -301,7 +432,14 @@ fn round5_f64(v: f64) -> f32 {
 mod tests {
     use ndarray::Array3;
 
-    use super::CtcLabelDecoder;
+    use super::{CtcLabelDecoder, parse_character_text};
+    use crate::config::{CtcDecodeConfig, RecognizeOptions};
+
+    #[test]
+    fn character_text_skips_blank_lines_and_crlf() {
+        let chars = parse_character_text("a\r\n\n b \nc");
+        assert_eq!(chars, vec!["a", "b", "c"]);
+    }
 
     #[test]
     fn decode_basic_ctc() {
@@ -319,13 +457,89 @@ mod tests {
         )
         .expect("shape should match");
 
-        let (lines, words) = decoder
+        let (lines, words, _) = decoder
             .decode(&preds, true, &[1.0], 1.0)
             .expect("decode should pass");
         assert_eq!(lines[0].0, "ab");
         assert_eq!(words.len(), 1);
     }
 
+    #[test]
+    fn masked_argmax_picks_next_best_allowed_class() {
+        let decoder = CtcLabelDecoder::new(Some(vec!["0".into(), "O".into()]), None)
+            .expect("decoder init should pass");
+        // character list after init: [blank, 0, O, " "]
+        let preds = Array3::from_shape_vec(
+            (1, 3, 4),
+            vec![
+                0.1, 0.3, 0.6, 0.0, // O, then 0 once masked
+                0.1, 0.3, 0.6, 0.0, // repeat collapses after masking too
+                0.9, 0.0, 0.1, 0.0, // blank
+            ],
+        )
+        .expect("shape should match");
+
+        let (lines, _, _) = decoder
+            .decode(&preds, false, &[], 1.0)
+            .expect("decode should pass");
+        assert_eq!(lines[0].0, "O");
+
+        for config in [
+            CtcDecodeConfig {
+                allowed_chars: Some("0123456789".to_string()),
+                ..CtcDecodeConfig::default()
+            },
+            CtcDecodeConfig {
+                forbidden_chars: Some("O".to_string()),
+                ..CtcDecodeConfig::default()
+            },
+        ] {
+            let decoding = decoder.decoding(&config).expect("valid decode config");
+            let (lines, _, _) = decoder
+                .decode_view(
+                    preds.view(),
+                    &decoding,
+                    RecognizeOptions::default(),
+                    &[],
+                    1.0,
+                )
+                .expect("decode should pass");
+            assert_eq!(lines[0], ("0".to_string(), 0.3));
+        }
+    }
+
+    #[test]
+    fn char_details_report_spans_and_alternatives() {
+        let decoder = CtcLabelDecoder::new(Some(vec!["a".into(), "b".into()]), None)
+            .expect("decoder init should pass");
+        let preds = Array3::from_shape_vec(
+            (1, 5, 4),
+            vec![
+                0.1, 0.6, 0.3, 0.0, // a
+                0.2, 0.7, 0.1, 0.0, // a continues
+                0.9, 0.1, 0.0, 0.0, // blank
+                0.1, 0.2, 0.5, 0.2, // b
+                0.9, 0.0, 0.1, 0.0, // blank
+            ],
+        )
+        .expect("shape should match");
+        let options = RecognizeOptions {
+            char_top_k: Some(1),
+            ..RecognizeOptions::default()
+        };
+        let (_, _, chars) = decoder
+            .decode_view(preds.view(), &Default::default(), options, &[], 1.0)
+            .expect("decode should pass");
+        let chars = &chars[0];
+        assert_eq!(chars.len(), 2);
+        assert_eq!((chars[0].start, chars[0].end), (0, 2));
+        assert_eq!(chars[0].alternatives[0].text, "b");
+        assert_eq!((chars[1].start, chars[1].end), (3, 4));
+        assert_eq!(chars[1].prob, 0.5);
+        // The blank is never offered as an alternative.
+        assert_eq!(chars[1].alternatives[0].text, "a");
+    }
+
     #[test]
     fn decode_rejects_wh_ratio_len_mismatch_when_word_box_enabled() {
         let decoder = CtcLabelDecoder::new(Some(vec!["a".into(), "b".into()]), None)
@@ -1,5 +1,8 @@
 pub mod bidi;
+pub mod correction;
+pub mod ctc_beam;
 pub mod decode;
 pub mod preprocess;
 pub mod recognizer;
+pub mod router;
 pub mod word_boxes;
@@ -1,36 +1,53 @@
-use std::{path::PathBuf, time::Instant};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use ndarray::ArrayView4;
 use rayon::prelude::*;
 
 use crate::{
-    config::{LangRec, RecImage, RecognizeOptions, RecognizerConfig, VisionBackend},
+    clock::Instant,
+    config::{
+        CtcDecodeConfig, LangRec, RecImage, RecognizeOptions, RecognizerConfig, VisionBackend,
+    },
     error::{RapidOcrError, Result},
     model_registry::{ModelRegistry, ResolvedRecModel},
-    model_store::{default_model_store_dir, ensure_downloaded, verify_existing_file},
+    model_store::{default_model_store_dir, verify_existing_file},
     rec::{
         bidi::reorder_bidi_for_display,
-        decode::CtcLabelDecoder,
+        correction::{LineCorrector, WordFrequencyCorrector, restore_char_top_k},
+        ctc_beam::CtcDecoding,
+        decode::{CtcLabelDecoder, parse_character_text},
         preprocess::{batch_shape_for, write_resize_norm_img_into_slice_with_scratch},
     },
     runtime::provider::ProviderResolution,
-    runtime::session::OrtSession,
-    types::{LineResult, RecognizeOutput},
+    runtime::session::{OrtSession, SessionContract},
+    types::{LineResult, RecognizeOutput, TextOrientation},
     vision::backend::resolve_backend_strict,
     vision::resize::LinearResizeScratch,
 };
 
+pub type RecBatchCallback<'a> = dyn FnMut(&[usize], &[LineResult]) -> Result<()> + 'a;
+
+// Per-call decode overrides compiled so far; past this many distinct configs
+// the cache starts over rather than growing without bound.
+const DECODING_CACHE_LIMIT: usize = 16;
+
 #[derive(Debug)]
 pub struct Recognizer {
     config: RecognizerConfig,
     vision_backend: VisionBackend,
     session: OrtSession,
     decoder: CtcLabelDecoder,
-    batch_scratch: Vec<f32>,
+    decoding: Arc<CtcDecoding>,
+    call_decodings: Mutex<HashMap<CtcDecodeConfig, Arc<CtcDecoding>>>,
+    corrector: Option<Arc<dyn LineCorrector>>,
 }
 
 impl Recognizer {
-    pub fn new(config: RecognizerConfig) -> Result<Self> {
+    pub fn new(config: RecognizerConfig, registry: &ModelRegistry) -> Result<Self> {
         if config.rec_img_shape[0] != 3 {
             return Err(RapidOcrError::Config(format!(
                 "rec_img_shape must start with channel=3, got {:?}",
@@ -48,41 +65,96 @@ impl Recognizer {
             .clone()
             .unwrap_or_else(default_model_store_dir);
         let vision_backend = resolve_backend_strict(config.runtime.vision_backend)?;
-
-        let registry = ModelRegistry::from_default_yaml()?;
         let resolved = registry.resolve_rec(
             config.model.ocr_version,
             config.model.lang,
             config.model.model_type,
         )?;
 
-        let model_path = resolve_model_path(&config, &resolved, &model_store_dir)?;
-        let mut session = OrtSession::new(&model_path, &config.runtime)?;
+        let mut session = OrtSession::from_source(
+            &config.model.model_source,
+            &config.runtime,
+            SessionContract::Rec,
+            || download_registry_model(&config, registry, &resolved, &model_store_dir),
+        )?;
 
-        let character = session.character_list.take();
-        let character_path = if character.is_none() {
-            resolve_character_path(&config, &resolved, &model_store_dir)?
-        } else {
-            config.model.rec_keys_path.clone()
+        let decoder = match (session.character_list.take(), &config.model.rec_keys_text) {
+            (Some(character), _) => CtcLabelDecoder::new(Some(character), None)?,
+            (None, Some(text)) => CtcLabelDecoder::new(Some(parse_character_text(text)), None)?,
+            (None, None) => {
+                let character_path =
+                    resolve_character_path(&config, registry, &resolved, &model_store_dir)?;
+                CtcLabelDecoder::new(None, character_path.as_deref())?
+            }
+        };
+        let decoding = Arc::new(decoder.decoding(&config.decode)?);
+        let corrector = match &config.correction.word_freq_path {
+            Some(path) => Some(Arc::new(WordFrequencyCorrector::from_file(
+                &verify_existing_file(path)?,
+                &config.correction,
+            )?) as Arc<dyn LineCorrector>),
+            None => None,
         };
-
-        let decoder = CtcLabelDecoder::new(character, character_path.as_deref())?;
 
         Ok(Self {
             config,
             vision_backend,
             session,
             decoder,
-            batch_scratch: Vec::new(),
+            decoding,
+            call_decodings: Mutex::new(HashMap::new()),
+            corrector,
         })
     }
 
+    // Compiling a pattern or reading `word_list_path` is too slow to repeat on
+    // every call, so overrides are built once per distinct config.
+    fn decoding_for(&self, decode: Option<&CtcDecodeConfig>) -> Result<Arc<CtcDecoding>> {
+        let Some(config) = decode.filter(|config| **config != self.config.decode) else {
+            return Ok(Arc::clone(&self.decoding));
+        };
+        let mut cache = self
+            .call_decodings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(decoding) = cache.get(config) {
+            return Ok(Arc::clone(decoding));
+        }
+        let decoding = Arc::new(self.decoder.decoding(config)?);
+        if cache.len() >= DECODING_CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(config.clone(), Arc::clone(&decoding));
+        Ok(decoding)
+    }
+
+    // Replaces the corrector built from `RecognizerConfig::correction`.
+    pub fn set_line_corrector(&mut self, corrector: Option<Arc<dyn LineCorrector>>) {
+        self.corrector = corrector;
+    }
+
+    // `decode` replaces `RecognizerConfig::decode` for this call only. `on_batch`
+    // receives the source indices and lines of every finished batch; returning
+    // an error stops before the next batch is run.
     pub fn recognize(
-        &mut self,
+        &self,
         images: &[RecImage],
         options: RecognizeOptions,
+        decode: Option<&CtcDecodeConfig>,
+        batch_scratch: &mut Vec<f32>,
+        on_batch: &mut RecBatchCallback<'_>,
     ) -> Result<RecognizeOutput> {
         let start = Instant::now();
+        let decoding = self.decoding_for(decode)?;
+        let corrector = self.corrector.as_deref();
+        // The corrector may need character alternatives the caller did not ask for.
+        let decode_options = match corrector.map(|c| c.char_top_k()) {
+            Some(k) => RecognizeOptions {
+                char_top_k: Some(options.char_top_k.unwrap_or(0).max(k)),
+                ..options
+            },
+            None => options,
+        };
 
         if images.is_empty() {
             return Ok(RecognizeOutput::default());
@@ -129,10 +201,10 @@ impl Recognizer {
             let total_len = sample_len.checked_mul(batch_size).ok_or_else(|| {
                 RapidOcrError::InvalidInput("rec batch size overflow".to_string())
             })?;
-            self.batch_scratch.resize(total_len, 0.0);
+            batch_scratch.resize(total_len, 0.0);
 
             if batch_size > 1 {
-                self.batch_scratch[..total_len]
+                batch_scratch[..total_len]
                     .par_chunks_mut(sample_len)
                     .zip(batch_indices.par_iter().copied())
                     .try_for_each_init(
@@ -170,7 +242,7 @@ impl Recognizer {
                     max_wh_ratio,
                     self.config.rec_img_shape,
                     self.vision_backend,
-                    &mut self.batch_scratch[..sample_len],
+                    &mut batch_scratch[..sample_len],
                     &mut tmp_bgr,
                     &mut resize_scratch,
                 )?;
@@ -178,35 +250,55 @@ impl Recognizer {
 
             let batch_view = ArrayView4::from_shape(
                 (batch_size, img_channel, img_height, dst_width),
-                &self.batch_scratch[..total_len],
+                &batch_scratch[..total_len],
             )
             .map_err(|e| {
                 RapidOcrError::InvalidInput(format!("invalid rec batch tensor shape: {e}"))
             })?;
             let decoder = &self.decoder;
-            let (line_results, word_results) =
+            let (line_results, word_results, char_results) =
                 self.session.run_array3_view_with(batch_view, |preds| {
                     decoder.decode_view(
                         preds,
-                        options.return_word_box,
+                        &decoding,
+                        decode_options,
                         &wh_ratio_list,
                         max_wh_ratio as f32,
                     )
                 })?;
 
-            for (rno, (text, score)) in line_results.into_iter().enumerate() {
-                let word_info = if options.return_word_box {
-                    word_results.get(rno).cloned()
-                } else {
-                    None
-                };
-
-                let target_idx = indices[beg + rno];
-                rec_res[target_idx] = Some(LineResult {
-                    text,
-                    score,
-                    word_info,
-                });
+            let is_arabic = self.config.model.lang == LangRec::Arabic;
+            let batch_lines: Vec<LineResult> = line_results
+                .into_iter()
+                .enumerate()
+                .map(|(rno, (text, score))| {
+                    let word_info = if options.return_word_box {
+                        word_results.get(rno).cloned()
+                    } else {
+                        None
+                    };
+                    let mut line = LineResult {
+                        text,
+                        score,
+                        word_info,
+                        orientation: TextOrientation::Horizontal,
+                        chars: char_results.get(rno).cloned(),
+                        lang: Some(self.config.model.lang),
+                    };
+                    if let Some(corrector) = corrector {
+                        corrector.correct(&mut line);
+                        restore_char_top_k(&mut line, options.char_top_k);
+                    }
+                    if is_arabic {
+                        line.text = reorder_bidi_for_display(&line.text);
+                    }
+                    line
+                })
+                .collect();
+
+            on_batch(batch_indices, &batch_lines)?;
+            for (target_idx, line) in batch_indices.iter().zip(batch_lines) {
+                rec_res[*target_idx] = Some(line);
             }
         }
 
@@ -215,12 +307,6 @@ impl Recognizer {
             lines.push(line);
         }
 
-        if self.config.model.lang == LangRec::Arabic {
-            for line in &mut lines {
-                line.text = reorder_bidi_for_display(&line.text);
-            }
-        }
-
         Ok(RecognizeOutput {
             lines,
             elapsed: start.elapsed(),
@@ -232,30 +318,23 @@ impl Recognizer {
     }
 }
 
-fn resolve_model_path(
+fn download_registry_model(
     config: &RecognizerConfig,
+    registry: &ModelRegistry,
     resolved: &ResolvedRecModel,
     model_store_dir: &PathBuf,
 ) -> Result<PathBuf> {
-    if let Some(model_path) = &config.model.model_path {
-        return verify_existing_file(model_path);
-    }
-
-    if !config.model.allow_download {
-        return Err(RapidOcrError::Config(
-            "model_path is not set and allow_download=false".to_string(),
-        ));
-    }
-
-    ensure_downloaded(
+    registry.fetch_model(
         &resolved.model_url,
         resolved.sha256.as_deref(),
         model_store_dir,
+        config.model.allow_download,
     )
 }
 
 fn resolve_character_path(
     config: &RecognizerConfig,
+    registry: &ModelRegistry,
     resolved: &ResolvedRecModel,
     model_store_dir: &PathBuf,
 ) -> Result<Option<PathBuf>> {
@@ -267,13 +346,7 @@ fn resolve_character_path(
         return Ok(None);
     };
 
-    if !config.model.allow_download {
-        return Err(RapidOcrError::Config(
-            "character metadata missing and dict download disabled".to_string(),
-        ));
-    }
-
-    let path = ensure_downloaded(dict_url, None, model_store_dir)?;
+    let path = registry.fetch_file(dict_url, None, model_store_dir, config.model.allow_download)?;
     Ok(Some(path))
 }
 
@@ -284,8 +357,9 @@ mod tests {
     use crate::{
         config::{
             LangRec, ModelType, OcrVersion, ProviderPreference, RecImage, RecognizeOptions,
-            RecognizerConfig, RuntimeConfig, VisionBackend,
+            RecognizerConfig, RuntimeBackend, RuntimeConfig, VisionBackend,
         },
+        model_registry::ModelRegistry,
         rec::recognizer::Recognizer,
         runtime::provider::ResolvedExecutionProvider,
     };
@@ -317,6 +391,7 @@ mod tests {
     fn recognizer_config(
         version: OcrVersion,
         model_type: ModelType,
+        backend: RuntimeBackend,
         vision_backend: VisionBackend,
     ) -> RecognizerConfig {
         let mut model_store_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -328,11 +403,11 @@ mod tests {
                 lang: LangRec::Ch,
                 ocr_version: version,
                 model_type,
-                model_path: None,
-                rec_keys_path: None,
                 allow_download: true,
+                ..Default::default()
             },
             runtime: RuntimeConfig {
+                backend,
                 vision_backend,
                 auto_tune_threads: false,
                 intra_threads: Some(1),
@@ -344,13 +419,27 @@ mod tests {
             rec_batch_num: 6,
             rec_img_shape: [3, 48, 320],
             model_store_dir: Some(model_store_dir),
+            decode: Default::default(),
+            correction: Default::default(),
         }
     }
 
     #[test]
     #[ignore = "downloads v4/v5/v6 recognition models and runs ONNX inference on all image fixtures"]
     fn pure_and_opencv_recognition_match_ch_v4_v5_v6_on_test_images() {
+        let backends = [
+            RuntimeBackend::OnnxCpu,
+            #[cfg(feature = "tract")]
+            RuntimeBackend::Tract,
+        ];
+        for backend in backends {
+            assert_vision_backends_match(backend);
+        }
+    }
+
+    fn assert_vision_backends_match(backend: RuntimeBackend) {
         let images = test_images();
+        let registry = ModelRegistry::from_default_yaml().expect("registry should parse");
         let versions = [
             (OcrVersion::PPocrV4, ModelType::Mobile),
             (OcrVersion::PPocrV5, ModelType::Mobile),
@@ -358,17 +447,15 @@ mod tests {
         ];
 
         for (version, model_type) in versions {
-            let mut pure = Recognizer::new(recognizer_config(
-                version,
-                model_type,
-                VisionBackend::PureRust,
-            ))
+            let pure = Recognizer::new(
+                recognizer_config(version, model_type, backend, VisionBackend::PureRust),
+                &registry,
+            )
             .expect("pure recognizer should initialize");
-            let mut opencv = Recognizer::new(recognizer_config(
-                version,
-                model_type,
-                VisionBackend::OpenCv,
-            ))
+            let opencv = Recognizer::new(
+                recognizer_config(version, model_type, backend, VisionBackend::OpenCv),
+                &registry,
+            )
             .expect("opencv recognizer should initialize");
 
             assert!(matches!(
@@ -383,18 +470,19 @@ mod tests {
             let opts = RecognizeOptions {
                 return_word_box: false,
                 return_single_char_box: false,
+                char_top_k: None,
             };
             let pure_out = pure
-                .recognize(&images, opts)
+                .recognize(&images, opts, None, &mut Vec::new(), &mut |_, _| Ok(()))
                 .expect("pure recognition should run");
             let opencv_out = opencv
-                .recognize(&images, opts)
+                .recognize(&images, opts, None, &mut Vec::new(), &mut |_, _| Ok(()))
                 .expect("opencv recognition should run");
 
             assert_eq!(
                 pure_out.lines.len(),
                 opencv_out.lines.len(),
-                "line count mismatch for {version:?}"
+                "line count mismatch for {backend:?} {version:?}"
             );
             for (idx, (pure_line, opencv_line)) in pure_out
                 .lines
@@ -404,11 +492,11 @@ mod tests {
             {
                 assert_eq!(
                     pure_line.text, opencv_line.text,
-                    "text mismatch for {version:?} image index {idx}"
+                    "text mismatch for {backend:?} {version:?} image index {idx}"
                 );
                 assert_eq!(
                     pure_line.score, opencv_line.score,
-                    "score mismatch for {version:?} image index {idx}"
+                    "score mismatch for {backend:?} {version:?} image index {idx}"
                 );
             }
         }
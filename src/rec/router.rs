@@ -0,0 +1,390 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::Instant,
+    config::{CtcDecodeConfig, LangRec, RecImage, RecognizeOptions, RecognizerConfig},
+    error::Result,
+    model_registry::ModelRegistry,
+    rec::{
+        correction::LineCorrector,
+        recognizer::{RecBatchCallback, Recognizer},
+    },
+    runtime::provider::ProviderResolution,
+    types::{LineResult, RecognizeOutput},
+};
+
+// Extra recognition models for mixed-script pages. Every line is first read by
+// `rec.model`; a line it reads with a score below `reroute_below_score` is read
+// again by every model in `langs`. A routed reading replaces the primary one
+// only when its text is mostly (`min_script_ratio`) in a script that model
+// covers and it scores higher. The primary model's own text is no guide here:
+// its dictionary cannot spell the scripts the routes exist for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MultiRecConfig {
+    pub langs: Vec<LangRec>,
+    pub min_script_ratio: f32,
+    pub reroute_below_score: f32,
+}
+
+impl Default for MultiRecConfig {
+    fn default() -> Self {
+        Self {
+            langs: Vec::new(),
+            min_script_ratio: 0.5,
+            reroute_below_score: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Arabic,
+    Devanagari,
+    Tamil,
+    Telugu,
+    Kannada,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+const SCRIPTS: [Script; 12] = [
+    Script::Latin,
+    Script::Greek,
+    Script::Cyrillic,
+    Script::Arabic,
+    Script::Devanagari,
+    Script::Tamil,
+    Script::Telugu,
+    Script::Kannada,
+    Script::Thai,
+    Script::Hangul,
+    Script::Kana,
+    Script::Han,
+];
+
+fn script_of(ch: char) -> Option<Script> {
+    let script = match ch as u32 {
+        0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F => Script::Latin,
+        0x370..=0x3FF => Script::Greek,
+        0x400..=0x52F => Script::Cyrillic,
+        0x600..=0x6FF | 0x750..=0x77F | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => Script::Arabic,
+        0x900..=0x97F => Script::Devanagari,
+        0xB80..=0xBFF => Script::Tamil,
+        0xC00..=0xC7F => Script::Telugu,
+        0xC80..=0xCFF => Script::Kannada,
+        0xE00..=0xE7F => Script::Thai,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x30FF => Script::Kana,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF => Script::Han,
+        _ => return None,
+    };
+    Some(script)
+}
+
+fn lang_scripts(lang: LangRec) -> &'static [Script] {
+    match lang {
+        LangRec::Ch | LangRec::ChDoc | LangRec::ChineseCht => &[Script::Han, Script::Latin],
+        LangRec::En | LangRec::Latin => &[Script::Latin],
+        LangRec::Arabic => &[Script::Arabic],
+        LangRec::Cyrillic | LangRec::Eslav => &[Script::Cyrillic],
+        LangRec::Devanagari => &[Script::Devanagari],
+        LangRec::Japan => &[Script::Kana, Script::Han],
+        LangRec::Korean => &[Script::Hangul],
+        LangRec::Ka => &[Script::Kannada],
+        LangRec::Ta => &[Script::Tamil],
+        LangRec::Te => &[Script::Telugu],
+        LangRec::Th => &[Script::Thai],
+        LangRec::El => &[Script::Greek],
+    }
+}
+
+// Script holding at least `min_ratio` of the script-bearing characters. Any
+// kana in a Han line marks it as Japanese.
+fn dominant_script(text: &str, min_ratio: f32) -> Option<Script> {
+    let mut counts = [0_usize; SCRIPTS.len()];
+    for script in text.chars().filter_map(script_of) {
+        if let Some(slot) = SCRIPTS.iter().position(|s| *s == script) {
+            counts[slot] += 1;
+        }
+    }
+    let total: usize = counts.iter().sum();
+    let (slot, count) = counts
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by_key(|(_, count)| *count)?;
+    if total == 0 || (count as f32) < min_ratio * total as f32 {
+        return None;
+    }
+    let kana = counts[SCRIPTS.len() - 2];
+    match SCRIPTS[slot] {
+        Script::Han if kana > 0 => Some(Script::Kana),
+        script => Some(script),
+    }
+}
+
+// Whether `text`, as read by the `lang` model, is mostly in a script that model
+// was trained on rather than a forced reading of a foreign script.
+fn reading_fits(lang: LangRec, text: &str, min_ratio: f32) -> bool {
+    dominant_script(text, min_ratio).is_some_and(|script| lang_scripts(lang).contains(&script))
+}
+
+#[derive(Debug)]
+pub struct RecognizerRouter {
+    primary: Recognizer,
+    routes: Vec<Recognizer>,
+    route_langs: Vec<LangRec>,
+    min_script_ratio: f32,
+    reroute_below_score: f32,
+}
+
+impl RecognizerRouter {
+    pub fn new(
+        config: RecognizerConfig,
+        multi: &MultiRecConfig,
+        registry: &ModelRegistry,
+    ) -> Result<Self> {
+        let primary_lang = config.model.lang;
+        let mut route_langs: Vec<LangRec> = Vec::new();
+        for lang in &multi.langs {
+            if *lang != primary_lang && !route_langs.contains(lang) {
+                route_langs.push(*lang);
+            }
+        }
+        // Routed models always come from the registry; explicit paths belong
+        // to the primary model.
+        let routes = route_langs
+            .iter()
+            .map(|lang| {
+                let mut cfg = config.clone();
+                cfg.model.lang = *lang;
+                cfg.model.use_registry();
+                Recognizer::new(cfg, registry)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            primary: Recognizer::new(config, registry)?,
+            routes,
+            route_langs,
+            min_script_ratio: multi.min_script_ratio,
+            reroute_below_score: multi.reroute_below_score,
+        })
+    }
+
+    // Same contract as `Recognizer::recognize`; `on_batch` sees routed lines.
+    pub fn recognize(
+        &self,
+        images: &[RecImage],
+        options: RecognizeOptions,
+        decode: Option<&CtcDecodeConfig>,
+        batch_scratch: &mut Vec<f32>,
+        on_batch: &mut RecBatchCallback<'_>,
+    ) -> Result<RecognizeOutput> {
+        if self.routes.is_empty() {
+            return self
+                .primary
+                .recognize(images, options, decode, batch_scratch, on_batch);
+        }
+
+        let start = Instant::now();
+        let mut routed: Vec<Option<LineResult>> = vec![None; images.len()];
+        let mut route_scratch = Vec::new();
+        self.primary.recognize(
+            images,
+            options,
+            decode,
+            batch_scratch,
+            &mut |indices, lines| {
+                let mut lines = lines.to_vec();
+                self.reroute(
+                    images,
+                    indices,
+                    &mut lines,
+                    options,
+                    decode,
+                    &mut route_scratch,
+                )?;
+                on_batch(indices, &lines)?;
+                for (index, line) in indices.iter().zip(lines) {
+                    routed[*index] = Some(line);
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(RecognizeOutput {
+            lines: routed.into_iter().flatten().collect(),
+            elapsed: start.elapsed(),
+        })
+    }
+
+    fn reroute(
+        &self,
+        images: &[RecImage],
+        indices: &[usize],
+        lines: &mut [LineResult],
+        options: RecognizeOptions,
+        decode: Option<&CtcDecodeConfig>,
+        scratch: &mut Vec<f32>,
+    ) -> Result<()> {
+        let positions: Vec<usize> = (0..lines.len())
+            .filter(|pos| lines[*pos].score < self.reroute_below_score)
+            .collect();
+        if positions.is_empty() {
+            return Ok(());
+        }
+        let subset: Vec<RecImage> = positions
+            .iter()
+            .map(|pos| images[indices[*pos]].clone())
+            .collect();
+        for (lang, recognizer) in self.route_langs.iter().zip(&self.routes) {
+            let rerun =
+                recognizer.recognize(&subset, options, decode, scratch, &mut |_, _| Ok(()))?;
+            for (pos, line) in positions.iter().zip(rerun.lines) {
+                if line.score > lines[*pos].score
+                    && reading_fits(*lang, &line.text, self.min_script_ratio)
+                {
+                    lines[*pos] = line;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_line_corrector(&mut self, corrector: Option<Arc<dyn LineCorrector>>) {
+        for recognizer in &mut self.routes {
+            recognizer.set_line_corrector(corrector.clone());
+        }
+        self.primary.set_line_corrector(corrector);
+    }
+
+    pub fn provider_resolution(&self) -> ProviderResolution {
+        self.primary.provider_resolution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::{
+        config::{LangRec, ModelSource, RecImage, RecognizeOptions, RecognizerConfig},
+        model_registry::ModelRegistry,
+        rec::{
+            correction::LineCorrector,
+            recognizer::Recognizer,
+            router::{RecognizerRouter, Script, dominant_script, reading_fits},
+        },
+        types::LineResult,
+    };
+
+    #[test]
+    fn dominant_script_ignores_digits_and_flags_kana() {
+        assert_eq!(
+            dominant_script("Привет, мир 2024", 0.5),
+            Some(Script::Cyrillic)
+        );
+        assert_eq!(dominant_script("東京タワー", 0.5), Some(Script::Kana));
+        assert_eq!(dominant_script("12:30 -", 0.5), None);
+        assert_eq!(dominant_script("ab вг", 0.6), None);
+    }
+
+    #[test]
+    fn routed_readings_must_be_in_the_route_script() {
+        assert!(reading_fits(LangRec::Cyrillic, "Москва", 0.5));
+        assert!(reading_fits(LangRec::Arabic, "مرحبا", 0.5));
+        assert!(!reading_fits(LangRec::Cyrillic, "Invoice", 0.5));
+        assert!(!reading_fits(LangRec::Korean, "2024", 0.5));
+    }
+
+    // Stands in for a model trained on another script: rewrites whatever the
+    // tiny model read into a fixed line.
+    struct FixedReading(&'static str, f32);
+
+    impl LineCorrector for FixedReading {
+        fn correct(&self, line: &mut LineResult) {
+            line.text = self.0.to_string();
+            line.score = self.1;
+        }
+    }
+
+    fn tiny_recognizer(lang: LangRec, reading: FixedReading) -> Recognizer {
+        let mut config = RecognizerConfig::default();
+        config.model.lang = lang;
+        config.model.model_source = ModelSource::Path(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/models/tiny_rec.onnx"),
+        );
+        let registry = ModelRegistry::from_default_yaml().expect("registry");
+        let mut recognizer = Recognizer::new(config, &registry).expect("tiny recognizer");
+        recognizer.set_line_corrector(Some(Arc::new(reading)));
+        recognizer
+    }
+
+    fn router(primary: FixedReading, routes: Vec<(LangRec, FixedReading)>) -> RecognizerRouter {
+        RecognizerRouter {
+            primary: tiny_recognizer(LangRec::Ch, primary),
+            route_langs: routes.iter().map(|(lang, _)| *lang).collect(),
+            routes: routes
+                .into_iter()
+                .map(|(lang, reading)| tiny_recognizer(lang, reading))
+                .collect(),
+            min_script_ratio: 0.5,
+            reroute_below_score: 0.8,
+        }
+    }
+
+    fn read(router: &RecognizerRouter) -> LineResult {
+        let mut data = vec![255_u8; 96 * 48 * 3];
+        for y in 12..36 {
+            data[(y * 96 + 16) * 3..(y * 96 + 80) * 3].fill(0);
+        }
+        let image = RecImage::from_bgr_u8(96, 48, data).expect("line image");
+        let mut out = router
+            .recognize(
+                &[image],
+                RecognizeOptions::default(),
+                None,
+                &mut Vec::new(),
+                &mut |_, _| Ok(()),
+            )
+            .expect("recognize");
+        out.lines.pop().expect("one line")
+    }
+
+    #[test]
+    fn reroute_keeps_the_most_confident_reading_in_its_own_script() {
+        let line = read(&router(
+            FixedReading("口口", 0.4),
+            vec![
+                (LangRec::Arabic, FixedReading("abc", 0.99)),
+                (LangRec::Cyrillic, FixedReading("Жук", 0.9)),
+                (LangRec::Korean, FixedReading("한국", 0.6)),
+            ],
+        ));
+        assert_eq!(
+            (line.text.as_str(), line.lang),
+            ("Жук", Some(LangRec::Cyrillic))
+        );
+
+        let line = read(&router(
+            FixedReading("口口", 0.7),
+            vec![(LangRec::Cyrillic, FixedReading("Жук", 0.5))],
+        ));
+        assert_eq!((line.text.as_str(), line.lang), ("口口", Some(LangRec::Ch)));
+
+        // Confident primary readings are never re-read.
+        let line = read(&router(
+            FixedReading("口口", 0.85),
+            vec![(LangRec::Cyrillic, FixedReading("Жук", 0.99))],
+        ));
+        assert_eq!(line.lang, Some(LangRec::Ch));
+    }
+}
@@ -12,16 +12,10 @@ use crate::{
     Quad,
     config::{RecImage, VisionBackend},
     error::{RapidOcrError, Result},
-    types::{LineResult, WordBox, WordInfo, WordType},
+    types::{LineResult, TextOrientation, WordBox, WordInfo, WordType},
     vision::backend::resolve_backend_strict,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Horizontal,
-    Vertical,
-}
-
 #[cfg(test)]
 pub fn compute_word_boxes(
     imgs: &[RecImage],
@@ -95,14 +89,19 @@ fn compute_word_boxes_unchecked(
             cal_ocr_word_box(&line.text, img_box, word_info, return_single_char_box);
 
         adjust_box_overlap(&mut word_boxes);
-        let direction = get_box_direction(*det_box);
-        let mapped =
-            reverse_rotate_crop_image_with_backend(*det_box, &word_boxes, direction, backend);
+        // Boxes were laid out along the rotated crop, so vertical lines are
+        // turned back before projecting onto the detection quad.
+        let mapped = reverse_rotate_crop_image_with_backend(
+            *det_box,
+            &word_boxes,
+            line.orientation,
+            backend,
+        );
 
         let item = word_content
             .into_iter()
             .zip(confs.into_iter().chain(std::iter::repeat(0.0)))
-            .zip(mapped.into_iter())
+            .zip(mapped)
             .map(|((text, score), bbox)| WordBox { text, score, bbox })
             .collect::<Vec<_>>();
 
@@ -112,29 +111,6 @@ fn compute_word_boxes_unchecked(
     out
 }
 
-fn get_box_direction(box_: Quad) -> Direction {
-    let edge_lengths = [
-        l2(box_[0], box_[1]),
-        l2(box_[1], box_[2]),
-        l2(box_[2], box_[3]),
-        l2(box_[3], box_[0]),
-    ];
-
-    let width = edge_lengths[0].max(edge_lengths[2]);
-    let height = edge_lengths[1].max(edge_lengths[3]);
-
-    if width < 1e-6 {
-        return Direction::Vertical;
-    }
-
-    let aspect_ratio = (height / width * 100.0).round() / 100.0;
-    if aspect_ratio >= 1.5 {
-        Direction::Vertical
-    } else {
-        Direction::Horizontal
-    }
-}
-
 fn cal_ocr_word_box(
     rec_txt: &str,
     bbox: Quad,
@@ -270,7 +246,7 @@ fn adjust_box_overlap(word_box_list: &mut [Quad]) {
 fn reverse_rotate_crop_image(
     bbox_points: Quad,
     word_points_list: &[Quad],
-    direction: Direction,
+    orientation: TextOrientation,
 ) -> Vec<Quad> {
     let left = bbox_points
         .iter()
@@ -308,7 +284,7 @@ fn reverse_rotate_crop_image(
 
         for (i, point) in word_points.iter().enumerate() {
             let mut p = *point;
-            if matches!(direction, Direction::Vertical) {
+            if orientation == TextOrientation::Vertical {
                 p = s_rotate(-90.0_f32.to_radians(), p[0], p[1], 0.0, 0.0);
                 p[0] += img_crop_width;
             }
@@ -329,24 +305,24 @@ fn reverse_rotate_crop_image(
 fn reverse_rotate_crop_image_with_backend(
     bbox_points: Quad,
     word_points_list: &[Quad],
-    direction: Direction,
+    orientation: TextOrientation,
     backend: VisionBackend,
 ) -> Vec<Quad> {
     match backend {
         VisionBackend::PureRust => {
-            reverse_rotate_crop_image(bbox_points, word_points_list, direction)
+            reverse_rotate_crop_image(bbox_points, word_points_list, orientation)
         }
         VisionBackend::OpenCv => {
             #[cfg(feature = "opencv-backend")]
             {
-                reverse_rotate_crop_image_opencv(bbox_points, word_points_list, direction)
+                reverse_rotate_crop_image_opencv(bbox_points, word_points_list, orientation)
                     .unwrap_or_else(|_| {
-                        reverse_rotate_crop_image(bbox_points, word_points_list, direction)
+                        reverse_rotate_crop_image(bbox_points, word_points_list, orientation)
                     })
             }
             #[cfg(not(feature = "opencv-backend"))]
             {
-                reverse_rotate_crop_image(bbox_points, word_points_list, direction)
+                reverse_rotate_crop_image(bbox_points, word_points_list, orientation)
             }
         }
     }
@@ -356,7 +332,7 @@ fn reverse_rotate_crop_image_with_backend(
 fn reverse_rotate_crop_image_opencv(
     bbox_points: Quad,
     word_points_list: &[Quad],
-    direction: Direction,
+    orientation: TextOrientation,
 ) -> Result<Vec<Quad>> {
     let left = bbox_points
         .iter()
@@ -435,7 +411,7 @@ fn reverse_rotate_crop_image_opencv(
 
         for (idx, point) in word_points.iter().enumerate() {
             let mut p = *point;
-            if matches!(direction, Direction::Vertical) {
+            if orientation == TextOrientation::Vertical {
                 p = s_rotate(-90.0_f32.to_radians(), p[0], p[1], 0.0, 0.0);
                 p[0] += img_crop_width as f32;
             }
@@ -563,7 +539,7 @@ mod tests {
     use crate::{
         config::RecImage,
         error::RapidOcrError,
-        types::{LineResult, WordInfo, WordType},
+        types::{LineResult, TextOrientation, WordInfo, WordType},
     };
 
     use super::compute_word_boxes;
@@ -581,6 +557,9 @@ mod tests {
                 line_txt_len: 4.0,
                 confs: vec![0.8, 0.9],
             }),
+            orientation: TextOrientation::Horizontal,
+            chars: None,
+            lang: None,
         };
         let det = [[[0.0, 0.0], [100.0, 0.0], [100.0, 20.0], [0.0, 20.0]]];
         let out =
@@ -590,6 +569,38 @@ mod tests {
         assert_eq!(out[0][0].text, "AB");
     }
 
+    #[test]
+    fn vertical_lines_map_char_boxes_down_the_column() {
+        // The recognizer saw the 20x100 column rotated into a 100x20 line.
+        let img = RecImage::from_bgr_u8(100, 20, vec![0; 100 * 20 * 3]).expect("valid image");
+        let line = LineResult {
+            text: "中文".to_string(),
+            score: 0.9,
+            word_info: Some(WordInfo {
+                words: vec![vec!["中".to_string()], vec!["文".to_string()]],
+                word_cols: vec![vec![1], vec![3]],
+                word_types: vec![WordType::Cn, WordType::Cn],
+                line_txt_len: 5.0,
+                confs: vec![0.8, 0.9],
+            }),
+            orientation: TextOrientation::Vertical,
+            chars: None,
+            lang: None,
+        };
+        let det = [[[0.0, 0.0], [20.0, 0.0], [20.0, 100.0], [0.0, 100.0]]];
+        let out =
+            compute_word_boxes(&[img], &det, &[line], true).expect("word boxes should compute");
+        let spans: Vec<(f32, f32, f32, f32)> = out[0]
+            .iter()
+            .map(|w| super::quad_to_rect_bbox(&w.bbox))
+            .collect();
+        assert_eq!(out[0][0].text, "中");
+        for (span, (y0, y1)) in spans.iter().zip([(5.0, 50.0), (50.0, 95.0)]) {
+            assert!((span.0 - 0.0).abs() < 1e-3 && (span.2 - 20.0).abs() < 1e-3);
+            assert!((span.1 - y0).abs() < 1e-3 && (span.3 - y1).abs() < 1e-3);
+        }
+    }
+
     #[test]
     fn compute_word_boxes_rejects_mismatch() {
         let img = RecImage::from_bgr_u8(100, 20, vec![0; 100 * 20 * 3]).expect("valid image");
@@ -597,6 +608,9 @@ mod tests {
             text: "AB".to_string(),
             score: 0.9,
             word_info: None,
+            orientation: TextOrientation::Horizontal,
+            chars: None,
+            lang: None,
         };
         let det = [[[0.0, 0.0], [100.0, 0.0], [100.0, 20.0], [0.0, 20.0]]];
 
@@ -614,6 +628,9 @@ mod tests {
                 text: "A".to_string(),
                 score: 1.0,
                 word_info: None,
+                orientation: TextOrientation::Horizontal,
+                chars: None,
+                lang: None,
             };
             let det = [[[0.0, 0.0], [20.0, 0.0], [20.0, 20.0], [0.0, 20.0]]];
             let err = super::compute_word_boxes_with_backend(
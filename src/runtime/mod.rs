@@ -1,2 +1,6 @@
 pub mod provider;
 pub mod session;
+#[cfg(feature = "onnxruntime")]
+mod session_pool;
+#[cfg(feature = "tract")]
+mod tract_session;
@@ -1,3 +1,4 @@
+#[cfg(feature = "onnxruntime")]
 use ort::execution_providers::{
     CANNExecutionProvider, CPUExecutionProvider, CUDAExecutionProvider, DirectMLExecutionProvider,
     ExecutionProvider, ExecutionProviderDispatch,
@@ -23,12 +24,14 @@ pub struct ProviderResolution {
     pub fallback_used: bool,
 }
 
+#[cfg(feature = "onnxruntime")]
 #[derive(Debug)]
 pub struct ProviderChain {
     pub providers: Vec<ExecutionProviderDispatch>,
     pub resolution: ProviderResolution,
 }
 
+#[cfg(feature = "onnxruntime")]
 pub fn resolve_execution_providers(
     preference: &ProviderPreference,
     enable_cpu_mem_arena: bool,
@@ -67,6 +70,7 @@ pub fn resolve_execution_providers(
     }
 }
 
+#[cfg(feature = "onnxruntime")]
 fn device_id_to_i32(provider_name: &str, device_id: usize) -> Result<i32> {
     i32::try_from(device_id).map_err(|_| {
         RapidOcrError::Config(format!(
@@ -75,6 +79,7 @@ fn device_id_to_i32(provider_name: &str, device_id: usize) -> Result<i32> {
     })
 }
 
+#[cfg(feature = "onnxruntime")]
 fn resolve_cuda_execution_providers(
     device_id: usize,
     cpu_provider: ExecutionProviderDispatch,
@@ -94,6 +99,7 @@ fn resolve_cuda_execution_providers(
     )
 }
 
+#[cfg(feature = "onnxruntime")]
 fn resolve_directml_execution_providers(
     device_id: usize,
     cpu_provider: ExecutionProviderDispatch,
@@ -113,6 +119,7 @@ fn resolve_directml_execution_providers(
     )
 }
 
+#[cfg(feature = "onnxruntime")]
 fn resolve_cann_execution_providers(
     device_id: usize,
     cpu_provider: ExecutionProviderDispatch,
@@ -132,6 +139,7 @@ fn resolve_cann_execution_providers(
     )
 }
 
+#[cfg(feature = "onnxruntime")]
 fn resolve_accelerator_execution_providers<F>(
     provider_name: &str,
     requested: ProviderPreference,
@@ -166,7 +174,7 @@ where
     }
 }
 
-fn decide_provider_resolution(
+pub(crate) fn decide_provider_resolution(
     requested: ProviderPreference,
     preferred: ResolvedExecutionProvider,
     preferred_is_available: bool,
@@ -207,12 +215,13 @@ fn format_provider_preference(preference: ProviderPreference) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{
-        ResolvedExecutionProvider, decide_provider_resolution, resolve_execution_providers,
-    };
+    #[cfg(feature = "onnxruntime")]
+    use super::resolve_execution_providers;
+    use super::{ResolvedExecutionProvider, decide_provider_resolution};
     use crate::config::ProviderPreference;
 
     #[test]
+    #[cfg(feature = "onnxruntime")]
     fn directml_preference_has_cpu_fallback() {
         let providers = resolve_execution_providers(
             &ProviderPreference::DirectMl { device_id: 0 },
@@ -227,6 +236,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "onnxruntime")]
     fn cuda_preference_has_cpu_fallback() {
         let providers =
             resolve_execution_providers(&ProviderPreference::Cuda { device_id: 0 }, false, false)
@@ -238,6 +248,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "onnxruntime")]
     fn cann_preference_has_cpu_fallback() {
         let providers =
             resolve_execution_providers(&ProviderPreference::Cann { device_id: 0 }, false, false)
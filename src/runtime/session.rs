@@ -1,7 +1,9 @@
-use std::path::Path;
-use std::thread;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "onnxruntime")]
+use std::{sync::Arc, thread};
 
 use ndarray::{ArrayView2, ArrayView3, ArrayView4, ArrayViewD, Ix2, Ix3, Ix4};
+#[cfg(feature = "onnxruntime")]
 use ort::{
     inputs,
     session::{Session, builder::GraphOptimizationLevel},
@@ -10,18 +12,88 @@ use ort::{
     value::ValueType,
 };
 
+#[cfg(not(any(feature = "onnxruntime", feature = "tract")))]
+compile_error!("rapid-ocr-rs needs an inference backend: enable `onnxruntime` or `tract`");
+
+#[cfg(feature = "onnxruntime")]
+use crate::runtime::{provider::resolve_execution_providers, session_pool::SessionPool};
+#[cfg(feature = "tract")]
+use crate::{
+    config::ProviderPreference,
+    runtime::{
+        provider::{ResolvedExecutionProvider, decide_provider_resolution},
+        tract_session::TractSession,
+    },
+};
 use crate::{
-    config::{RuntimeBackend, RuntimeConfig},
+    config::{ModelSource, RuntimeBackend, RuntimeConfig},
     error::{RapidOcrError, Result},
-    runtime::provider::{ProviderResolution, resolve_execution_providers},
+    model_store::verify_existing_file,
+    runtime::provider::ProviderResolution,
 };
 
+#[derive(Debug)]
+enum SessionEngine {
+    #[cfg(feature = "onnxruntime")]
+    Ort {
+        sessions: SessionPool<Session>,
+        model: OwnedModelData,
+        runtime_cfg: Box<RuntimeConfig>,
+        output_names: Vec<String>,
+    },
+    #[cfg(feature = "tract")]
+    Tract(Box<TractSession>),
+}
+
+// Where a session reads its ONNX graph from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ModelData<'a> {
+    Path(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+impl ModelData<'_> {
+    // Used in error messages in place of a file path.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            ModelData::Path(path) => path.display().to_string(),
+            ModelData::Bytes(bytes) => format!("<{} bytes in memory>", bytes.len()),
+        }
+    }
+}
+
+// Kept by an ONNX Runtime stage so it can open more sessions of the same model.
+#[cfg(feature = "onnxruntime")]
+#[derive(Debug)]
+enum OwnedModelData {
+    Path(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+#[cfg(feature = "onnxruntime")]
+impl OwnedModelData {
+    fn new(model: ModelData<'_>) -> Self {
+        match model {
+            ModelData::Path(path) => Self::Path(path.to_path_buf()),
+            ModelData::Bytes(bytes) => Self::Bytes(Arc::from(bytes)),
+        }
+    }
+
+    fn as_data(&self) -> ModelData<'_> {
+        match self {
+            Self::Path(path) => ModelData::Path(path),
+            Self::Bytes(bytes) => ModelData::Bytes(bytes),
+        }
+    }
+}
+
+// One loaded model on the configured `RuntimeBackend`; every backend honours
+// the same `run_array*_view_with` contract.
 #[derive(Debug)]
 pub struct OrtSession {
-    session: Session,
+    engine: SessionEngine,
     model_path: String,
     provider_resolution: ProviderResolution,
-    pub output_names: Vec<String>,
     pub character_list: Option<Vec<String>>,
 }
 
@@ -32,61 +104,143 @@ pub enum SessionContract {
     Det,
 }
 
-impl OrtSession {
-    pub fn new(model_path: &Path, runtime_cfg: &RuntimeConfig) -> Result<Self> {
-        Self::new_with_contract(model_path, runtime_cfg, SessionContract::Rec)
+impl SessionContract {
+    pub(crate) fn output_rank(self) -> usize {
+        match self {
+            SessionContract::Rec => 3,
+            SessionContract::Cls => 2,
+            SessionContract::Det => 4,
+        }
+    }
+
+    pub(crate) fn stage_name(self) -> &'static str {
+        match self {
+            SessionContract::Rec => "recognition",
+            SessionContract::Cls => "classification",
+            SessionContract::Det => "detection",
+        }
     }
+}
 
+impl OrtSession {
     pub fn new_with_contract(
         model_path: &Path,
         runtime_cfg: &RuntimeConfig,
         contract: SessionContract,
     ) -> Result<Self> {
-        if runtime_cfg.backend != RuntimeBackend::OnnxCpu {
-            return Err(RapidOcrError::UnsupportedBackend(format!(
-                "only `onnx_cpu` is supported in this release, got {:?}",
-                runtime_cfg.backend
-            )));
-        }
+        Self::load(ModelData::Path(model_path), runtime_cfg, contract)
+    }
 
-        let mut builder = Session::builder()?;
-        builder = builder.with_optimization_level(GraphOptimizationLevel::Level3)?;
+    // `registry_model` runs only for `ModelSource::Registry` and returns the
+    // downloaded file.
+    pub(crate) fn from_source(
+        source: &ModelSource,
+        runtime_cfg: &RuntimeConfig,
+        contract: SessionContract,
+        registry_model: impl FnOnce() -> Result<PathBuf>,
+    ) -> Result<Self> {
+        match source {
+            ModelSource::Registry => {
+                Self::new_with_contract(&registry_model()?, runtime_cfg, contract)
+            }
+            ModelSource::Path(path) => {
+                Self::new_with_contract(&verify_existing_file(path)?, runtime_cfg, contract)
+            }
+            ModelSource::Bytes(bytes) => {
+                Self::from_bytes_with_contract(bytes, runtime_cfg, contract)
+            }
+        }
+    }
 
-        let (intra_threads, inter_threads) = derive_runtime_threads(runtime_cfg);
+    pub fn from_bytes_with_contract(
+        model: &[u8],
+        runtime_cfg: &RuntimeConfig,
+        contract: SessionContract,
+    ) -> Result<Self> {
+        Self::load(ModelData::Bytes(model), runtime_cfg, contract)
+    }
 
-        if let Some(intra) = intra_threads {
-            builder = builder.with_intra_threads(intra)?;
+    pub(crate) fn load(
+        model: ModelData<'_>,
+        runtime_cfg: &RuntimeConfig,
+        contract: SessionContract,
+    ) -> Result<Self> {
+        match runtime_cfg.backend {
+            #[cfg(feature = "onnxruntime")]
+            RuntimeBackend::OnnxCpu => Self::new_ort(model, runtime_cfg, contract),
+            #[cfg(not(feature = "onnxruntime"))]
+            RuntimeBackend::OnnxCpu => Err(RapidOcrError::UnsupportedBackend(
+                "`onnx_cpu` requires building with the `onnxruntime` feature".to_string(),
+            )),
+            #[cfg(feature = "tract")]
+            RuntimeBackend::Tract => Self::new_tract(model, runtime_cfg, contract),
+            #[cfg(not(feature = "tract"))]
+            RuntimeBackend::Tract => Err(RapidOcrError::UnsupportedBackend(
+                "`tract` requires building with the `tract` feature".to_string(),
+            )),
         }
+    }
 
-        if let Some(inter) = inter_threads {
-            builder = builder.with_inter_threads(inter)?;
-        }
+    #[cfg(feature = "onnxruntime")]
+    fn new_ort(
+        model: ModelData<'_>,
+        runtime_cfg: &RuntimeConfig,
+        contract: SessionContract,
+    ) -> Result<Self> {
+        let (session, provider_resolution) = open_ort_session(model, runtime_cfg)?;
+        let model_path = model.label();
+        validate_model_io_contract(&model_path, &session, contract)?;
 
-        let provider_chain = resolve_execution_providers(
-            &runtime_cfg.provider_preference,
-            runtime_cfg.enable_cpu_mem_arena,
-            runtime_cfg.fail_if_provider_unavailable,
-        )?;
-        builder = builder.with_execution_providers(provider_chain.providers)?;
+        let output_names = session.outputs.iter().map(|v| v.name.clone()).collect();
 
-        let session = builder.commit_from_file(model_path)?;
-        validate_model_io_contract(model_path, &session, contract)?;
+        let character_list = session
+            .metadata()?
+            .custom("character")?
+            .and_then(|raw| parse_character_list(&raw));
 
-        let output_names = session.outputs.iter().map(|v| v.name.clone()).collect();
+        Ok(Self {
+            engine: SessionEngine::Ort {
+                sessions: SessionPool::new(session, runtime_cfg.max_sessions),
+                model: OwnedModelData::new(model),
+                runtime_cfg: Box::new(runtime_cfg.clone()),
+                output_names,
+            },
+            model_path,
+            provider_resolution,
+            character_list,
+        })
+    }
 
-        let character_list = match session.metadata()?.custom("character")? {
-            Some(raw) if !raw.trim().is_empty() => {
-                Some(raw.lines().map(|line| line.to_string()).collect())
+    // tract only runs on the CPU; other providers fall back to it unless
+    // `fail_if_provider_unavailable` is set.
+    #[cfg(feature = "tract")]
+    fn new_tract(
+        model: ModelData<'_>,
+        runtime_cfg: &RuntimeConfig,
+        contract: SessionContract,
+    ) -> Result<Self> {
+        let requested = runtime_cfg.provider_preference;
+        let provider_resolution = if requested == ProviderPreference::Cpu {
+            ProviderResolution {
+                requested,
+                resolved: ResolvedExecutionProvider::Cpu,
+                fallback_used: false,
             }
-            _ => None,
+        } else {
+            decide_provider_resolution(
+                requested,
+                ResolvedExecutionProvider::Cpu,
+                false,
+                runtime_cfg.fail_if_provider_unavailable,
+            )?
         };
 
+        let mut session = TractSession::new(model, contract)?;
         Ok(Self {
-            session,
-            model_path: model_path.display().to_string(),
-            provider_resolution: provider_chain.resolution,
-            output_names,
-            character_list,
+            model_path: model.label(),
+            provider_resolution,
+            character_list: session.character_list.take(),
+            engine: SessionEngine::Tract(Box::new(session)),
         })
     }
 
@@ -94,14 +248,47 @@ impl OrtSession {
         self.provider_resolution
     }
 
-    pub fn run_arrayd_view_with<T, F>(&mut self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
+    pub fn run_arrayd_view_with<T, F>(&self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
+    where
+        F: for<'a> FnOnce(ArrayViewD<'a, f32>) -> Result<T>,
+    {
+        match &self.engine {
+            #[cfg(feature = "onnxruntime")]
+            SessionEngine::Ort {
+                sessions,
+                model,
+                runtime_cfg,
+                output_names,
+            } => {
+                // `Session::run` needs `&mut self`; each concurrent caller runs
+                // on its own session, opened on first demand.
+                let mut session = sessions.checkout(|| {
+                    open_ort_session(model.as_data(), runtime_cfg).map(|(session, _)| session)
+                })?;
+                self.run_ort_view_with(&mut session, output_names, input, f)
+            }
+            #[cfg(feature = "tract")]
+            SessionEngine::Tract(session) => {
+                session.run_arrayd_view_with(&self.model_path, input, f)
+            }
+        }
+    }
+
+    #[cfg(feature = "onnxruntime")]
+    fn run_ort_view_with<T, F>(
+        &self,
+        session: &mut Session,
+        output_names: &[String],
+        input: ArrayView4<'_, f32>,
+        f: F,
+    ) -> Result<T>
     where
         F: for<'a> FnOnce(ArrayViewD<'a, f32>) -> Result<T>,
     {
         let input_tensor = TensorRef::from_array_view(input)?;
-        let outputs = self.session.run(inputs![input_tensor])?;
+        let outputs = session.run(inputs![input_tensor])?;
 
-        let output_name = self.output_names.first().ok_or_else(|| {
+        let output_name = output_names.first().ok_or_else(|| {
             RapidOcrError::Decode(format!(
                 "ONNX session has no output names (model={})",
                 self.model_path
@@ -123,7 +310,7 @@ impl OrtSession {
         f(arr.view())
     }
 
-    pub fn run_array2_view_with<T, F>(&mut self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
+    pub fn run_array2_view_with<T, F>(&self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
     where
         F: for<'a> FnOnce(ArrayView2<'a, f32>) -> Result<T>,
     {
@@ -139,7 +326,7 @@ impl OrtSession {
         })
     }
 
-    pub fn run_array3_view_with<T, F>(&mut self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
+    pub fn run_array3_view_with<T, F>(&self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
     where
         F: for<'a> FnOnce(ArrayView3<'a, f32>) -> Result<T>,
     {
@@ -155,7 +342,7 @@ impl OrtSession {
         })
     }
 
-    pub fn run_array4_view_with<T, F>(&mut self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
+    pub fn run_array4_view_with<T, F>(&self, input: ArrayView4<'_, f32>, f: F) -> Result<T>
     where
         F: for<'a> FnOnce(ndarray::ArrayView4<'a, f32>) -> Result<T>,
     {
@@ -172,6 +359,47 @@ impl OrtSession {
     }
 }
 
+#[cfg(feature = "onnxruntime")]
+fn open_ort_session(
+    model: ModelData<'_>,
+    runtime_cfg: &RuntimeConfig,
+) -> Result<(Session, ProviderResolution)> {
+    let mut builder = Session::builder()?;
+    builder = builder.with_optimization_level(GraphOptimizationLevel::Level3)?;
+
+    let (intra_threads, inter_threads) = derive_runtime_threads(runtime_cfg);
+
+    if let Some(intra) = intra_threads {
+        builder = builder.with_intra_threads(intra)?;
+    }
+
+    if let Some(inter) = inter_threads {
+        builder = builder.with_inter_threads(inter)?;
+    }
+
+    let provider_chain = resolve_execution_providers(
+        &runtime_cfg.provider_preference,
+        runtime_cfg.enable_cpu_mem_arena,
+        runtime_cfg.fail_if_provider_unavailable,
+    )?;
+    builder = builder.with_execution_providers(provider_chain.providers)?;
+
+    let session = match model {
+        ModelData::Path(path) => builder.commit_from_file(path)?,
+        ModelData::Bytes(bytes) => builder.commit_from_memory(bytes)?,
+    };
+    Ok((session, provider_chain.resolution))
+}
+
+// The `character` metadata entry PaddleOCR exports embed: one label per line.
+pub(crate) fn parse_character_list(raw: &str) -> Option<Vec<String>> {
+    if raw.trim().is_empty() {
+        return None;
+    }
+    Some(raw.lines().map(|line| line.to_string()).collect())
+}
+
+#[cfg(feature = "onnxruntime")]
 fn derive_runtime_threads(runtime_cfg: &RuntimeConfig) -> (Option<usize>, Option<usize>) {
     let mut intra = runtime_cfg.intra_threads.filter(|v| *v > 0);
     let mut inter = runtime_cfg.inter_threads.filter(|v| *v > 0);
@@ -189,6 +417,7 @@ fn derive_runtime_threads(runtime_cfg: &RuntimeConfig) -> (Option<usize>, Option
     (intra, inter)
 }
 
+#[cfg(feature = "onnxruntime")]
 fn auto_tuned_thread_budget() -> usize {
     let physical_cores = num_cpus::get_physical().max(1);
     let available = thread::available_parallelism()
@@ -198,28 +427,32 @@ fn auto_tuned_thread_budget() -> usize {
     available.clamp(1, physical_cores)
 }
 
+#[cfg(feature = "onnxruntime")]
 fn validate_model_io_contract(
-    model_path: &Path,
+    model_path: &str,
     session: &Session,
     contract: SessionContract,
 ) -> Result<()> {
     if session.inputs.len() != 1 {
         return Err(RapidOcrError::Config(format!(
-            "recognition model must expose exactly one input, got {} (model={})",
+            "{} model must expose exactly one input, got {} (model={})",
+            contract.stage_name(),
             session.inputs.len(),
-            model_path.display()
+            model_path
         )));
     }
     if session.outputs.is_empty() {
         return Err(RapidOcrError::Config(format!(
-            "recognition model must expose at least one output (model={})",
-            model_path.display()
+            "{} model must expose at least one output (model={})",
+            contract.stage_name(),
+            model_path
         )));
     }
 
     let input = &session.inputs[0];
     validate_tensor_spec(
         model_path,
+        contract,
         "input",
         &input.name,
         &input.input_type,
@@ -228,35 +461,34 @@ fn validate_model_io_contract(
     )?;
 
     let output = &session.outputs[0];
-    let output_rank = match contract {
-        SessionContract::Rec => Some(3),
-        SessionContract::Cls => Some(2),
-        SessionContract::Det => Some(4),
-    };
     validate_tensor_spec(
         model_path,
+        contract,
         "output",
         &output.name,
         &output.output_type,
-        output_rank,
+        Some(contract.output_rank()),
         TensorElementType::Float32,
     )?;
 
     Ok(())
 }
 
+#[cfg(feature = "onnxruntime")]
 fn validate_tensor_spec(
-    model_path: &Path,
+    model_path: &str,
+    contract: SessionContract,
     io_kind: &str,
     io_name: &str,
     value_type: &ValueType,
     expected_rank: Option<usize>,
     expected_tensor_type: TensorElementType,
 ) -> Result<()> {
+    let stage = contract.stage_name();
     if !value_type.is_tensor() {
         return Err(RapidOcrError::Config(format!(
-            "model {io_kind} `{io_name}` must be a tensor, got `{value_type}` (model={})",
-            model_path.display()
+            "{stage} model {io_kind} `{io_name}` must be a tensor, got `{value_type}` (model={})",
+            model_path
         )));
     }
 
@@ -268,26 +500,124 @@ fn validate_tensor_spec(
         && actual_rank != expected_rank
     {
         return Err(RapidOcrError::Config(format!(
-            "model {io_kind} `{io_name}` rank mismatch: expected {expected_rank}, got {actual_rank} (type={value_type}, model={})",
-            model_path.display()
+            "{stage} model {io_kind} `{io_name}` rank mismatch: expected {expected_rank}, got {actual_rank} (type={value_type}, model={})",
+            model_path
         )));
     }
 
     let actual_type = value_type.tensor_type().ok_or_else(|| {
         RapidOcrError::Config(format!(
-            "model {io_kind} `{io_name}` has no tensor element type (type={value_type}, model={})",
-            model_path.display()
+            "{stage} model {io_kind} `{io_name}` has no tensor element type (type={value_type}, model={})",
+            model_path
         ))
     })?;
 
     if actual_type != expected_tensor_type {
         return Err(RapidOcrError::Config(format!(
-            "model {io_kind} `{io_name}` dtype mismatch: expected {:?}, got {:?} (model={})",
-            expected_tensor_type,
-            actual_type,
-            model_path.display()
+            "{stage} model {io_kind} `{io_name}` dtype mismatch: expected {:?}, got {:?} (model={})",
+            expected_tensor_type, actual_type, model_path
         )));
     }
 
     Ok(())
 }
+
+#[cfg(all(test, feature = "tract"))]
+mod tests {
+    use std::path::PathBuf;
+
+    use ndarray::{Array4, ArrayD};
+
+    use super::{OrtSession, SessionContract};
+    use crate::config::{RuntimeBackend, RuntimeConfig};
+
+    // The fixtures under test/models are small enough to run on every test
+    // pass: det [n,3,h,w] -> [n,1,h,w], cls [n,3,48,192] -> [n,2] and
+    // rec [n,3,48,w] -> [n,w,4].
+    const TINY_MODELS: [(&str, SessionContract, [usize; 4]); 3] = [
+        ("tiny_det.onnx", SessionContract::Det, [1, 3, 32, 64]),
+        ("tiny_cls.onnx", SessionContract::Cls, [2, 3, 48, 192]),
+        ("tiny_rec.onnx", SessionContract::Rec, [1, 3, 48, 40]),
+    ];
+
+    fn run_tiny_model(
+        backend: RuntimeBackend,
+        file_name: &str,
+        contract: SessionContract,
+        shape: [usize; 4],
+    ) -> ArrayD<f32> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test/models")
+            .join(file_name);
+        let runtime_cfg = RuntimeConfig {
+            backend,
+            ..RuntimeConfig::default()
+        };
+        let session = OrtSession::new_with_contract(&path, &runtime_cfg, contract)
+            .unwrap_or_else(|e| panic!("load {file_name} on {backend:?}: {e}"));
+        // Deterministic values in [-1, 1) that vary across every axis.
+        let input = Array4::from_shape_fn(shape, |(n, c, y, x)| {
+            ((n * 7 + c * 13 + y * 31 + x * 17) % 64) as f32 / 32.0 - 1.0
+        });
+        session
+            .run_arrayd_view_with(input.view(), |out| Ok(out.to_owned()))
+            .unwrap_or_else(|e| panic!("run {file_name} on {backend:?}: {e}"))
+    }
+
+    #[test]
+    fn tract_runs_the_tiny_models() {
+        for (file_name, contract, shape) in TINY_MODELS {
+            let out = run_tiny_model(RuntimeBackend::Tract, file_name, contract, shape);
+            let expected: Vec<usize> = match contract {
+                SessionContract::Det => vec![shape[0], 1, shape[2], shape[3]],
+                SessionContract::Cls => vec![shape[0], 2],
+                SessionContract::Rec => vec![shape[0], shape[3], 4],
+            };
+            assert_eq!(out.shape(), expected.as_slice(), "{file_name}");
+            assert!(out.iter().all(|v| v.is_finite()), "{file_name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "onnxruntime")]
+    fn tract_matches_onnxruntime_on_the_tiny_models() {
+        for (file_name, contract, shape) in TINY_MODELS {
+            let tract = run_tiny_model(RuntimeBackend::Tract, file_name, contract, shape);
+            let ort = run_tiny_model(RuntimeBackend::OnnxCpu, file_name, contract, shape);
+            assert_eq!(tract.shape(), ort.shape(), "{file_name}");
+            let max_diff = tract
+                .iter()
+                .zip(ort.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0f32, f32::max);
+            assert!(
+                max_diff < 1e-4,
+                "{file_name}: max |tract - ort| = {max_diff}"
+            );
+        }
+    }
+
+    #[test]
+    fn contract_errors_name_the_stage() {
+        let models = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/models");
+        let runtime_cfg = RuntimeConfig {
+            backend: RuntimeBackend::Tract,
+            ..RuntimeConfig::default()
+        };
+        let cases = [
+            ("tiny_det.onnx", SessionContract::Cls, "classification"),
+            ("tiny_cls.onnx", SessionContract::Rec, "recognition"),
+            ("tiny_rec.onnx", SessionContract::Det, "detection"),
+        ];
+        for (file_name, contract, stage) in cases {
+            let err =
+                OrtSession::new_with_contract(&models.join(file_name), &runtime_cfg, contract)
+                    .expect_err("the output rank does not fit the contract");
+            assert!(
+                err.to_string()
+                    .contains(&format!("{stage} model output rank mismatch")),
+                "{err}"
+            );
+        }
+    }
+}
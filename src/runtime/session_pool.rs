@@ -0,0 +1,206 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Condvar, Mutex, PoisonError},
+    thread,
+};
+
+use crate::error::Result;
+
+// Sessions that can only run through `&mut self`, shared by checking one out
+// per call. A caller that finds no idle session builds another one, up to
+// `limit`; past that it waits for a session to be returned.
+#[derive(Debug)]
+pub(crate) struct SessionPool<S> {
+    state: Mutex<PoolState<S>>,
+    returned: Condvar,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+struct PoolState<S> {
+    idle: Vec<S>,
+    open: usize,
+}
+
+impl<S> SessionPool<S> {
+    pub(crate) fn new(first: S, limit: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(PoolState {
+                idle: vec![first],
+                open: 1,
+            }),
+            returned: Condvar::new(),
+            limit,
+        }
+    }
+
+    // The pool state is only touched under short critical sections that cannot
+    // panic half-way, so a poisoned lock still holds a consistent state.
+    pub(crate) fn checkout(
+        &self,
+        build: impl FnOnce() -> Result<S>,
+    ) -> Result<PooledSession<'_, S>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(session) = state.idle.pop() {
+                return Ok(PooledSession {
+                    pool: self,
+                    session: Some(session),
+                });
+            }
+            if self.limit.is_none_or(|limit| state.open < limit) {
+                break;
+            }
+            state = self
+                .returned
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        state.open += 1;
+        drop(state);
+
+        match build() {
+            Ok(session) => Ok(PooledSession {
+                pool: self,
+                session: Some(session),
+            }),
+            Err(err) => {
+                self.close_one();
+                Err(err)
+            }
+        }
+    }
+
+    fn give_back(&self, session: S) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.idle.push(session);
+        drop(state);
+        self.returned.notify_one();
+    }
+
+    fn close_one(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.open -= 1;
+        drop(state);
+        self.returned.notify_one();
+    }
+}
+
+// A checked-out session. It goes back to the pool on drop, unless the run
+// panicked, in which case it is discarded and the pool may build a new one.
+pub(crate) struct PooledSession<'a, S> {
+    pool: &'a SessionPool<S>,
+    session: Option<S>,
+}
+
+impl<S> Deref for PooledSession<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.session
+            .as_ref()
+            .expect("pooled session present until drop")
+    }
+}
+
+impl<S> DerefMut for PooledSession<'_, S> {
+    fn deref_mut(&mut self) -> &mut S {
+        self.session
+            .as_mut()
+            .expect("pooled session present until drop")
+    }
+}
+
+impl<S> Drop for PooledSession<'_, S> {
+    fn drop(&mut self) {
+        let Some(session) = self.session.take() else {
+            return;
+        };
+        if thread::panicking() {
+            drop(session);
+            self.pool.close_one();
+        } else {
+            self.pool.give_back(session);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc, Barrier,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::SessionPool;
+    use crate::error::RapidOcrError;
+
+    #[test]
+    fn concurrent_checkouts_build_extra_sessions_and_reuse_them() {
+        let threads = 4;
+        let pool = SessionPool::new(0_usize, None);
+        let built = AtomicUsize::new(1);
+        let barrier = Barrier::new(threads);
+
+        let results = std::thread::scope(|scope| {
+            let handles = (0..threads)
+                .map(|_| {
+                    let (pool, built, barrier) = (&pool, &built, &barrier);
+                    scope.spawn(move || {
+                        let session = pool
+                            .checkout(|| Ok(built.fetch_add(1, Ordering::SeqCst)))
+                            .expect("checkout");
+                        // Every thread holds its session at once, so none can share.
+                        barrier.wait();
+                        *session
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("worker"))
+                .collect::<Vec<_>>()
+        });
+
+        let mut ids = results;
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(built.load(Ordering::SeqCst), threads);
+
+        let again = pool
+            .checkout(|| panic!("idle sessions must be reused"))
+            .expect("checkout");
+        assert!(*again < threads);
+    }
+
+    #[test]
+    fn limit_makes_callers_wait_for_a_returned_session() {
+        let pool = Arc::new(SessionPool::new(7_u32, Some(1)));
+        let held = pool.checkout(|| unreachable!()).expect("checkout");
+
+        let waiter = {
+            let pool = Arc::clone(&pool);
+            std::thread::spawn(move || {
+                *pool
+                    .checkout(|| panic!("limit reached, must wait"))
+                    .expect("checkout")
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        drop(held);
+        assert_eq!(waiter.join().expect("waiter"), 7);
+    }
+
+    #[test]
+    fn failed_build_frees_its_slot() {
+        let pool = SessionPool::new(1_u32, Some(2));
+        let _held = pool.checkout(|| unreachable!()).expect("checkout");
+        let err = pool
+            .checkout(|| Err(RapidOcrError::Decode("boom".to_string())))
+            .err()
+            .expect("build error");
+        assert!(matches!(err, RapidOcrError::Decode(_)));
+        assert_eq!(*pool.checkout(|| Ok(2)).expect("checkout"), 2);
+    }
+}
@@ -0,0 +1,120 @@
+use ndarray::{ArrayView4, ArrayViewD, IxDyn};
+use tract_onnx::prelude::*;
+
+use crate::{
+    error::{RapidOcrError, Result},
+    runtime::session::{ModelData, SessionContract, parse_character_list},
+};
+
+// Pure-Rust inference through tract. The graph is optimized once for the
+// model's symbolic batch/height/width dims, so no per-shape recompilation.
+#[derive(Debug)]
+pub(crate) struct TractSession {
+    plan: TypedRunnableModel<TypedModel>,
+    pub(crate) character_list: Option<Vec<String>>,
+}
+
+impl TractSession {
+    pub(crate) fn new(model: ModelData<'_>, contract: SessionContract) -> Result<Self> {
+        let onnx = tract_onnx::onnx();
+        let proto = match model {
+            ModelData::Path(path) => onnx.proto_model_for_path(path)?,
+            ModelData::Bytes(mut bytes) => onnx.proto_model_for_read(&mut bytes)?,
+        };
+        let character_list = proto
+            .metadata_props
+            .iter()
+            .find(|prop| prop.key == "character")
+            .and_then(|prop| parse_character_list(&prop.value));
+
+        let typed = onnx.model_for_proto_model(&proto)?.into_typed()?;
+        validate_model_io_contract(&model.label(), &typed, contract)?;
+        let plan = typed.into_optimized()?.into_runnable()?;
+
+        Ok(Self {
+            plan,
+            character_list,
+        })
+    }
+
+    pub(crate) fn run_arrayd_view_with<T, F>(
+        &self,
+        model_path: &str,
+        input: ArrayView4<'_, f32>,
+        f: F,
+    ) -> Result<T>
+    where
+        F: for<'a> FnOnce(ArrayViewD<'a, f32>) -> Result<T>,
+    {
+        let input = match input.as_slice() {
+            Some(data) => Tensor::from_shape(input.shape(), data)?,
+            None => Tensor::from_shape(input.shape(), &input.iter().copied().collect::<Vec<_>>())?,
+        };
+        let outputs = self.plan.run(tvec!(input.into()))?;
+        let output = outputs.first().ok_or_else(|| {
+            RapidOcrError::Decode(format!(
+                "tract run returned no outputs (model={model_path})"
+            ))
+        })?;
+        let data = output.as_slice::<f32>().map_err(|e| {
+            RapidOcrError::Decode(format!(
+                "failed to extract output as f32 tensor (model={model_path}): {e}"
+            ))
+        })?;
+        let arr = ArrayViewD::from_shape(IxDyn(output.shape()), data).map_err(|e| {
+            RapidOcrError::Decode(format!(
+                "tract output shape does not match its data (model={model_path}): {e}"
+            ))
+        })?;
+        f(arr)
+    }
+}
+
+fn validate_model_io_contract(
+    model_path: &str,
+    model: &TypedModel,
+    contract: SessionContract,
+) -> Result<()> {
+    let inputs = model.input_outlets()?;
+    if inputs.len() != 1 {
+        return Err(RapidOcrError::Config(format!(
+            "{} model must expose exactly one input, got {} (model={})",
+            contract.stage_name(),
+            inputs.len(),
+            model_path
+        )));
+    }
+    let outputs = model.output_outlets()?;
+    let Some(output) = outputs.first() else {
+        return Err(RapidOcrError::Config(format!(
+            "{} model must expose at least one output (model={})",
+            contract.stage_name(),
+            model_path
+        )));
+    };
+
+    let specs = [
+        ("input", inputs[0], 4),
+        ("output", *output, contract.output_rank()),
+    ];
+    for (io_kind, outlet, expected_rank) in specs {
+        let fact = model.outlet_fact(outlet)?;
+        if fact.rank() != expected_rank {
+            return Err(RapidOcrError::Config(format!(
+                "{} model {io_kind} rank mismatch: expected {expected_rank}, got {} (model={})",
+                contract.stage_name(),
+                fact.rank(),
+                model_path
+            )));
+        }
+        if fact.datum_type != f32::datum_type() {
+            return Err(RapidOcrError::Config(format!(
+                "{} model {io_kind} dtype mismatch: expected F32, got {:?} (model={})",
+                contract.stage_name(),
+                fact.datum_type,
+                model_path
+            )));
+        }
+    }
+    Ok(())
+}
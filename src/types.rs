@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crate::Quad;
+use crate::{Quad, config::LangRec};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WordType {
@@ -19,11 +19,44 @@ pub struct WordInfo {
     pub confs: Vec<f32>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharAlternative {
+    pub text: String,
+    pub prob: f32,
+}
+
+// One decoded character: the CTC timesteps `[start, end)` it spans, its
+// probability and the next most likely classes at its emitting timestep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharDetail {
+    pub text: String,
+    pub prob: f32,
+    pub start: usize,
+    pub end: usize,
+    pub alternatives: Vec<CharAlternative>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextOrientation {
+    #[default]
+    Horizontal,
+    // The crop was taller than wide and rotated 90 degrees for recognition.
+    Vertical,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineResult {
     pub text: String,
     pub score: f32,
     pub word_info: Option<WordInfo>,
+    #[serde(default)]
+    pub orientation: TextOrientation,
+    // Set when `RecognizeOptions::char_top_k` is; in logical (not display) order.
+    #[serde(default)]
+    pub chars: Option<Vec<CharDetail>>,
+    // Recognition model that produced the line.
+    #[serde(default)]
+    pub lang: Option<LangRec>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -0,0 +1,73 @@
+use wasm_bindgen::{Clamped, prelude::*};
+
+use crate::{
+    config::{ModelSource, RuntimeBackend, RuntimeConfig, VisionBackend},
+    input::image_loader::OcrInput,
+    pipeline::{config::EngineConfig, rapid_ocr::RapidOcr, types::OcrCallOptions},
+};
+
+// Browser entry point: models come in as bytes (e.g. from `fetch`) and images
+// as canvas `ImageData`. Every stage runs on tract and the pure-Rust vision
+// backend whatever the config says.
+#[wasm_bindgen]
+pub struct WasmOcr {
+    engine: RapidOcr,
+}
+
+#[wasm_bindgen]
+impl WasmOcr {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        det: Vec<u8>,
+        cls: Option<Vec<u8>>,
+        rec: Vec<u8>,
+        rec_keys: Option<String>,
+        config_yaml: Option<String>,
+    ) -> Result<WasmOcr, JsError> {
+        let mut config = match config_yaml {
+            Some(yaml) => EngineConfig::from_yaml_str(&yaml)?,
+            None => EngineConfig::default(),
+        };
+        for runtime in [
+            &mut config.det.runtime,
+            &mut config.cls.runtime,
+            &mut config.rec.runtime,
+        ] {
+            force_wasm_runtime(runtime);
+        }
+        config.det.model_source = ModelSource::from(det);
+        config.rec.model.model_source = ModelSource::from(rec);
+        config.rec.model.rec_keys_text = rec_keys;
+        match cls {
+            Some(cls) => config.cls.model_source = ModelSource::from(cls),
+            None => config.global.use_cls = false,
+        }
+        // There is no registry to download from in the browser.
+        config.global.use_doc_orientation = false;
+        config.multi_rec.langs.clear();
+        Ok(Self {
+            engine: RapidOcr::new(config)?,
+        })
+    }
+
+    // `rgba` is `ImageData.data`; returns the `OcrJsonItem` list as JSON.
+    pub fn run(
+        &mut self,
+        rgba: Clamped<Vec<u8>>,
+        width: usize,
+        height: usize,
+    ) -> Result<String, JsError> {
+        let input = OcrInput::RgbaU8 {
+            width,
+            height,
+            data: rgba.0,
+        };
+        let output = self.engine.run(input, OcrCallOptions::default())?;
+        Ok(serde_json::to_string(&output.to_json()?)?)
+    }
+}
+
+fn force_wasm_runtime(runtime: &mut RuntimeConfig) {
+    runtime.backend = RuntimeBackend::Tract;
+    runtime.vision_backend = VisionBackend::PureRust;
+}